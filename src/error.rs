@@ -13,6 +13,8 @@ pub enum SquadError {
     NotRentExempt,
     #[error("Squad already exists")]
     SquadAlreadyExists,
+    #[error("Squad has no room for more members")]
+    SquadFull,
 }
 
 impl From<SquadError> for ProgramError {
@@ -0,0 +1,527 @@
+//! Vote tallying rules shared by the processors, free of any account handling
+//! so clients can predict proposal outcomes with the exact on-chain math
+
+use solana_program::program_error::ProgramError;
+
+use crate::{
+    state::{
+        proposal::{Proposal, ProposalStatus, ProposalType},
+        squad::{AllocationType, Squad},
+    },
+    UnixTimestamp,
+};
+
+/// Basis points in a whole, 10_000 bps = 100%
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Whether `part` is at least `bps` basis points of `whole`, exact for any u64 amounts
+pub fn meets_bps(part: u64, whole: u64, bps: u16) -> bool {
+    whole > 0 && part as u128 * BPS_DENOMINATOR as u128 >= whole as u128 * bps as u128
+}
+
+// smallest part of `whole` that meets `bps`, u128::div_ceil isn't on the BPF toolchain
+#[allow(clippy::manual_div_ceil)]
+fn bps_of(whole: u64, bps: u16) -> u64 {
+    let denominator = BPS_DENOMINATOR as u128;
+    ((whole as u128 * bps as u128 + denominator - 1) / denominator) as u64
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Pending,
+    Passed,
+    Rejected,
+    // can't be executed anymore, voting or the execution window closed
+    Expired,
+}
+
+/// The squad thresholds a proposal is tallied against
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SquadParams {
+    pub allocation_type: u8,
+    // members counted for the Team quorum, members holding the voter role for a Multisig
+    pub members: u64,
+    // Team quorum and support in basis points
+    pub quorum_bps: u16,
+    pub support_bps: u16,
+    // Multisig approvals needed
+    pub threshold: u8,
+    // Team quorum measured by vote weight against the supply instead of by voters,
+    // used by token-gated squads
+    pub weighted_quorum: bool,
+    // Team core council approvals needed on top of support and quorum
+    pub core_threshold: u8,
+}
+
+impl SquadParams {
+    /// Current thresholds of the squad for the proposal, vault policies included,
+    /// counting the members the proposal was created with
+    pub fn live(squad: &Squad, proposal: &Proposal) -> Self {
+        let members_at_creation = proposal.members_at_creation as u64;
+        if squad.allocation_type == AllocationType::Multisig as u8 {
+            SquadParams {
+                allocation_type: squad.allocation_type,
+                members: if members_at_creation > 0 {
+                    members_at_creation
+                } else {
+                    squad.voter_count() as u64
+                },
+                quorum_bps: 0,
+                support_bps: 0,
+                threshold: squad.proposal_threshold(proposal),
+                weighted_quorum: false,
+                core_threshold: 0,
+            }
+        } else {
+            SquadParams {
+                allocation_type: squad.allocation_type,
+                members: if members_at_creation > 0 {
+                    members_at_creation
+                } else {
+                    squad.members.len() as u64
+                },
+                quorum_bps: squad.quorum_bps(),
+                support_bps: squad.proposal_support_bps(proposal),
+                threshold: 0,
+                weighted_quorum: squad.token_gated,
+                core_threshold: squad.proposal_core_threshold(proposal),
+            }
+        }
+    }
+
+    /// Thresholds frozen on the proposal once it was approved,
+    /// the live ones until then
+    pub fn at_execute(squad: &Squad, proposal: &Proposal) -> Self {
+        let mut params = SquadParams::live(squad, proposal);
+        if proposal.is_approved() {
+            if params.allocation_type == AllocationType::Multisig as u8 {
+                params.threshold = proposal.threshold_at_execute;
+            } else {
+                params.members = proposal.members_at_execute as u64;
+            }
+        }
+        params
+    }
+}
+
+/// Outcome of the proposal votes against the squad thresholds at `now`,
+/// `supply` is the governance token supply of a Team and is ignored for a Multisig
+pub fn evaluate(
+    proposal: &Proposal,
+    params: &SquadParams,
+    supply: u64,
+    now: UnixTimestamp,
+) -> Outcome {
+    if expired(proposal, params, now) {
+        Outcome::Expired
+    } else if proposal.veto_threshold > 0 {
+        evaluate_optimistic(proposal, params, supply, now)
+    } else if params.allocation_type == AllocationType::Multisig as u8 {
+        evaluate_multisig(proposal, params)
+    } else {
+        evaluate_team(proposal, params, supply)
+    }
+}
+
+/// Moves the proposal status after a vote was tallied to `outcome`, a draft
+/// becomes active with its first vote and the approval is only recorded once
+pub fn record_outcome(
+    proposal: &mut Proposal,
+    outcome: Outcome,
+    now: UnixTimestamp,
+) -> Result<(), ProgramError> {
+    if proposal.status == ProposalStatus::Draft {
+        proposal.set_status(ProposalStatus::Active)?;
+    }
+    match outcome {
+        Outcome::Rejected => proposal.set_status(ProposalStatus::Rejected),
+        // the execution window of the squad starts with the approval
+        Outcome::Passed if !proposal.is_approved() => proposal.approve(now),
+        Outcome::Passed | Outcome::Pending => Ok(()),
+        Outcome::Expired => proposal.set_status(ProposalStatus::Expired),
+    }
+}
+
+// a Multisig proposal expires when its voting closes before it was approved or
+// when its execution window closes, an optimistic one counts the window from its close
+fn expired(proposal: &Proposal, params: &SquadParams, now: UnixTimestamp) -> bool {
+    if proposal.status == ProposalStatus::Expired {
+        return true;
+    }
+    if proposal.is_closed() {
+        return false;
+    }
+    if proposal.execute_before > 0 {
+        return now > proposal.execute_before;
+    }
+    if proposal.veto_threshold > 0 {
+        return proposal.execution_window > 0
+            && now
+                > proposal
+                    .close_timestamp
+                    .saturating_add(proposal.execution_window);
+    }
+    params.allocation_type == AllocationType::Multisig as u8
+        && !proposal.is_approved()
+        && now > proposal.close_timestamp
+}
+
+// an optimistic proposal passes once its voting closed without enough vetoes
+fn evaluate_optimistic(
+    proposal: &Proposal,
+    params: &SquadParams,
+    supply: u64,
+    now: UnixTimestamp,
+) -> Outcome {
+    let veto_votes = proposal.votes.get(1).copied().unwrap_or(0);
+    let vetoed = if params.allocation_type == AllocationType::Multisig as u8 {
+        veto_votes >= proposal.veto_threshold as u64
+    } else {
+        meets_bps(veto_votes, supply, proposal.veto_threshold as u16 * 100)
+    };
+    if vetoed {
+        return Outcome::Rejected;
+    }
+    if now > proposal.close_timestamp {
+        return Outcome::Passed;
+    }
+    Outcome::Pending
+}
+
+fn evaluate_multisig(proposal: &Proposal, params: &SquadParams) -> Outcome {
+    let pass_votes = proposal.votes.first().copied().unwrap_or(0);
+    let fail_votes = proposal.votes.get(1).copied().unwrap_or(0);
+    let threshold = params.threshold as u64;
+
+    if pass_votes >= threshold {
+        return Outcome::Passed;
+    }
+    // reject once the remaining voters can't reach the threshold anymore
+    let possible_votes_left = params.members.saturating_sub(pass_votes + fail_votes);
+    if threshold > possible_votes_left + pass_votes {
+        return Outcome::Rejected;
+    }
+    Outcome::Pending
+}
+
+fn evaluate_team(proposal: &Proposal, params: &SquadParams, supply: u64) -> Outcome {
+    let total_votes: u64 = proposal.votes.iter().sum();
+    let possible_votes_left = supply.saturating_sub(total_votes);
+    let quorum_ready = if params.weighted_quorum {
+        meets_bps(total_votes, supply, params.quorum_bps)
+    } else {
+        meets_bps(
+            proposal.has_voted.len() as u64,
+            params.members,
+            params.quorum_bps,
+        )
+    };
+
+    if proposal.proposal_type == ProposalType::Text as u8 {
+        // a text proposal passes once its leading option can't be caught up
+        let (most_index, most_votes) = leading_option(&proposal.votes);
+        let second_most_votes = proposal
+            .votes
+            .iter()
+            .enumerate()
+            .filter(|(ind, _)| *ind != most_index)
+            .map(|(_, votes)| *votes)
+            .max()
+            .unwrap_or(0);
+        if most_votes > second_most_votes + possible_votes_left
+            && quorum_ready
+            && meets_bps(most_votes, supply, params.support_bps)
+        {
+            return Outcome::Passed;
+        }
+        return Outcome::Pending;
+    }
+
+    let pass_votes = proposal.votes.first().copied().unwrap_or(0);
+    let fail_votes = proposal.votes.get(1).copied().unwrap_or(0);
+    // close the proposal once reject can't be caught up
+    if fail_votes > pass_votes + possible_votes_left {
+        return Outcome::Rejected;
+    }
+    if pass_votes >= fail_votes
+        && quorum_ready
+        && meets_bps(pass_votes, supply, params.support_bps)
+        && proposal.core_votes >= params.core_threshold
+    {
+        return Outcome::Passed;
+    }
+    Outcome::Pending
+}
+
+// index and votes of the option with the most votes, the first one on a tie
+fn leading_option(votes: &[u64]) -> (usize, u64) {
+    votes.iter().enumerate().fold(
+        (0, 0),
+        |max, (ind, &val)| if val > max.1 { (ind, val) } else { max },
+    )
+}
+
+/// Approvals still missing for the proposal to pass: votes for a Multisig,
+/// governance token weight for a Team, 0 for an optimistic proposal
+pub fn votes_needed(proposal: &Proposal, params: &SquadParams, supply: u64) -> u64 {
+    if proposal.veto_threshold > 0 {
+        return 0;
+    }
+    let pass_votes = if params.allocation_type != AllocationType::Multisig as u8
+        && proposal.proposal_type == ProposalType::Text as u8
+    {
+        leading_option(&proposal.votes).1
+    } else {
+        proposal.votes.first().copied().unwrap_or(0)
+    };
+    let needed = if params.allocation_type == AllocationType::Multisig as u8 {
+        params.threshold as u64
+    } else {
+        bps_of(supply, params.support_bps)
+    };
+    needed.saturating_sub(pass_votes)
+}
+
+/// Members that still have to vote for a Team proposal to reach quorum,
+/// 0 for a Multisig, a quorum weighed by tokens or an optimistic proposal
+pub fn voters_needed(proposal: &Proposal, params: &SquadParams) -> u64 {
+    if params.allocation_type == AllocationType::Multisig as u8
+        || params.weighted_quorum
+        || proposal.veto_threshold > 0
+    {
+        return 0;
+    }
+    bps_of(params.members, params.quorum_bps).saturating_sub(proposal.has_voted.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::{program_pack::Pack, pubkey::Pubkey};
+
+    fn proposal(proposal_type: ProposalType, votes: Vec<u64>, voters: usize) -> Proposal {
+        let proposal_arr = vec![0; Proposal::get_packed_len()];
+        let mut proposal_info = Proposal::unpack_unchecked(&proposal_arr).unwrap();
+        proposal_info.proposal_type = proposal_type as u8;
+        proposal_info.votes = votes;
+        proposal_info.has_voted = (0..voters).map(|_| Pubkey::new_unique()).collect();
+        proposal_info
+    }
+
+    fn team(members: u64, quorum_bps: u16, support_bps: u16) -> SquadParams {
+        SquadParams {
+            allocation_type: AllocationType::TeamCoordination as u8,
+            members,
+            quorum_bps,
+            support_bps,
+            threshold: 0,
+            weighted_quorum: false,
+            core_threshold: 0,
+        }
+    }
+
+    #[test]
+    fn governance_bps_math() {
+        // as f32 16_777_216 / 33_554_433 rounds to exactly 50%
+        assert!(!meets_bps(16_777_216, 33_554_433, 5_000));
+        assert!(meets_bps(16_777_217, 33_554_433, 4_999));
+        assert!(meets_bps(u64::MAX / 2 + 1, u64::MAX, 5_000));
+        assert!(!meets_bps(0, 0, 0));
+        assert_eq!(bps_of(33_554_433, 5_000), 16_777_217);
+    }
+
+    #[test]
+    fn governance_multisig_outcomes() {
+        let params = SquadParams {
+            allocation_type: AllocationType::Multisig as u8,
+            members: 3,
+            quorum_bps: 0,
+            support_bps: 0,
+            threshold: 2,
+            weighted_quorum: false,
+            core_threshold: 0,
+        };
+        let pending = proposal(ProposalType::WithdrawSol, vec![1, 0], 1);
+        assert_eq!(evaluate(&pending, &params, 0, 0), Outcome::Pending);
+        assert_eq!(votes_needed(&pending, &params, 0), 1);
+
+        let passed = proposal(ProposalType::WithdrawSol, vec![2, 1], 3);
+        assert_eq!(evaluate(&passed, &params, 0, 0), Outcome::Passed);
+
+        let rejected = proposal(ProposalType::WithdrawSol, vec![0, 2], 2);
+        assert_eq!(evaluate(&rejected, &params, 0, 0), Outcome::Rejected);
+    }
+
+    #[test]
+    fn governance_team_outcomes() {
+        let params = team(4, 5_000, 5_000);
+        // support is there but only 1 of 4 members voted
+        let no_quorum = proposal(ProposalType::Support, vec![60, 0], 1);
+        assert_eq!(evaluate(&no_quorum, &params, 100, 0), Outcome::Pending);
+        assert_eq!(voters_needed(&no_quorum, &params), 1);
+
+        let passed = proposal(ProposalType::Support, vec![60, 10], 2);
+        assert_eq!(evaluate(&passed, &params, 100, 0), Outcome::Passed);
+
+        let rejected = proposal(ProposalType::Support, vec![10, 60], 2);
+        assert_eq!(evaluate(&rejected, &params, 100, 0), Outcome::Rejected);
+
+        let short = proposal(ProposalType::Support, vec![40, 10], 2);
+        assert_eq!(evaluate(&short, &params, 100, 0), Outcome::Pending);
+        assert_eq!(votes_needed(&short, &params, 100), 10);
+
+        // text proposals pass on an unreachable lead
+        let text = proposal(ProposalType::Text, vec![10, 55, 5], 3);
+        assert_eq!(evaluate(&text, &params, 100, 0), Outcome::Passed);
+    }
+
+    #[test]
+    fn governance_weighted_quorum() {
+        let mut params = team(4, 5_000, 5_000);
+        params.weighted_quorum = true;
+        // a single large depositor reaches quorum on their own
+        let whale = proposal(ProposalType::Support, vec![60, 0], 1);
+        assert_eq!(evaluate(&whale, &params, 100, 0), Outcome::Passed);
+        assert_eq!(voters_needed(&whale, &params), 0);
+
+        // every member voted but too little of the deposits
+        let small = proposal(ProposalType::Support, vec![30, 10], 4);
+        assert_eq!(evaluate(&small, &params, 100, 0), Outcome::Pending);
+    }
+
+    #[test]
+    fn governance_core_threshold() {
+        let mut params = team(4, 5_000, 5_000);
+        params.core_threshold = 2;
+        let mut sensitive = proposal(ProposalType::RemoveMember, vec![80, 0], 3);
+        sensitive.core_votes = 1;
+        assert_eq!(evaluate(&sensitive, &params, 100, 0), Outcome::Pending);
+
+        sensitive.core_votes = 2;
+        assert_eq!(evaluate(&sensitive, &params, 100, 0), Outcome::Passed);
+    }
+
+    #[test]
+    fn governance_multisig_expiry() {
+        let params = SquadParams {
+            allocation_type: AllocationType::Multisig as u8,
+            members: 3,
+            quorum_bps: 0,
+            support_bps: 0,
+            threshold: 2,
+            weighted_quorum: false,
+            core_threshold: 0,
+        };
+        let mut stale = proposal(ProposalType::WithdrawSol, vec![1, 0], 1);
+        stale.close_timestamp = 100;
+        assert_eq!(evaluate(&stale, &params, 0, 100), Outcome::Pending);
+        assert_eq!(evaluate(&stale, &params, 0, 101), Outcome::Expired);
+
+        // an approved proposal has its execution window
+        let mut approved = proposal(ProposalType::WithdrawSol, vec![2, 0], 2);
+        approved.close_timestamp = 100;
+        approved.execution_window = 50;
+        approved.approve(80).unwrap();
+        assert_eq!(evaluate(&approved, &params, 0, 130), Outcome::Passed);
+        assert_eq!(evaluate(&approved, &params, 0, 131), Outcome::Expired);
+    }
+
+    #[test]
+    fn governance_scheduled_execution() {
+        let params = SquadParams {
+            allocation_type: AllocationType::Multisig as u8,
+            members: 3,
+            quorum_bps: 0,
+            support_bps: 0,
+            threshold: 2,
+            weighted_quorum: false,
+            core_threshold: 0,
+        };
+        // approved at 80, the execution window runs from execute_after to 250
+        let mut scheduled = proposal(ProposalType::WithdrawSol, vec![2, 0], 2);
+        scheduled.close_timestamp = 100;
+        scheduled.execute_after = 200;
+        scheduled.execution_window = 50;
+        scheduled.approve(80).unwrap();
+        assert_eq!(scheduled.execute_before, 250);
+
+        // too early, passed but time-locked
+        assert_eq!(evaluate(&scheduled, &params, 0, 199), Outcome::Passed);
+        assert!(scheduled.is_time_locked(199));
+        // inside the window
+        for now in [200, 225, 250] {
+            assert_eq!(evaluate(&scheduled, &params, 0, now), Outcome::Passed);
+            assert!(!scheduled.is_time_locked(now));
+        }
+        // too late
+        assert_eq!(evaluate(&scheduled, &params, 0, 251), Outcome::Expired);
+
+        // approved after execute_after, the window starts with the approval
+        let mut late = proposal(ProposalType::WithdrawSol, vec![2, 0], 2);
+        late.close_timestamp = 400;
+        late.execute_after = 200;
+        late.execution_window = 50;
+        late.approve(300).unwrap();
+        assert!(!late.is_time_locked(300));
+        assert_eq!(evaluate(&late, &params, 0, 350), Outcome::Passed);
+        assert_eq!(evaluate(&late, &params, 0, 351), Outcome::Expired);
+
+        // no execution window, only the time-lock applies
+        let mut open_ended = proposal(ProposalType::WithdrawSol, vec![2, 0], 2);
+        open_ended.close_timestamp = 100;
+        open_ended.execute_after = 200;
+        open_ended.approve(80).unwrap();
+        assert!(open_ended.is_time_locked(199));
+        assert!(!open_ended.is_time_locked(10_000));
+        assert_eq!(evaluate(&open_ended, &params, 0, 10_000), Outcome::Passed);
+
+        // the address book delay locks the proposal without scheduling it for keepers
+        let mut unlisted = proposal(ProposalType::WithdrawSol, vec![2, 0], 2);
+        unlisted.close_timestamp = 100;
+        unlisted.unlisted_until = 300;
+        unlisted.execution_window = 50;
+        unlisted.approve(80).unwrap();
+        assert_eq!(unlisted.execute_after, 0);
+        assert_eq!(unlisted.execute_before, 350);
+        assert!(unlisted.is_time_locked(299));
+        assert!(!unlisted.is_time_locked(300));
+        assert_eq!(evaluate(&unlisted, &params, 0, 350), Outcome::Passed);
+        assert_eq!(evaluate(&unlisted, &params, 0, 351), Outcome::Expired);
+    }
+
+    #[test]
+    fn governance_optimistic_veto() {
+        let params = team(4, 5_000, 5_000);
+        let mut optimistic = proposal(ProposalType::WithdrawSol, vec![0, 10], 1);
+        optimistic.veto_threshold = 20;
+        optimistic.close_timestamp = 100;
+        assert_eq!(evaluate(&optimistic, &params, 100, 100), Outcome::Pending);
+        assert_eq!(evaluate(&optimistic, &params, 100, 101), Outcome::Passed);
+        assert_eq!(votes_needed(&optimistic, &params, 100), 0);
+
+        // approvals don't matter, enough vetoes reject it even after the window
+        optimistic.votes = vec![90, 20];
+        assert_eq!(evaluate(&optimistic, &params, 100, 101), Outcome::Rejected);
+    }
+
+    #[test]
+    fn governance_optimistic_backdated_start() {
+        let params = team(4, 5_000, 5_000);
+        // a Team creator picks the start, one long past still gets the whole window
+        let mut backdated = proposal(ProposalType::WithdrawSol, vec![0, 0], 0);
+        backdated.start_timestamp = 1_000;
+        backdated.make_optimistic(20, 86_400, 500_000);
+        assert_eq!(backdated.close_timestamp, 586_400);
+        assert_eq!(
+            evaluate(&backdated, &params, 100, 500_001),
+            Outcome::Pending
+        );
+        assert_eq!(evaluate(&backdated, &params, 100, 586_401), Outcome::Passed);
+
+        // a later start delays the window
+        let mut scheduled = proposal(ProposalType::WithdrawSol, vec![0, 0], 0);
+        scheduled.start_timestamp = 600_000;
+        scheduled.make_optimistic(20, 86_400, 500_000);
+        assert_eq!(scheduled.close_timestamp, 686_400);
+    }
+}
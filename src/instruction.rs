@@ -28,7 +28,7 @@ pub enum SquadInstruction {
     /// ACCOUNTS EXPECTED
     /// 1. [signer] - the user creating the squad/payer/initial member
     /// 2. [writable] - the account address for the new squad
-    /// 3. [] - the mint address for the new squad
+    /// 3. [] - the mint address for the new squad, the existing governance mint if token gated
     /// 4. [] - the member marker mint account
     /// 5. [] - the token program account
     /// 6. [] - the system program account
@@ -42,6 +42,13 @@ pub enum SquadInstruction {
         description: String,
         token: String,
         random_id: String,
+        // optional, 0 to use the whole percent settings
+        vote_support_bps: u16,
+        vote_quorum_bps: u16,
+        // optional, members deposit an existing mint instead of being minted equity
+        token_gated: bool,
+        // optional, governance tokens a token-gated member has to keep deposited
+        min_deposit: u64,
     },
 
     /// ACCOUNTS EXPECTED
@@ -80,6 +87,9 @@ pub enum SquadInstruction {
     /// 4. [] - the system account
     /// 5. [] - the rent sys var account
     /// 6. [] - the squad program account
+    /// 7. [...] - the accounts specific to the proposal type, then the squad config
+    ///    account (PDA of squad) once the squad has one, then the address book account
+    ///    (PDA of squad) once the squad has one
     CreateProposalAccount {
         proposal_type: u8,
         votes_num: u8,
@@ -91,6 +101,10 @@ pub enum SquadInstruction {
         close_timestamp: UnixTimestamp,
         amount: u64,
         minimum_out: u64,
+        vesting_cliff: i64,
+        vault_index: u8,
+        execute_after: UnixTimestamp,
+        token_symbol: String,
     },
 
     /// ACCOUNTS EXPECTED
@@ -103,6 +117,13 @@ pub enum SquadInstruction {
     /// 7. [] - the system program account
     /// 8. [] - the rent sysvar account
     /// 9. [] - the squads program account
+    /// 10. [writable] - the member checkpoints account (PDA of squad, member),
+    ///     written to lock the deposit of a token-gated squad
+    /// 11. [] - the supply checkpoints account (PDA of squad, squad governance mint)
+    ///
+    /// Accounts 10 and 11 were added with the vote-weight checkpoints and are optional
+    /// except in a token-gated squad, without them the vote weighs the live equity
+    /// balance against the live supply
     CastVote { vote: u8 },
 
     /// ACCOUNTS EXPECTED
@@ -116,6 +137,8 @@ pub enum SquadInstruction {
     /// 8. [] - the token program account
     /// 8. [] - the associated token program account
     /// 9. [] - the rent sysvar account
+    /// 10. [...] - the accounts specific to the proposal type, then last the proposal
+    ///     vault (writable) when a keeper executes it in a squad paying keeper tips
     ExecuteProposal { random_id: String },
 
     /// ACCOUNTS EXPECTED - DEPRECATED
@@ -146,7 +169,125 @@ pub enum SquadInstruction {
     /// 7. [] - the token program account
     /// 8. [] - the associated token program account
     /// 9. [] - the rent sysvar account
+    /// 10. [...] - the accounts specific to the proposal type, then last the proposal
+    ///     vault (writable) when a keeper executes it in a squad paying keeper tips
     ExecuteMultisigProposal { random_id: String },
+
+    /// ACCOUNTS EXPECTED
+    /// 1. [signer] - the signer of the transaction, and the wallet address of the grant member
+    /// 2. [writable] - the squad account
+    /// 3. [writable] - the squad governance mint account
+    /// 4. [writable] - the vesting grant account (PDA)
+    /// 5. [writable] - the members governance PDA
+    /// 6. [] - the token program account
+    /// 7. [] - the system program account
+    /// 8. [] - the rent sysvar account
+    /// 9. [writable] - the member checkpoints account (PDA of squad, member)
+    /// 10. [writable] - the supply checkpoints account (PDA of squad, squad governance mint)
+    ClaimVested,
+
+    /// ACCOUNTS EXPECTED
+    /// 1. [signer] - the signer of the transaction, and the stream recipient
+    /// 2. [] - the squad account
+    /// 3. [writable] - the stream account (PDA)
+    /// 4. [writable] - the destination, a wallet for SOL streams or a token account for SPL streams
+    /// 5. [] - the token program account
+    /// 6. [writable] - the stream token account, for SPL streams only
+    WithdrawStream,
+
+    /// Only invoked by executing a parent CreateChildSquad proposal, which signs as the parent vault
+    /// ACCOUNTS EXPECTED
+    /// 1. [signer] - the payer, drafts the members of a Team child
+    /// 2. [writable] - the parent squad account
+    /// 3. [signer] - the parent squad sol_account (!squadsol)
+    /// 4. [writable] - the account address for the new child squad
+    /// 5. [] - the system program account
+    /// 6. [] - the rent sysvar account
+    ///
+    /// Team Coordination only
+    /// 7. [writable] - the mint address for the new child squad
+    /// 8. [] - the token program account
+    ///
+    /// Multisig only
+    /// 7. [...] - initial members key, the parent vault is added on top
+    CreateChildSquad {
+        allocation_type: u8,
+        vote_support: u8,
+        vote_quorum: u8,
+        core_threshold: u8,
+        squad_name: String,
+        description: String,
+        token: String,
+        random_id: String,
+        members_num: u8,
+    },
+
+    /// ACCOUNTS EXPECTED
+    /// 1. [signer] - the depositor, owner of the source token account
+    /// 2. [writable] - the token-gated squad account
+    /// 3. [] - the squad governance mint account
+    /// 4. [writable] - the source token account
+    /// 5. [writable] - the members governance PDA, created on the first deposit
+    /// 6. [] - the squad mint PDA, authority of the governance PDAs
+    /// 7. [writable] - the member checkpoints account (PDA of squad, member)
+    /// 8. [writable] - the supply checkpoints account (PDA of squad, squad governance mint)
+    /// 9. [] - the token program account
+    /// 10. [] - the system program account
+    /// 11. [] - the rent sysvar account
+    DepositGovernance { amount: u64 },
+
+    /// ACCOUNTS EXPECTED
+    /// 1. [signer] - the member withdrawing
+    /// 2. [writable] - the token-gated squad account
+    /// 3. [] - the squad governance mint account
+    /// 4. [writable] - the destination token account
+    /// 5. [writable] - the members governance PDA
+    /// 6. [] - the squad mint PDA, authority of the governance PDAs
+    /// 7. [writable] - the member checkpoints account (PDA of squad, member)
+    /// 8. [writable] - the supply checkpoints account (PDA of squad, squad governance mint)
+    /// 9. [] - the token program account
+    /// 10. [] - the system program account
+    /// 11. [] - the rent sysvar account
+    WithdrawGovernance { amount: u64 },
+
+    /// Tallies multisig approvals signed off-chain over the proposal approval digest,
+    /// verified by Ed25519 program instructions placed before this one
+    /// ACCOUNTS EXPECTED
+    /// 1. [] - the multisig squad account
+    /// 2. [writable] - the proposal account (PDA)
+    /// 3. [] - the instructions sysvar account
+    ApproveWithSignatures,
+
+    /// Approves a multisig proposal and executes it in the same instruction
+    /// once the approval reaches the threshold
+    /// ACCOUNTS EXPECTED
+    /// 1. [signer] - the signer of the transaction, the member approving and executing
+    /// 2. [writable] - the squad account
+    /// 3. [writable] - the proposal account (PDA)
+    /// 4. [writable] - the vote record account
+    /// 5. [writable?] - the execution source account
+    /// 6. [] - the execution destination account
+    /// 7. [] - the system program account
+    /// 8. [] - the token program account
+    /// 9. [] - the associated token program account
+    /// 10. [] - the rent sysvar account
+    /// 11. [] - the squads program account
+    /// 12. [...] - the accounts specific to executing the proposal type
+    ApproveAndExecute { random_id: String },
+
+    /// Closes an expired multisig proposal and returns its rent to the creator
+    /// ACCOUNTS EXPECTED
+    /// 1. [] - the multisig squad account
+    /// 2. [writable] - the proposal account (PDA)
+    /// 3. [writable] - the proposal creator account
+    CloseProposal,
+
+    /// Cancels a proposal nobody voted on yet, only its creator can cancel it
+    /// ACCOUNTS EXPECTED
+    /// 1. [signer] - the proposal creator
+    /// 2. [] - the squad account
+    /// 3. [writable] - the proposal account (PDA)
+    CancelProposal,
 }
 
 impl SquadInstruction {
@@ -164,6 +305,10 @@ impl SquadInstruction {
                 description: Self::unpack_squad_description(&rest[28..64])?,
                 token: Self::unpack_squad_token(&rest[64..70])?,
                 random_id: Self::unpack_squad_random_id(&rest[70..80])?,
+                vote_support_bps: Self::unpack_squad_bps(rest, 80)?,
+                vote_quorum_bps: Self::unpack_squad_bps(rest, 82)?,
+                token_gated: Self::unpack_squad_token_gated(rest, 84)?,
+                min_deposit: Self::unpack_squad_min_deposit(rest, 85)?,
             },
 
             1 => Self::CreateMultisig {
@@ -186,6 +331,10 @@ impl SquadInstruction {
                 close_timestamp: Self::unpack_proposal_close(&rest[810..818])?,
                 amount: Self::unpack_proposal_amount_in(rest)?,
                 minimum_out: Self::unpack_proposal_amount_out(rest)?,
+                vesting_cliff: Self::unpack_proposal_vesting_cliff(rest)?,
+                vault_index: Self::unpack_proposal_vault_index(rest)?,
+                execute_after: Self::unpack_proposal_execute_after(rest)?,
+                token_symbol: Self::unpack_proposal_token_symbol(rest)?,
             },
 
             // Proposal vote (private squad)
@@ -211,6 +360,39 @@ impl SquadInstruction {
 
             // Deprecated
             // 8 => Self::QuitSquad,
+
+            // mint the vested part of a grant
+            9 => Self::ClaimVested,
+
+            // pull the accrued part of a stream
+            10 => Self::WithdrawStream,
+
+            // same layout as CreateSquad, with the multisig members count at the end
+            11 => Self::CreateChildSquad {
+                allocation_type: rest[0],
+                vote_support: rest[1],
+                vote_quorum: rest[2],
+                core_threshold: rest[3],
+                squad_name: Self::unpack_squad_name(&rest[4..28])?,
+                description: Self::unpack_squad_description(&rest[28..64])?,
+                token: Self::unpack_squad_token(&rest[64..70])?,
+                random_id: Self::unpack_squad_random_id(&rest[70..80])?,
+                members_num: rest[80],
+            },
+
+            // token-gated squads only
+            12 => Self::DepositGovernance {
+                amount: Self::unpack_amount(rest)?,
+            },
+            13 => Self::WithdrawGovernance {
+                amount: Self::unpack_amount(rest)?,
+            },
+            14 => Self::ApproveWithSignatures,
+            15 => Self::ApproveAndExecute {
+                random_id: Self::unpack_wsol_random_id(rest)?,
+            },
+            16 => Self::CloseProposal,
+            17 => Self::CancelProposal,
             _ => return Err(InvalidInstruction.into()),
         })
     }
@@ -235,6 +417,30 @@ impl SquadInstruction {
         Ok(random_id)
     }
 
+    fn unpack_squad_bps(input: &[u8], offset: usize) -> Result<u16, ProgramError> {
+        let mut bps: [u8; 2] = [0; 2];
+        if input.len() >= offset + 2 {
+            bps = input[offset..offset + 2].try_into().unwrap();
+        }
+        Ok(u16::from_le_bytes(bps))
+    }
+
+    fn unpack_squad_token_gated(input: &[u8], offset: usize) -> Result<bool, ProgramError> {
+        match input.get(offset) {
+            None | Some(0) => Ok(false),
+            Some(1) => Ok(true),
+            _ => Err(InvalidInstruction.into()),
+        }
+    }
+
+    fn unpack_squad_min_deposit(input: &[u8], offset: usize) -> Result<u64, ProgramError> {
+        let mut amount: [u8; 8] = [0; 8];
+        if input.len() >= offset + 8 {
+            amount = input[offset..offset + 8].try_into().unwrap();
+        }
+        Ok(u64::from_le_bytes(amount))
+    }
+
     fn unpack_wsol_random_id(input: &[u8]) -> Result<String, ProgramError> {
         let mut string: String = String::from("0000000000000000");
         if input.len() >= 16 {
@@ -293,6 +499,43 @@ impl SquadInstruction {
         }
         Ok(u64::from_le_bytes(amount_out))
     }
+    fn unpack_proposal_vesting_cliff(input: &[u8]) -> Result<i64, ProgramError> {
+        let mut vesting_cliff: [u8; 8] = [0; 8];
+        if input.len() >= 842 {
+            vesting_cliff = input[834..842].try_into().unwrap();
+        }
+        Ok(i64::from_le_bytes(vesting_cliff))
+    }
+    fn unpack_proposal_vault_index(input: &[u8]) -> Result<u8, ProgramError> {
+        let mut vault_index: u8 = 0;
+        if input.len() >= 843 {
+            vault_index = input[842];
+        }
+        Ok(vault_index)
+    }
+    fn unpack_proposal_execute_after(input: &[u8]) -> Result<i64, ProgramError> {
+        let mut execute_after: [u8; 8] = [0; 8];
+        if input.len() >= 851 {
+            execute_after = input[843..851].try_into().unwrap();
+        }
+        Ok(i64::from_le_bytes(execute_after))
+    }
+    fn unpack_proposal_token_symbol(input: &[u8]) -> Result<String, ProgramError> {
+        let mut token_symbol = String::new();
+        if input.len() >= 861 {
+            token_symbol = String::from_utf8(input[851..861].to_vec()).unwrap();
+        }
+        Ok(token_symbol)
+    }
+
+    fn unpack_amount(input: &[u8]) -> Result<u64, ProgramError> {
+        let amount = input
+            .get(..8)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u64::from_le_bytes)
+            .ok_or(InvalidInstruction)?;
+        Ok(amount)
+    }
 
     fn unpack_add_members_allocation_table(input: &[u8]) -> Result<Vec<u64>, ProgramError> {
         let members_num = input[0];
@@ -1,16 +1,30 @@
 pub mod error;
+pub mod governance;
 pub mod instruction;
 pub mod processor;
 pub mod state;
 
+use num_traits::FromPrimitive;
 use solana_program::{
     account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program::invoke_signed,
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
+    system_instruction::transfer,
 };
 
-use crate::state::{proposal::Proposal, squad::Squad, vote::VoteReceipt};
+use crate::state::{
+    address_book::AddressBook,
+    checkpoint::Checkpoints,
+    config::SquadConfig,
+    proposal::{Proposal, ProposalType},
+    squad::Squad,
+    stream::Stream,
+    vesting::Vesting,
+    vote::VoteReceipt,
+};
 
 #[cfg(not(feature = "no-entrypoint"))]
 pub mod entrypoint;
@@ -93,6 +107,174 @@ pub(crate) fn get_vote(
     Ok(vote_account_info)
 }
 
+/// Get the Vesting account info after check of ownership
+pub(crate) fn get_vesting(
+    program_id: &Pubkey,
+    squad_account: &AccountInfo,
+    vesting_account: &AccountInfo,
+) -> Result<Vesting, ProgramError> {
+    if squad_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if vesting_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let vesting_account_info = Vesting::unpack_unchecked(&vesting_account.data.borrow())?;
+
+    if vesting_account_info.is_initialized
+        && vesting_account_info.squad_address != *squad_account.key
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(vesting_account_info)
+}
+
+/// Get the Stream account info after check of ownership
+pub(crate) fn get_stream(
+    program_id: &Pubkey,
+    squad_account: &AccountInfo,
+    stream_account: &AccountInfo,
+) -> Result<Stream, ProgramError> {
+    if squad_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if stream_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let stream_account_info = Stream::unpack_unchecked(&stream_account.data.borrow())?;
+
+    if stream_account_info.is_initialized && stream_account_info.squad_address != *squad_account.key
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(stream_account_info)
+}
+
+/// Get the Checkpoints account info after check of ownership
+pub(crate) fn get_checkpoints(
+    program_id: &Pubkey,
+    squad_account: &AccountInfo,
+    checkpoints_account: &AccountInfo,
+) -> Result<Checkpoints, ProgramError> {
+    if squad_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if checkpoints_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let checkpoints_account_info =
+        Checkpoints::unpack_unchecked(&checkpoints_account.data.borrow())?;
+
+    if checkpoints_account_info.is_initialized
+        && checkpoints_account_info.squad_address != *squad_account.key
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(checkpoints_account_info)
+}
+
+/// Get the SquadConfig account info after check of ownership
+pub(crate) fn get_config(
+    program_id: &Pubkey,
+    squad_account: &AccountInfo,
+    config_account: &AccountInfo,
+) -> Result<SquadConfig, ProgramError> {
+    if squad_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let config_account_info = SquadConfig::unpack_unchecked(&config_account.data.borrow())?;
+
+    if config_account_info.is_initialized && config_account_info.squad_address != *squad_account.key
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(config_account_info)
+}
+
+/// Get the AddressBook account info after check of ownership
+pub(crate) fn get_address_book(
+    program_id: &Pubkey,
+    squad_account: &AccountInfo,
+    address_book_account: &AccountInfo,
+) -> Result<AddressBook, ProgramError> {
+    if squad_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if address_book_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let address_book_account_info =
+        AddressBook::unpack_unchecked(&address_book_account.data.borrow())?;
+
+    if address_book_account_info.is_initialized
+        && address_book_account_info.squad_address != *squad_account.key
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(address_book_account_info)
+}
+
+/// Get an SPL token mint after check of ownership
+pub(crate) fn get_token_mint(
+    mint_account: &AccountInfo,
+) -> Result<spl_token::state::Mint, ProgramError> {
+    if *mint_account.owner != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    spl_token::state::Mint::unpack(&mint_account.data.borrow())
+}
+
+/// Get an SPL token account after check of ownership
+pub(crate) fn get_token_account(
+    token_account: &AccountInfo,
+) -> Result<spl_token::state::Account, ProgramError> {
+    if *token_account.owner != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    spl_token::state::Account::unpack(&token_account.data.borrow())
+}
+
+/// Whether the vault of a withdrawal proposal holds less than the proposal moves
+pub(crate) fn vault_is_short(
+    proposal: &Proposal,
+    source_account: &AccountInfo,
+) -> Result<bool, ProgramError> {
+    match FromPrimitive::from_u8(proposal.proposal_type) {
+        Some(ProposalType::WithdrawSol) => {
+            Ok(source_account.lamports() < proposal.execution_amount)
+        }
+        Some(ProposalType::WithdrawSpl) => {
+            let source_token_account =
+                spl_token::state::Account::unpack(&source_account.data.borrow())?;
+            Ok(source_token_account.amount < proposal.execution_amount)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Authority of an upgradeable loader buffer, None for any other account or a buffer
+/// that was made immutable
+pub(crate) fn get_buffer_authority(buffer_data: &[u8]) -> Option<Pubkey> {
+    // bincode layout of UpgradeableLoaderState::Buffer: u32 tag 1, then the Option tag
+    match buffer_data.get(0..37) {
+        Some([1, 0, 0, 0, 1, authority @ ..]) => Some(Pubkey::new(authority)),
+        _ => None,
+    }
+}
+
 /// Get the Squad Mint address from the squad address with the bump seed
 pub(crate) fn get_squad_address_with_seed(
     creator_address: &Pubkey,
@@ -105,6 +287,22 @@ pub(crate) fn get_squad_address_with_seed(
     )
 }
 
+/// Get a child squad address from the parent squad address and child index with the bump seed
+pub(crate) fn get_child_squad_address_with_seed(
+    parent_address: &Pubkey,
+    child_index: u32,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &parent_address.to_bytes(),
+            &child_index.to_le_bytes(),
+            b"!child",
+        ],
+        program_id,
+    )
+}
+
 /// Get the Squad Mint address from the squad address with the bump seed
 pub(crate) fn get_mint_address_with_seed(
     squad_address: &Pubkey,
@@ -132,6 +330,75 @@ pub(crate) fn get_sol_address_with_seed(
     Pubkey::find_program_address(&[&squad_address.to_bytes(), b"!squadsol"], &program_id)
 }
 
+/// Get a Squad vault address from the squad address and vault index with the bump seed,
+/// vault 0 is the original sol address
+pub(crate) fn get_vault_address_with_seed(
+    squad_address: &Pubkey,
+    vault_index: u8,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    if vault_index == 0 {
+        return get_sol_address_with_seed(squad_address, program_id);
+    }
+    Pubkey::find_program_address(
+        &[&squad_address.to_bytes(), &[vault_index], b"!vault"],
+        program_id,
+    )
+}
+
+/// Get the signer seeds of a Squad vault for invoke_signed
+pub(crate) fn get_vault_signer_seeds(
+    squad_address: &Pubkey,
+    vault_index: u8,
+    bump_seed: u8,
+) -> Vec<Vec<u8>> {
+    if vault_index == 0 {
+        return vec![
+            squad_address.to_bytes().to_vec(),
+            b"!squadsol".to_vec(),
+            vec![bump_seed],
+        ];
+    }
+    vec![
+        squad_address.to_bytes().to_vec(),
+        vec![vault_index],
+        b"!vault".to_vec(),
+        vec![bump_seed],
+    ]
+}
+
+/// Pay a keeper executing a proposal up to `tip` lamports from the proposal vault
+pub(crate) fn pay_keeper_tip<'a>(
+    squad_address: &Pubkey,
+    proposal: &Proposal,
+    vault_account: &AccountInfo<'a>,
+    keeper: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    tip: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let (vault_address, vault_bump_seed) =
+        get_vault_address_with_seed(squad_address, proposal.vault_index, program_id);
+    if *vault_account.key != vault_address {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let tip = tip.min(vault_account.lamports());
+    if tip == 0 {
+        return Ok(());
+    }
+    let vault_seeds = get_vault_signer_seeds(squad_address, proposal.vault_index, vault_bump_seed);
+    let vault_seeds_refs: Vec<&[u8]> = vault_seeds.iter().map(|seed| seed.as_slice()).collect();
+    invoke_signed(
+        &transfer(&vault_address, keeper.key, tip),
+        &[
+            vault_account.clone(),
+            keeper.clone(),
+            system_program_account.clone(),
+        ],
+        &[&vault_seeds_refs],
+    )
+}
+
 /// Get the Squad wsol address from the sol address with the bump seed
 pub(crate) fn get_wsol_address_with_seed(
     sol_address: &Pubkey,
@@ -229,6 +496,72 @@ pub(crate) fn get_vote_address_with_seed(
         &program_id,
     )
 }
+/// Get the vesting grant address created by a proposal with the bump seed
+pub(crate) fn get_vesting_address_with_seed(
+    proposal_address: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&proposal_address.to_bytes(), b"!vesting"], program_id)
+}
+/// Get the balance history address of a member, or of the squad mint, with the bump seed
+pub(crate) fn get_checkpoints_address_with_seed(
+    squad_address: &Pubkey,
+    owner_address: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &squad_address.to_bytes(),
+            &owner_address.to_bytes(),
+            b"!checkpoint",
+        ],
+        program_id,
+    )
+}
+/// Get the squad config extension address with the bump seed
+pub(crate) fn get_config_address_with_seed(
+    squad_address: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&squad_address.to_bytes(), b"!config"], program_id)
+}
+/// Get the squad address book address with the bump seed
+pub(crate) fn get_address_book_address_with_seed(
+    squad_address: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&squad_address.to_bytes(), b"!addressbook"], program_id)
+}
+/// Get the payment stream address created by a proposal with the bump seed
+pub(crate) fn get_stream_address_with_seed(
+    proposal_address: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&proposal_address.to_bytes(), b"!stream"], program_id)
+}
+/// Get the stake account address created or split off by a proposal with the bump seed
+pub(crate) fn get_stake_address_with_seed(
+    proposal_address: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&proposal_address.to_bytes(), b"!stake"], program_id)
+}
+/// Get the address of a mint created by a squad (CreateMint) with the bump seed
+pub(crate) fn get_token_mint_address_with_seed(
+    squad_address: &Pubkey,
+    mint_index: u32,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &squad_address.to_bytes(),
+            &mint_index.to_le_bytes(),
+            b"!mint",
+        ],
+        program_id,
+    )
+}
+
 // GET THE SQUAD ADDRESS ONLY
 pub fn get_squad_address(
     creator_address: &Pubkey,
@@ -238,6 +571,15 @@ pub fn get_squad_address(
     get_squad_address_with_seed(&creator_address, &random_id, program_id).0
 }
 
+/// Derive a child squad address, a parent's children are indexed 0..child_index
+pub fn get_child_squad_address(
+    parent_address: &Pubkey,
+    child_index: u32,
+    program_id: &Pubkey,
+) -> Pubkey {
+    get_child_squad_address_with_seed(parent_address, child_index, program_id).0
+}
+
 /// Derive the SPL Token mint address associated with a squad account
 pub fn get_mint_address(squad_address: &Pubkey, program_id: &Pubkey) -> Pubkey {
     get_mint_address_with_seed(&squad_address, &program_id).0
@@ -252,6 +594,11 @@ pub fn get_sol_address(squad_address: &Pubkey, program_id: &Pubkey) -> Pubkey {
     get_sol_address_with_seed(&squad_address, &program_id).0
 }
 
+/// Derive a Squad vault address, vault 0 being the Squad SOL address
+pub fn get_vault_address(squad_address: &Pubkey, vault_index: u8, program_id: &Pubkey) -> Pubkey {
+    get_vault_address_with_seed(squad_address, vault_index, program_id).0
+}
+
 /// Derive the Squad WrappedSOL address associated with a sol account
 pub fn get_wsol_address(sol_address: &Pubkey, random_id: &String, program_id: &Pubkey) -> Pubkey {
     get_wsol_address_with_seed(&sol_address, random_id, &program_id).0
@@ -297,3 +644,45 @@ pub fn get_vote_address(
 ) -> Pubkey {
     get_vote_address_with_seed(&proposal_account, &program_id, &voter_address).0
 }
+
+/// Derive the vesting grant address created by a proposal
+pub fn get_vesting_address(proposal_address: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    get_vesting_address_with_seed(proposal_address, program_id).0
+}
+
+/// Derive the payment stream address created by a proposal
+pub fn get_stream_address(proposal_address: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    get_stream_address_with_seed(proposal_address, program_id).0
+}
+
+/// Derive the address of the mint_index mint created by a squad
+pub fn get_token_mint_address(
+    squad_address: &Pubkey,
+    mint_index: u32,
+    program_id: &Pubkey,
+) -> Pubkey {
+    get_token_mint_address_with_seed(squad_address, mint_index, program_id).0
+}
+
+/// Derive the stake account address created or split off by a proposal
+pub fn get_stake_address(proposal_address: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    get_stake_address_with_seed(proposal_address, program_id).0
+}
+
+pub fn get_checkpoints_address(
+    squad_address: &Pubkey,
+    owner_address: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    get_checkpoints_address_with_seed(squad_address, owner_address, program_id).0
+}
+
+/// Derive the squad config extension address
+pub fn get_config_address(squad_address: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    get_config_address_with_seed(squad_address, program_id).0
+}
+
+/// Derive the squad address book address
+pub fn get_address_book_address(squad_address: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    get_address_book_address_with_seed(squad_address, program_id).0
+}
@@ -1,25 +1,63 @@
 /* SQUADS PROCESSOR */
 
 mod process_add_members_to_squad;
+mod process_address_book;
+mod process_approve_and_execute;
+mod process_approve_with_signatures;
+mod process_cancel_proposal;
 mod process_cast_multisig_vote;
 mod process_cast_vote;
+mod process_claim_vested;
+mod process_close_proposal;
+mod process_create_child_squad;
 mod process_create_multisig;
 mod process_create_proposal;
 mod process_create_squad;
+mod process_deposit_governance;
+mod process_execute_child;
+mod process_execute_create_mint;
 mod process_execute_multisig_proposal;
 mod process_execute_proposal;
+mod process_execute_stake;
+mod process_execute_stream;
 mod process_execute_swap;
+mod process_execute_token_account;
+mod process_execute_token_authority;
+mod process_execute_upgrade;
+mod process_record_checkpoint;
+mod process_squad_config;
+mod process_withdraw_governance;
+mod process_withdraw_stream;
 // mod process_quit_squad;
 
 use process_add_members_to_squad::*;
+use process_address_book::*;
+use process_approve_and_execute::*;
+use process_approve_with_signatures::*;
+use process_cancel_proposal::*;
 use process_cast_multisig_vote::*;
 use process_cast_vote::*;
+use process_claim_vested::*;
+use process_close_proposal::*;
+use process_create_child_squad::*;
 use process_create_multisig::*;
 use process_create_proposal::*;
 use process_create_squad::*;
+use process_deposit_governance::*;
+use process_execute_child::*;
+use process_execute_create_mint::*;
 use process_execute_multisig_proposal::*;
 use process_execute_proposal::*;
+use process_execute_stake::*;
+use process_execute_stream::*;
 use process_execute_swap::*;
+use process_execute_token_account::*;
+use process_execute_token_authority::*;
+use process_execute_upgrade::*;
+use process_record_checkpoint::*;
+use process_squad_config::*;
+use process_withdraw_governance::*;
+use process_withdraw_stream::*;
 // use process_quit_squad::*;
 
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
@@ -45,6 +83,10 @@ pub fn process(
             description,
             token,
             random_id,
+            vote_support_bps,
+            vote_quorum_bps,
+            token_gated,
+            min_deposit,
         } => process_create_squad(
             accounts,
             allocation_type,
@@ -55,6 +97,10 @@ pub fn process(
             description,
             token,
             random_id,
+            vote_support_bps,
+            vote_quorum_bps,
+            token_gated,
+            min_deposit,
             program_id,
         ),
 
@@ -91,6 +137,10 @@ pub fn process(
             close_timestamp,
             amount,
             minimum_out,
+            vesting_cliff,
+            vault_index,
+            execute_after,
+            token_symbol,
         } => process_create_proposal(
             accounts,
             proposal_type,
@@ -103,6 +153,10 @@ pub fn process(
             close_timestamp,
             amount,
             minimum_out,
+            vesting_cliff,
+            vault_index,
+            execute_after,
+            token_symbol,
             program_id,
         ),
 
@@ -123,6 +177,50 @@ pub fn process(
         SquadInstruction::ExecuteMultisigProposal { random_id } => {
             process_execute_multisig_proposal(accounts, random_id, program_id)
         }
+
+        SquadInstruction::ClaimVested => process_claim_vested(accounts, program_id),
+
+        SquadInstruction::WithdrawStream => process_withdraw_stream(accounts, program_id),
+
+        SquadInstruction::CreateChildSquad {
+            allocation_type,
+            vote_support,
+            vote_quorum,
+            core_threshold,
+            squad_name,
+            description,
+            token,
+            random_id,
+            members_num,
+        } => process_create_child_squad(
+            accounts,
+            allocation_type,
+            vote_support,
+            vote_quorum,
+            core_threshold,
+            squad_name,
+            description,
+            token,
+            random_id,
+            members_num,
+            program_id,
+        ),
+
+        SquadInstruction::DepositGovernance { amount } => {
+            process_deposit_governance(accounts, amount, program_id)
+        }
+
+        SquadInstruction::WithdrawGovernance { amount } => {
+            process_withdraw_governance(accounts, amount, program_id)
+        }
+        SquadInstruction::ApproveWithSignatures => {
+            process_approve_with_signatures(accounts, program_id)
+        }
+        SquadInstruction::ApproveAndExecute { random_id } => {
+            process_approve_and_execute(accounts, random_id, program_id)
+        }
+        SquadInstruction::CloseProposal => process_close_proposal(accounts, program_id),
+        SquadInstruction::CancelProposal => process_cancel_proposal(accounts, program_id),
     }
 }
 
@@ -15,7 +15,7 @@ use solana_program::{
 use spl_token::instruction::initialize_account;
 
 use crate::{
-    state::squad::{Member, Squad},
+    state::squad::{Member, Squad, MEMBER_ROLE_ALL},
     *,
 };
 
@@ -67,11 +67,14 @@ pub fn process_add_members_to_squad(
     ];
 
     // check that the squad PDA matches the one provided
-    let (squad_account_pda, _squad_pda_bump) =
-        get_squad_address_with_seed(initializer.key, &squad_info.random_id, program_id);
-    if squad_account_pda != *squad_account.key {
-        msg!("PDAS Do not match");
-        return Err(ProgramError::InvalidArgument);
+    // child squads are derived from their parent instead of the admin
+    if squad_info.parent == Pubkey::default() {
+        let (squad_account_pda, _squad_pda_bump) =
+            get_squad_address_with_seed(initializer.key, &squad_info.random_id, program_id);
+        if squad_account_pda != *squad_account.key {
+            msg!("PDAS Do not match");
+            return Err(ProgramError::InvalidArgument);
+        }
     }
 
     for member_index in 0..members_num {
@@ -80,12 +83,14 @@ pub fn process_add_members_to_squad(
         // member governance PDA
         let member_account = next_account_info(account_info_iter)?;
 
-        squad_info.members.insert(
+        squad_info.add_member(
             *member.key,
             Member {
                 equity_token_account: *member_account.key,
+                role: MEMBER_ROLE_ALL,
+                joined_version: 0,
             },
-        );
+        )?;
 
         // derive the PDA from the member and squad keys
         let (member_pda, member_bump_seed) =
@@ -186,6 +191,14 @@ pub fn process_add_members_to_squad(
             &[&mint_signer_seeds],
         )?;
     }
+    // a child squad has to give its parent vault a seat
+    if squad_info.parent != Pubkey::default() {
+        let parent_sol_address = get_sol_address(&squad_info.parent, program_id);
+        if !Squad::member_exists(&squad_info, &parent_sol_address) {
+            msg!("SQDS: The parent squad vault needs a seat in the child squad");
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
     squad_info.open = false;
 
     Squad::pack(squad_info, &mut squad_account.data.borrow_mut())?;
@@ -0,0 +1,169 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::{allocate, assign, create_account, transfer},
+};
+
+use crate::{state::address_book::AddressBook, *};
+
+// adds (listed) or removes a withdrawal destination, the address book account is
+// created on the first update, the caller flags the squad as having one
+#[allow(clippy::too_many_arguments)]
+pub fn process_set_address_book_entry<'a>(
+    payer: &AccountInfo<'a>,
+    squad_account: &AccountInfo<'a>,
+    address_book_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    rent: &Rent,
+    destination: &Pubkey,
+    listed: bool,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let mut address_book_info = load_or_create_address_book(
+        payer,
+        squad_account,
+        address_book_account,
+        system_program_account,
+        rent,
+        program_id,
+    )?;
+
+    if !address_book_info.set_entry(destination, listed) {
+        msg!("SQDS: Address book is full");
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    AddressBook::pack(
+        address_book_info,
+        &mut address_book_account.data.borrow_mut(),
+    )?;
+    Ok(())
+}
+
+// sets what withdrawals to destinations missing from the address book need
+#[allow(clippy::too_many_arguments)]
+pub fn process_set_unlisted_policy<'a>(
+    payer: &AccountInfo<'a>,
+    squad_account: &AccountInfo<'a>,
+    address_book_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    rent: &Rent,
+    unlisted_threshold: u8,
+    unlisted_delay: UnixTimestamp,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let mut address_book_info = load_or_create_address_book(
+        payer,
+        squad_account,
+        address_book_account,
+        system_program_account,
+        rent,
+        program_id,
+    )?;
+
+    address_book_info.unlisted_threshold = unlisted_threshold;
+    address_book_info.unlisted_delay = unlisted_delay;
+    AddressBook::pack(
+        address_book_info,
+        &mut address_book_account.data.borrow_mut(),
+    )?;
+    Ok(())
+}
+
+// the squad address book after checking its address, empty if it was never created
+pub fn address_book_info(
+    squad_account: &AccountInfo,
+    address_book_account: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<AddressBook, ProgramError> {
+    let address_book_address = get_address_book_address(squad_account.key, program_id);
+    if address_book_address != *address_book_account.key {
+        msg!("SQDS: Address book account PDA mismatch");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    get_address_book(program_id, squad_account, address_book_account)
+}
+
+fn load_or_create_address_book<'a>(
+    payer: &AccountInfo<'a>,
+    squad_account: &AccountInfo<'a>,
+    address_book_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    rent: &Rent,
+    program_id: &Pubkey,
+) -> Result<AddressBook, ProgramError> {
+    let (address_book_address, address_book_bump_seed) =
+        get_address_book_address_with_seed(squad_account.key, program_id);
+    if address_book_address != *address_book_account.key {
+        msg!("SQDS: Address book account PDA mismatch");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if address_book_account.data_is_empty() {
+        let address_book_signer_seeds: &[&[_]] = &[
+            &squad_account.key.to_bytes(),
+            b"!addressbook",
+            &[address_book_bump_seed],
+        ];
+
+        // DoS check
+        let rent_exempt_lamports = rent.minimum_balance(AddressBook::get_packed_len()).max(1);
+        if address_book_account.lamports() > 0 {
+            let top_up_lamports =
+                rent_exempt_lamports.saturating_sub(address_book_account.lamports());
+
+            if top_up_lamports > 0 {
+                invoke(
+                    &transfer(payer.key, address_book_account.key, top_up_lamports),
+                    &[
+                        payer.clone(),
+                        address_book_account.clone(),
+                        system_program_account.clone(),
+                    ],
+                )?;
+            }
+
+            invoke_signed(
+                &allocate(
+                    address_book_account.key,
+                    AddressBook::get_packed_len() as u64,
+                ),
+                &[address_book_account.clone(), system_program_account.clone()],
+                &[address_book_signer_seeds],
+            )?;
+
+            invoke_signed(
+                &assign(address_book_account.key, program_id),
+                &[address_book_account.clone(), system_program_account.clone()],
+                &[address_book_signer_seeds],
+            )?;
+        } else {
+            invoke_signed(
+                &create_account(
+                    payer.key,
+                    &address_book_address,
+                    rent_exempt_lamports,
+                    AddressBook::get_packed_len() as u64,
+                    program_id,
+                ),
+                &[
+                    payer.clone(),
+                    address_book_account.clone(),
+                    system_program_account.clone(),
+                ],
+                &[address_book_signer_seeds],
+            )?;
+        }
+    }
+
+    let mut address_book_info = get_address_book(program_id, squad_account, address_book_account)?;
+    if !address_book_info.is_initialized {
+        address_book_info.save_address_book(squad_account.key);
+    }
+    Ok(address_book_info)
+}
@@ -0,0 +1,66 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::processor::{process_cast_multisig_vote, process_execute_multisig_proposal};
+use crate::*;
+
+// approves a multisig proposal and executes it right away once the approval reaches
+// the threshold, with the accounts of both CastMultisigVote and ExecuteMultisigProposal
+pub fn process_approve_and_execute(
+    accounts: &[AccountInfo],
+    random_id: String,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let initializer = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+    let vote_account = next_account_info(account_info_iter)?;
+    let source_account = next_account_info(account_info_iter)?;
+    let destination_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let associated_program_account = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let squads_program_account = next_account_info(account_info_iter)?;
+
+    let vote_accounts = [
+        initializer.clone(),
+        squad_account.clone(),
+        proposal_account.clone(),
+        vote_account.clone(),
+        system_program_account.clone(),
+        rent_account.clone(),
+        squads_program_account.clone(),
+    ];
+    process_cast_multisig_vote(&vote_accounts, program_id, 0)?;
+
+    // a proposal still short of approvals or time-locked only records the vote
+    let proposal_info = get_proposal(program_id, squad_account, proposal_account)?;
+    if !proposal_info.is_approved()
+        || proposal_info.is_closed()
+        || proposal_info.is_time_locked(Clock::get().unwrap().unix_timestamp)
+    {
+        return Ok(());
+    }
+
+    // the type specific accounts follow, as for ExecuteMultisigProposal
+    let mut execute_accounts = vec![
+        initializer.clone(),
+        squad_account.clone(),
+        proposal_account.clone(),
+        source_account.clone(),
+        destination_account.clone(),
+        system_program_account.clone(),
+        token_program_account.clone(),
+        associated_program_account.clone(),
+        rent_account.clone(),
+    ];
+    execute_accounts.extend(account_info_iter.cloned());
+    process_execute_multisig_proposal(&execute_accounts, random_id, program_id)
+}
@@ -0,0 +1,263 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    ed25519_program,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::{
+        instructions::{load_current_index_checked, load_instruction_at_checked},
+        Sysvar,
+    },
+};
+
+use crate::state::squad::AllocationType;
+use crate::{
+    governance::{evaluate, record_outcome, SquadParams},
+    state::{
+        proposal::Proposal,
+        squad::{Squad, MEMBER_ROLE_VOTER},
+    },
+    *,
+};
+
+// Ed25519 precompile instruction layout
+const ED25519_HEADER_BYTES: usize = 2;
+const ED25519_OFFSETS_BYTES: usize = 14;
+const ED25519_PUBKEY_BYTES: usize = 32;
+// instruction index pointing at the Ed25519 instruction itself
+const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+// tallies the approvals members signed off-chain over the proposal digest, the
+// signatures are checked by Ed25519 instructions earlier in the same transaction
+pub fn process_approve_with_signatures(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let squad_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+    let instructions_sysvar_account = next_account_info(account_info_iter)?;
+
+    let squad_account_info = get_squad(program_id, squad_account)?;
+    let mut proposal_info = get_proposal(program_id, squad_account, proposal_account)?;
+
+    if squad_account_info.allocation_type != AllocationType::Multisig as u8 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if proposal_info.close_timestamp < Clock::get().unwrap().unix_timestamp {
+        msg!("SQDS: Vote rejected, proposal has already ended");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if proposal_info.start_timestamp > Clock::get().unwrap().unix_timestamp {
+        msg!("SQDS: Vote rejected, proposal has not started yet");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if proposal_info.is_closed() {
+        msg!("SQDS: Vote rejected, proposal has already executed");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let digest = proposal_info.approval_digest(proposal_account.key, program_id);
+    let current_index = load_current_index_checked(instructions_sysvar_account)?;
+    let mut approvals = 0;
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index as usize, instructions_sysvar_account)?;
+        if instruction.program_id != ed25519_program::id() {
+            continue;
+        }
+        for signer in signers_of(&instruction.data, &digest)? {
+            // the signer has to be a voter from the proposal snapshot who didn't vote yet
+            if !Squad::member_has_role(&squad_account_info, &signer, MEMBER_ROLE_VOTER) {
+                msg!("SQDS: Approval signed by a non voting member");
+                return Err(ProgramError::InvalidArgument);
+            }
+            if !squad_account_info.member_joined_by(&signer, proposal_info.membership_version) {
+                msg!("SQDS: Vote rejected, member joined after the proposal was created");
+                return Err(ProgramError::InvalidArgument);
+            }
+            if proposal_info.has_voted.contains(&signer) {
+                msg!("SQDS: Vote already exists for this member");
+                return Err(ProgramError::InvalidArgument);
+            }
+            proposal_info.has_voted.push(signer);
+            approvals += 1;
+        }
+    }
+    if approvals == 0 {
+        msg!("SQDS: No signed approvals for this proposal");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // record the approvals to the proposal
+    let curr_vote = proposal_info
+        .votes
+        .get_mut(0)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    *curr_vote += approvals;
+    proposal_info.has_voted_num = proposal_info.has_voted.len() as u8;
+
+    let vote_params = SquadParams::live(&squad_account_info, &proposal_info);
+    let now = Clock::get().unwrap().unix_timestamp;
+    let outcome = evaluate(&proposal_info, &vote_params, 0, now);
+    record_outcome(&mut proposal_info, outcome, now)?;
+
+    if proposal_info.is_approved() {
+        proposal_info.threshold_at_execute = vote_params.threshold;
+    }
+
+    Proposal::pack(proposal_info, &mut proposal_account.data.borrow_mut())?;
+    Ok(())
+}
+
+// public keys that signed the digest in an Ed25519 instruction, only signatures whose
+// key and message are inside that instruction are read, the others are skipped
+fn signers_of(data: &[u8], digest: &[u8; 32]) -> Result<Vec<Pubkey>, ProgramError> {
+    let signatures_num = *data.first().ok_or(ProgramError::InvalidInstructionData)? as usize;
+    let mut signers = Vec::new();
+    for signature_index in 0..signatures_num {
+        let start = ED25519_HEADER_BYTES + signature_index * ED25519_OFFSETS_BYTES;
+        let offsets = data
+            .get(start..start + ED25519_OFFSETS_BYTES)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let read_u16 = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+        let public_key_offset = read_u16(4) as usize;
+        let public_key_instruction_index = read_u16(6);
+        let message_data_offset = read_u16(8) as usize;
+        let message_data_size = read_u16(10) as usize;
+        let message_instruction_index = read_u16(12);
+
+        if public_key_instruction_index != ED25519_CURRENT_INSTRUCTION
+            || message_instruction_index != ED25519_CURRENT_INSTRUCTION
+        {
+            continue;
+        }
+        let message = data
+            .get(message_data_offset..message_data_offset + message_data_size)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        if message != digest {
+            continue;
+        }
+        let public_key = data
+            .get(public_key_offset..public_key_offset + ED25519_PUBKEY_BYTES)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        signers.push(Pubkey::new(public_key));
+    }
+    Ok(signers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ED25519_SIGNATURE_BYTES: usize = 64;
+
+    // an Ed25519 instruction with one signature per entry, each signed key and message
+    // read from the given instruction index, the signatures themselves are not checked here
+    fn ed25519_data(entries: &[(Pubkey, [u8; 32], u16)]) -> Vec<u8> {
+        let mut data = vec![entries.len() as u8, 0];
+        let mut offset = ED25519_HEADER_BYTES + entries.len() * ED25519_OFFSETS_BYTES;
+        let mut payload = Vec::new();
+        for (public_key, message, instruction_index) in entries {
+            let public_key_offset = offset as u16;
+            let signature_offset = public_key_offset + ED25519_PUBKEY_BYTES as u16;
+            let message_offset = signature_offset + ED25519_SIGNATURE_BYTES as u16;
+            for value in [
+                signature_offset,
+                *instruction_index,
+                public_key_offset,
+                *instruction_index,
+                message_offset,
+                message.len() as u16,
+                *instruction_index,
+            ] {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+            payload.extend_from_slice(public_key.as_ref());
+            payload.extend_from_slice(&[0; ED25519_SIGNATURE_BYTES]);
+            payload.extend_from_slice(message);
+            offset += ED25519_PUBKEY_BYTES + ED25519_SIGNATURE_BYTES + message.len();
+        }
+        data.extend_from_slice(&payload);
+        data
+    }
+
+    fn digest_of(proposal_address: &Pubkey, program_id: &Pubkey) -> [u8; 32] {
+        let proposal_arr = vec![0; Proposal::get_packed_len()];
+        let proposal_info = Proposal::unpack_unchecked(&proposal_arr).unwrap();
+        proposal_info.approval_digest(proposal_address, program_id)
+    }
+
+    #[test]
+    fn signers_of_current_instruction() {
+        let program_id = Pubkey::new_unique();
+        let digest = digest_of(&Pubkey::new_unique(), &program_id);
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let data = ed25519_data(&[
+            (first, digest, ED25519_CURRENT_INSTRUCTION),
+            (second, digest, ED25519_CURRENT_INSTRUCTION),
+        ]);
+        assert_eq!(signers_of(&data, &digest).unwrap(), vec![first, second]);
+    }
+
+    #[test]
+    fn signers_of_other_instruction() {
+        // a key and message read from another instruction aren't covered by this one
+        let program_id = Pubkey::new_unique();
+        let digest = digest_of(&Pubkey::new_unique(), &program_id);
+        let member = Pubkey::new_unique();
+        let data = ed25519_data(&[
+            (Pubkey::new_unique(), digest, 0),
+            (Pubkey::new_unique(), digest, 2),
+            (member, digest, ED25519_CURRENT_INSTRUCTION),
+        ]);
+        assert_eq!(signers_of(&data, &digest).unwrap(), vec![member]);
+    }
+
+    #[test]
+    fn signers_of_duplicated_signer() {
+        // every signature is reported, the tally rejects the second approval of a member
+        let program_id = Pubkey::new_unique();
+        let digest = digest_of(&Pubkey::new_unique(), &program_id);
+        let member = Pubkey::new_unique();
+        let data = ed25519_data(&[
+            (member, digest, ED25519_CURRENT_INSTRUCTION),
+            (member, digest, ED25519_CURRENT_INSTRUCTION),
+        ]);
+        assert_eq!(signers_of(&data, &digest).unwrap(), vec![member, member]);
+    }
+
+    #[test]
+    fn signers_of_wrong_proposal() {
+        // an approval signed for another proposal doesn't count for this one
+        let program_id = Pubkey::new_unique();
+        let digest = digest_of(&Pubkey::new_unique(), &program_id);
+        let other_digest = digest_of(&Pubkey::new_unique(), &program_id);
+        let other_program_digest = digest_of(&Pubkey::new_unique(), &Pubkey::new_unique());
+        let (member, other_member) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let data = ed25519_data(&[
+            (other_member, other_digest, ED25519_CURRENT_INSTRUCTION),
+            (
+                Pubkey::new_unique(),
+                other_program_digest,
+                ED25519_CURRENT_INSTRUCTION,
+            ),
+            (member, digest, ED25519_CURRENT_INSTRUCTION),
+        ]);
+        assert_eq!(signers_of(&data, &digest).unwrap(), vec![member]);
+        assert_eq!(
+            signers_of(&data, &other_digest).unwrap(),
+            vec![other_member]
+        );
+
+        // offsets pointing past the instruction data are malformed
+        let mut truncated = data.clone();
+        truncated.truncate(data.len() - 1);
+        assert!(signers_of(&truncated, &digest).is_err());
+    }
+}
@@ -0,0 +1,39 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    state::proposal::{Proposal, ProposalStatus},
+    *,
+};
+
+// cancels a draft or active proposal before anyone voted on it
+pub fn process_cancel_proposal(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let initializer = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut proposal_info = get_proposal(program_id, squad_account, proposal_account)?;
+    if proposal_info.creator != *initializer.key {
+        msg!("SQDS: Only the proposal creator can cancel it");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if !proposal_info.has_voted.is_empty() {
+        msg!("SQDS: Proposal already has votes and can't be cancelled");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    proposal_info.set_status(ProposalStatus::Cancelled)?;
+    Proposal::pack(proposal_info, &mut proposal_account.data.borrow_mut())?;
+    Ok(())
+}
@@ -14,7 +14,12 @@ use solana_program::{
 
 use crate::state::squad::AllocationType;
 use crate::{
-    state::{proposal::Proposal, squad::Squad, vote::VoteReceipt},
+    governance::{evaluate, record_outcome, SquadParams},
+    state::{
+        proposal::Proposal,
+        squad::{Squad, MEMBER_ROLE_VOTER},
+        vote::VoteReceipt,
+    },
     *,
 };
 
@@ -56,7 +61,7 @@ pub fn process_cast_multisig_vote(
         return Err(ProgramError::InvalidArgument);
     }
 
-    if proposal_info.executed {
+    if proposal_info.is_closed() {
         msg!("SQDS: Vote rejected, proposal has already executed");
         return Err(ProgramError::InvalidArgument);
     }
@@ -66,7 +71,13 @@ pub fn process_cast_multisig_vote(
         return Err(ProgramError::InvalidArgument);
     }
     // check if the voter is a member
-    if !Squad::member_exists(&squad_account_info, initializer.key) {
+    if !Squad::member_has_role(&squad_account_info, initializer.key, MEMBER_ROLE_VOTER) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // members added after the proposal was created aren't part of its snapshot
+    if !squad_account_info.member_joined_by(initializer.key, proposal_info.membership_version) {
+        msg!("SQDS: Vote rejected, member joined after the proposal was created");
         return Err(ProgramError::InvalidArgument);
     }
 
@@ -86,7 +97,8 @@ pub fn process_cast_multisig_vote(
         return Err(ProgramError::InvalidAccountData);
     }
 
-    if !vote_account.data_is_empty() {
+    // signed approvals are tallied without a vote account
+    if !vote_account.data_is_empty() || proposal_info.has_voted.contains(initializer.key) {
         msg!("SQDS: Vote already exists for this member");
         return Err(ProgramError::AccountAlreadyInitialized);
     }
@@ -159,27 +171,14 @@ pub fn process_cast_multisig_vote(
     proposal_info.has_voted.push(*initializer.key);
     proposal_info.has_voted_num = proposal_info.has_voted.len() as u8;
 
-    let mut quorum_ready = false;
-
-    let pass_votes = *proposal_info.votes.get(0).unwrap();
-    let fail_votes = *proposal_info.votes.get(1).unwrap();
-    let possible_votes_left = squad_account_info.members.len() as u64 - (pass_votes + fail_votes);
-
-    if squad_account_info.vote_quorum as u64 > (possible_votes_left + pass_votes) {
-        proposal_info.execute_ready = true;
-        proposal_info.executed = true;
-    }
-
-    if pass_votes as f32 >= squad_account_info.vote_quorum as f32 {
-        quorum_ready = true;
-    }
-
-    if quorum_ready {
-        proposal_info.execute_ready = true;
-    }
+    // vault policies can override the threshold for proposals moving funds
+    let vote_params = SquadParams::live(&squad_account_info, &proposal_info);
+    let now = Clock::get().unwrap().unix_timestamp;
+    let outcome = evaluate(&proposal_info, &vote_params, 0, now);
+    record_outcome(&mut proposal_info, outcome, now)?;
 
-    if proposal_info.execute_ready {
-        proposal_info.threshold_at_execute = squad_account_info.vote_quorum;
+    if proposal_info.is_approved() {
+        proposal_info.threshold_at_execute = vote_params.threshold;
     }
 
     Proposal::pack(proposal_info, &mut proposal_account.data.borrow_mut())?;
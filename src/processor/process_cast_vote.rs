@@ -13,10 +13,16 @@ use solana_program::{
 };
 use spl_token::state::{Account, Mint};
 
-use crate::state::proposal::ProposalType;
+use crate::processor::checkpointed_amount;
 use crate::state::squad::AllocationType;
 use crate::{
-    state::{proposal::Proposal, squad::Squad, vote::VoteReceipt},
+    governance::{evaluate, record_outcome, SquadParams},
+    state::{
+        checkpoint::Checkpoints,
+        proposal::Proposal,
+        squad::{Squad, MEMBER_ROLE_CORE, MEMBER_ROLE_VOTER},
+        vote::VoteReceipt,
+    },
     *,
 };
 
@@ -31,6 +37,9 @@ pub fn process_cast_vote(accounts: &[AccountInfo], program_id: &Pubkey, vote: u8
     let system_program_account = next_account_info(account_info_iter)?;
     let rent_account = next_account_info(account_info_iter)?;
     let squads_program_account = next_account_info(account_info_iter)?;
+    // optional for clients predating the checkpoints, the live balances are used then
+    let member_checkpoints_account = next_account_info(account_info_iter).ok();
+    let supply_checkpoints_account = next_account_info(account_info_iter).ok();
     let rent = &Rent::from_account_info(rent_account)?;
 
     // check that the signer
@@ -60,9 +69,9 @@ pub fn process_cast_vote(accounts: &[AccountInfo], program_id: &Pubkey, vote: u8
         return Err(ProgramError::InvalidArgument);
     }
 
-    // check there isn't a member change lock on this proposal
+    // proposals created before membership snapshots can still be locked by a member change
     // if this proposal index is less than the member_lock_index, no voting allowed
-    if !proposal_info.execute_ready
+    if !proposal_info.is_approved()
         && proposal_info.proposal_index <= squad_account_info.member_lock_index
     {
         return Err(ProgramError::InvalidInstructionData);
@@ -86,13 +95,19 @@ pub fn process_cast_vote(accounts: &[AccountInfo], program_id: &Pubkey, vote: u8
         return Err(ProgramError::InvalidArgument);
     }
 
-    if proposal_info.executed {
+    if proposal_info.is_closed() {
         msg!("SQDS: Vote rejected, proposal has already executed");
         return Err(ProgramError::InvalidArgument);
     }
 
     // check that the signer is a member of this squad
-    if !Squad::member_exists(&squad_account_info, initializer.key) {
+    if !Squad::member_has_role(&squad_account_info, initializer.key, MEMBER_ROLE_VOTER) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // members added after the proposal was created aren't part of its snapshot
+    if !squad_account_info.member_joined_by(initializer.key, proposal_info.membership_version) {
+        msg!("SQDS: Vote rejected, member joined after the proposal was created");
         return Err(ProgramError::InvalidArgument);
     }
 
@@ -175,8 +190,60 @@ pub fn process_cast_vote(accounts: &[AccountInfo], program_id: &Pubkey, vote: u8
         )?;
     }
 
+    // vesting grants are only minted to the equity account once claimed,
+    // so unvested tokens never count towards the vote weight or the supply
     let governance_account_info =
         Account::unpack_unchecked(&member_governance_account.data.borrow())?;
+    let squad_mint_account_info = Mint::unpack_unchecked(&squad_mint_account.data.borrow())?;
+
+    // the vote weight and supply are taken as of the proposal creation,
+    // so equity changes don't affect the proposals already in flight
+    // a token-gated squad always needs them, deposits move freely and have to stay
+    // locked while the votes are active
+    if squad_account_info.token_gated
+        && (member_checkpoints_account.is_none() || supply_checkpoints_account.is_none())
+    {
+        msg!("SQDS: Token-gated votes need the checkpoints accounts");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let vote_weight = match member_checkpoints_account {
+        Some(member_checkpoints_account) => checkpointed_amount(
+            squad_account,
+            member_checkpoints_account,
+            initializer.key,
+            proposal_info.created_timestamp,
+            governance_account_info.amount,
+            false,
+            program_id,
+        )?,
+        None => governance_account_info.amount,
+    };
+    let live_supply = squad_account_info.governance_supply(squad_mint_account_info.supply);
+    let supply = match supply_checkpoints_account {
+        Some(supply_checkpoints_account) => checkpointed_amount(
+            squad_account,
+            supply_checkpoints_account,
+            squad_mint_account.key,
+            proposal_info.created_timestamp,
+            live_supply,
+            true,
+            program_id,
+        )?,
+        None => live_supply,
+    };
+
+    // a token-gated deposit stays locked until the proposals voted on are closed
+    if let Some(member_checkpoints_account) = member_checkpoints_account
+        .filter(|account| squad_account_info.token_gated && !account.data_is_empty())
+    {
+        let mut member_checkpoints_info =
+            get_checkpoints(program_id, squad_account, member_checkpoints_account)?;
+        member_checkpoints_info.lock_votes_until(proposal_info.close_timestamp);
+        Checkpoints::pack(
+            member_checkpoints_info,
+            &mut member_checkpoints_account.data.borrow_mut(),
+        )?;
+    }
 
     let mut vote_account_info = get_vote(program_id, squad_account, vote_account)?;
 
@@ -186,110 +253,31 @@ pub fn process_cast_vote(accounts: &[AccountInfo], program_id: &Pubkey, vote: u8
         vote,
         initializer.key,
         Clock::get().unwrap().unix_timestamp,
-        governance_account_info.amount,
+        vote_weight,
     );
 
     VoteReceipt::pack(vote_account_info, &mut vote_account.data.borrow_mut())?;
 
     // record the vote to the proposal
     let curr_vote = proposal_info.votes.get_mut(vote as usize).unwrap();
-    *curr_vote += governance_account_info.amount;
+    *curr_vote += vote_weight;
     proposal_info.has_voted.push(*initializer.key);
     proposal_info.has_voted_num = proposal_info.has_voted.len() as u8;
-
-    // get mint account supply
-    let squad_mint_account_info = Mint::unpack_unchecked(&squad_mint_account.data.borrow())?;
-
-    let total_votes_copy = proposal_info.votes.clone();
-    let total_votes = total_votes_copy.into_iter().reduce(|a, b| a + b).unwrap();
-
-    // get total votes
-    let possible_votes_left = squad_mint_account_info.supply - total_votes;
-
-    if proposal_info.proposal_type == ProposalType::Text as u8 {
-        let votes = proposal_info.votes.clone();
-        let most_index = votes
-            .iter()
-            .enumerate()
-            .fold(
-                (0, 0),
-                |max, (ind, &val)| if val > max.1 { (ind, val) } else { max },
-            )
-            .0;
-        let second_most_index = votes
-            .iter()
-            .enumerate()
-            .fold((0, 0), |max, (ind, &val)| {
-                if ind == most_index {
-                    if most_index == 0 {
-                        (ind + 1, 0)
-                    } else {
-                        max
-                    }
-                } else if val > max.1 {
-                    (ind, val)
-                } else {
-                    max
-                }
-            })
-            .0;
-
-        if votes[most_index] > votes[second_most_index] + possible_votes_left {
-            let mut quorum_ready = false;
-            let curr_quorum_percent = (proposal_info.has_voted.len() as f32
-                / squad_account_info.members.len() as f32)
-                * 100.0;
-
-            if curr_quorum_percent >= squad_account_info.vote_quorum as f32 {
-                quorum_ready = true;
-            }
-
-            let mut support_ready = false;
-            let current_support_percent =
-                (votes[most_index] as f32 / squad_mint_account_info.supply as f32) * 100.0;
-            if current_support_percent >= squad_account_info.vote_support as f32 {
-                support_ready = true;
-            }
-
-            if quorum_ready && support_ready {
-                proposal_info.execute_ready = true;
-            }
-        }
-    } else {
-        let pass_votes = *proposal_info.votes.get(0).unwrap();
-        let fail_votes = *proposal_info.votes.get(1).unwrap();
-
-        // Close proposal if decline are greater than accept
-        if fail_votes > pass_votes + possible_votes_left {
-            proposal_info.executed = true;
-        }
-
-        // check quorum
-        let mut quorum_ready = false;
-        let curr_quorum_percent = (proposal_info.has_voted.len() as f32
-            / squad_account_info.members.len() as f32)
-            * 100.0;
-
-        if curr_quorum_percent >= squad_account_info.vote_quorum as f32 {
-            quorum_ready = true;
-        }
-
-        // check support
-        let mut support_ready = false;
-        let current_support_percent =
-            (pass_votes as f32 / squad_mint_account_info.supply as f32) * 100.0;
-        if current_support_percent >= squad_account_info.vote_support as f32 {
-            support_ready = true;
-        }
-
-        if quorum_ready && support_ready {
-            proposal_info.execute_ready = true;
-        }
+    // sensitive proposals also need the approval of the core council
+    if vote == 0 && squad_account_info.member_has_role(initializer.key, MEMBER_ROLE_CORE) {
+        proposal_info.core_votes += 1;
     }
 
+    let params = SquadParams::live(&squad_account_info, &proposal_info);
+    let now = Clock::get().unwrap().unix_timestamp;
+    // Close proposal if decline are greater than accept
+    let outcome = evaluate(&proposal_info, &params, supply, now);
+    record_outcome(&mut proposal_info, outcome, now)?;
+
     // Save supply at execute & members to have history on each proposal/vote
-    proposal_info.supply_at_execute = squad_mint_account_info.supply;
-    proposal_info.members_at_execute = squad_account_info.members.len() as u8;
+    proposal_info.supply_at_execute = supply;
+    proposal_info.members_at_execute = params.members as u8;
+    proposal_info.threshold_at_execute = squad_account_info.proposal_threshold(&proposal_info);
 
     Proposal::pack(proposal_info, &mut proposal_account.data.borrow_mut())?;
     Ok(())
@@ -0,0 +1,133 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+
+use spl_token::state::{Account, Mint};
+
+use crate::processor::process_record_checkpoint;
+use crate::state::squad::AllocationType;
+use crate::{
+    state::{squad::Squad, vesting::Vesting},
+    *,
+};
+
+// mints the vested but unclaimed part of a grant to the member
+pub fn process_claim_vested(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let initializer = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    let mint_owner = next_account_info(account_info_iter)?;
+    let vesting_account = next_account_info(account_info_iter)?;
+    let member_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let member_checkpoints_account = next_account_info(account_info_iter)?;
+    let supply_checkpoints_account = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(rent_account)?;
+
+    if !initializer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // check the token program
+    if *token_program_account.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let squad_account_info = get_squad(program_id, squad_account)?;
+    let mut vesting_account_info = get_vesting(program_id, squad_account, vesting_account)?;
+
+    if squad_account_info.allocation_type != AllocationType::TeamCoordination as u8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if !vesting_account_info.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    // only the member of the grant can claim it
+    if vesting_account_info.member != *initializer.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if !Squad::member_exists(&squad_account_info, initializer.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (mint_owner_address, mint_bump_seed) =
+        get_mint_address_with_seed(squad_account.key, program_id);
+    if mint_owner_address != *mint_owner.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let member_equity = get_equity_address(initializer.key, squad_account.key, program_id);
+    if member_equity != *member_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let member_account_info = Account::unpack_unchecked(&member_account.data.borrow())?;
+    let mint_account_info = Mint::unpack_unchecked(&mint_owner.data.borrow())?;
+
+    let claimable = vesting_account_info.claimable_amount(Clock::get().unwrap().unix_timestamp);
+    if claimable == 0 {
+        msg!("SQDS: Nothing to claim on this grant yet");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mint_signer_seeds: &[&[_]] = &[
+        &squad_account.key.to_bytes(),
+        b"!squadmint",
+        &[mint_bump_seed],
+    ];
+
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            &spl_token::id(),
+            mint_owner.key,
+            member_account.key,
+            mint_owner.key,
+            &[],
+            claimable,
+        )?,
+        &[
+            member_account.clone(),
+            token_program_account.clone(),
+            mint_owner.clone(),
+        ],
+        &[mint_signer_seeds],
+    )?;
+
+    vesting_account_info.claimed_amount += claimable;
+    Vesting::pack(vesting_account_info, &mut vesting_account.data.borrow_mut())?;
+
+    // the proposals in flight keep the weights from their creation
+    process_record_checkpoint(
+        initializer,
+        squad_account,
+        member_checkpoints_account,
+        system_program_account,
+        rent,
+        initializer.key,
+        member_account_info.amount,
+        member_account_info.amount + claimable,
+        program_id,
+    )?;
+    process_record_checkpoint(
+        initializer,
+        squad_account,
+        supply_checkpoints_account,
+        system_program_account,
+        rent,
+        mint_owner.key,
+        mint_account_info.supply,
+        mint_account_info.supply + claimable,
+        program_id,
+    )?;
+    Ok(())
+}
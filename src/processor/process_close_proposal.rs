@@ -0,0 +1,53 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::state::squad::AllocationType;
+use crate::{
+    governance::{evaluate, Outcome, SquadParams},
+    *,
+};
+
+// closes an expired Multisig proposal, anyone can close it since the rent goes back
+// to the proposal creator
+pub fn process_close_proposal(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let squad_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+    let creator_account = next_account_info(account_info_iter)?;
+
+    let squad_account_info = get_squad(program_id, squad_account)?;
+    let proposal_info = get_proposal(program_id, squad_account, proposal_account)?;
+
+    if squad_account_info.allocation_type != AllocationType::Multisig as u8 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if proposal_info.creator != *creator_account.key {
+        msg!("SQDS: Rent has to go back to the proposal creator");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let vote_params = SquadParams::at_execute(&squad_account_info, &proposal_info);
+    if evaluate(
+        &proposal_info,
+        &vote_params,
+        0,
+        Clock::get().unwrap().unix_timestamp,
+    ) != Outcome::Expired
+    {
+        msg!("SQDS: Only expired proposals can be closed");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let rent_lamports = proposal_account.lamports();
+    **proposal_account.try_borrow_mut_lamports()? -= rent_lamports;
+    **creator_account.try_borrow_mut_lamports()? += rent_lamports;
+    proposal_account.data.borrow_mut().fill(0);
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::create_account,
+    sysvar::Sysvar,
+};
+use spl_token::instruction::initialize_mint;
+
+use crate::state::squad::AllocationType;
+use crate::{
+    state::squad::{Member, Squad, MEMBER_ROLE_ALL},
+    *,
+};
+
+// creates a Team or Multisig squad at the next child address of the parent squad, the
+// parent vault (!squadsol) signs through a parent CreateChildSquad proposal and always
+// gets a seat in the child
+#[allow(clippy::too_many_arguments)]
+pub fn process_create_child_squad(
+    accounts: &[AccountInfo],
+    allocation_type: u8,
+    vote_support: u8,
+    vote_quorum: u8,
+    core_threshold: u8,
+    squad_name: String,
+    description: String,
+    token: String,
+    random_id: String,
+    members_num: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?;
+    let parent_account = next_account_info(account_info_iter)?;
+    let parent_sol_account = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    let system_account = next_account_info(account_info_iter)?;
+    let rent_sysvar_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(rent_sysvar_info)?;
+
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut parent_info = get_squad(program_id, parent_account)?;
+    if !parent_info.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    // only the parent squad can create children, its vault signs once a proposal passed
+    if *parent_sol_account.key != parent_info.sol_account || !parent_sol_account.is_signer {
+        msg!("SQDS: Child squads are created by a parent CreateChildSquad proposal");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (squad_account_pda, squad_pda_bump) =
+        get_child_squad_address_with_seed(parent_account.key, parent_info.child_index, program_id);
+    if squad_account_pda != *squad_account.key {
+        msg!("PDAS Do not match");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !squad_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let squad_signer_seeds: &[&[_]] = &[
+        &parent_account.key.to_bytes(),
+        &parent_info.child_index.to_le_bytes(),
+        b"!child",
+        &[squad_pda_bump],
+    ];
+    // create the squad account
+    invoke_signed(
+        &create_account(
+            payer.key,
+            &squad_account_pda,
+            1.max(rent.minimum_balance(Squad::get_packed_len())),
+            Squad::get_packed_len() as u64,
+            program_id,
+        ),
+        &[payer.clone(), squad_account.clone(), system_account.clone()],
+        &[squad_signer_seeds],
+    )?;
+
+    let mut squad_info = get_squad(program_id, squad_account)?;
+
+    if squad_info.is_initialized() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let (sol_account_owner_pda, _sol_account_bump_seed) =
+        get_sol_address_with_seed(squad_account.key, program_id);
+
+    if allocation_type == AllocationType::TeamCoordination as u8 {
+        let mint_owner = next_account_info(account_info_iter)?;
+        let token_program_account = next_account_info(account_info_iter)?;
+
+        // check token program
+        if *token_program_account.key != spl_token::id() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // check support and quorum are within range
+        if !(1..=100).contains(&vote_quorum) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if !(1..=100).contains(&vote_support) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (mint_owner_address, mint_bump_seed) =
+            get_mint_address_with_seed(squad_account.key, program_id);
+        if mint_owner_address != *mint_owner.key {
+            msg!("Error: mint address derivation mismatch");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mint_signer_seeds: &[&[_]] = &[
+            &squad_account.key.to_bytes(),
+            b"!squadmint",
+            &[mint_bump_seed],
+        ];
+
+        // create the squads governance mint account
+        invoke_signed(
+            &create_account(
+                payer.key,
+                mint_owner.key,
+                1.max(rent.minimum_balance(spl_token::state::Mint::get_packed_len())),
+                spl_token::state::Mint::get_packed_len() as u64,
+                &spl_token::id(),
+            ),
+            &[payer.clone(), mint_owner.clone(), system_account.clone()],
+            &[mint_signer_seeds],
+        )?;
+
+        // initialize the squad governance mint account
+        invoke_signed(
+            &initialize_mint(&spl_token::id(), mint_owner.key, mint_owner.key, None, 0)?,
+            &[
+                token_program_account.clone(),
+                rent_sysvar_info.clone(),
+                mint_owner.clone(),
+            ],
+            &[mint_signer_seeds],
+        )?;
+
+        // the payer drafts the child, AddMembersToSquad has to seat the parent vault
+        Squad::setup_tc(
+            &mut squad_info,
+            allocation_type,
+            vote_support,
+            vote_quorum,
+            core_threshold,
+            squad_name,
+            description,
+            token,
+            payer.key,
+            mint_owner.key,
+            &sol_account_owner_pda,
+            random_id,
+        );
+    } else if allocation_type == AllocationType::Multisig as u8 {
+        // the parent vault takes one of the seats on top of the members passed in
+        squad_info.add_member(
+            parent_info.sol_account,
+            Member {
+                equity_token_account: parent_info.sol_account,
+                role: MEMBER_ROLE_ALL,
+                joined_version: 0,
+            },
+        )?;
+        for _i in 0..members_num {
+            let member = next_account_info(account_info_iter)?;
+
+            squad_info.add_member(
+                *member.key,
+                Member {
+                    equity_token_account: *member.key,
+                    role: MEMBER_ROLE_ALL,
+                    joined_version: 0,
+                },
+            )?;
+        }
+
+        // check that quorum is within bounds
+        if vote_quorum < 1 || vote_quorum as usize > squad_info.members.len() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Squad::setup_ms(
+            &mut squad_info,
+            vote_quorum,
+            squad_name,
+            description,
+            payer.key,
+            &sol_account_owner_pda,
+            random_id,
+        );
+    } else {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    squad_info.parent = *parent_account.key;
+    Squad::pack(squad_info, &mut squad_account.data.borrow_mut())?;
+
+    parent_info.child_index += 1;
+    Squad::pack(parent_info, &mut parent_account.data.borrow_mut())?;
+    Ok(())
+}
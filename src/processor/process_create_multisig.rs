@@ -12,7 +12,7 @@ use solana_program::{
 };
 
 use crate::{
-    state::squad::{Member, Squad},
+    state::squad::{Member, Squad, MEMBER_ROLE_ALL},
     *,
 };
 
@@ -87,12 +87,14 @@ pub fn process_create_multisig(
     for _i in 0..members_num {
         let member = next_account_info(account_info_iter)?;
 
-        squad_info.members.insert(
+        squad_info.add_member(
             *member.key,
             Member {
                 equity_token_account: *member.key,
+                role: MEMBER_ROLE_ALL,
+                joined_version: 0,
             },
-        );
+        )?;
     }
 
     Squad::setup_ms(
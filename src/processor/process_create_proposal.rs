@@ -1,10 +1,13 @@
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable,
     clock::Clock,
     entrypoint::ProgramResult,
+    hash::hash,
     msg,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
+    program_option::COption,
     program_pack::Pack,
     pubkey::Pubkey,
     rent::Rent,
@@ -14,11 +17,18 @@ use solana_program::{
 
 use num_traits::FromPrimitive;
 
+use crate::processor::{address_book_info, child_members_hash, squad_config_info};
 use crate::state::squad::AllocationType;
 use crate::{
+    governance::BPS_DENOMINATOR,
     state::{
-        proposal::{Proposal, ProposalType},
-        squad::Squad,
+        address_book::address_book_checked,
+        config::{optimistic_eligible, MAX_PROPOSAL_TYPES},
+        proposal::{Proposal, ProposalStatus, ProposalType},
+        squad::{
+            Squad, MAX_VAULTS, MEMBER_ROLE_ALL, MEMBER_ROLE_PROPOSER, SQUAD_DESCRIPTION_BYTES,
+            SQUAD_NAME_BYTES, SQUAD_TOKEN_BYTES,
+        },
     },
     *, // error::SquadError
 };
@@ -36,6 +46,10 @@ pub fn process_create_proposal(
     close_timestamp: i64,
     amount: u64,
     minimum_out: u64,
+    vesting_cliff: i64,
+    vault_index: u8,
+    execute_after: UnixTimestamp,
+    token_symbol: String,
     program_id: &Pubkey,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -64,7 +78,7 @@ pub fn process_create_proposal(
         msg!("SQDS: This proposal has already been created");
         return Err(ProgramError::AccountAlreadyInitialized);
     }
-    if !Squad::member_exists(&squad_account_info, initializer.key) {
+    if !Squad::member_has_role(&squad_account_info, initializer.key, MEMBER_ROLE_PROPOSER) {
         return Err(ProgramError::InvalidAccountData);
     }
     // check squad is not a draft/open
@@ -147,6 +161,28 @@ pub fn process_create_proposal(
         }
     }
 
+    if execute_after < 0 {
+        msg!("SQDS: Invalid execute_after timestamp");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if vault_index >= MAX_VAULTS {
+        msg!("SQDS: Vault index out of range");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // token-gated members join and leave by depositing the governance token,
+    // which the squad has no authority to mint or burn
+    if squad_account_info.token_gated
+        && (proposal_type == ProposalType::AddMember as u8
+            || proposal_type == ProposalType::RemoveMember as u8
+            || proposal_type == ProposalType::MintMemberToken as u8
+            || proposal_type == ProposalType::VestMemberToken as u8)
+    {
+        msg!("SQDS: Token-gated squads can't mint or burn their governance token");
+        return Err(ProgramError::InvalidArgument);
+    }
+
     match FromPrimitive::from_u8(proposal_type) {
         Some(ProposalType::Text) => {
             // text
@@ -206,8 +242,8 @@ pub fn process_create_proposal(
         Some(ProposalType::Quorum) => {
             let amount_check = match squad_account_info.allocation_type {
                 2 => {
-                    // MS quorum amount is limited by max members
-                    (squad_account_info.members.len() as u8) >= amount as u8
+                    // MS quorum amount is limited by the members that can vote
+                    (squad_account_info.voter_count() as u8) >= amount as u8
                 }
                 1 => {
                     // TS Quorum is limited to a percent
@@ -327,6 +363,18 @@ pub fn process_create_proposal(
             // remove member
             let member = next_account_info(account_info_iter)?;
 
+            // a child squad can't drop its parent's seat
+            if squad_account_info.parent != Pubkey::default()
+                && *member.key == get_sol_address(&squad_account_info.parent, program_id)
+            {
+                return Err(ProgramError::InvalidArgument);
+            }
+            // a seated core council can't shrink below core_threshold
+            if !squad_account_info.core_seat_removable(member.key) {
+                msg!("SQDS: Not enough core members left to reach the core threshold");
+                return Err(ProgramError::InvalidArgument);
+            }
+
             Proposal::save_member(
                 &mut proposal_account_info,
                 proposal_type,
@@ -407,9 +455,1002 @@ pub fn process_create_proposal(
                 proposal_nonce,
             );
         }
-        None => {
-            return Err(ProgramError::InvalidArgument);
+        Some(ProposalType::VestMemberToken) => {
+            // Grant member tokens that vest over time
+            if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let member = next_account_info(account_info_iter)?;
+
+            if !Squad::member_exists(&squad_account_info, member.key) {
+                return Err(ProgramError::InvalidArgument);
+            }
+            // minimum_out is the vesting duration in seconds, the cliff has to fall within it
+            if amount == 0 || vesting_cliff < 0 || vesting_cliff as u64 > minimum_out {
+                return Err(ProgramError::InvalidArgument);
+            }
+            // the duration is stored signed, past i64::MAX it would read as already vested
+            if minimum_out == 0 || minimum_out > i64::MAX as u64 {
+                msg!("SQDS: Invalid vesting duration");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            Proposal::save_vesting(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                member.key,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                start_timestamp,
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                minimum_out,
+                vesting_cliff,
+                proposal_nonce,
+            );
+        }
+        Some(ProposalType::RevokeVesting) => {
+            // Revoke the unvested part of a grant
+            if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let vesting_account = next_account_info(account_info_iter)?;
+            let vesting_account_info = get_vesting(program_id, squad_account, vesting_account)?;
+
+            if !vesting_account_info.is_initialized || vesting_account_info.revoked_on != 0 {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            Proposal::save_withdraw(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                vesting_account.key,
+                &vesting_account_info.member,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                start_timestamp,
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                0,
+                proposal_nonce,
+            );
+        }
+        Some(ProposalType::CreateStream) => {
+            // Stream SOL or tokens from the vault to a recipient
+            let source = next_account_info(account_info_iter)?;
+            let recipient = next_account_info(account_info_iter)?;
+
+            // minimum_out is the rate per second
+            if amount == 0 || minimum_out == 0 {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            Proposal::save_swap(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                source.key,
+                recipient.key,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                minimum_out,
+                proposal_nonce,
+            );
+        }
+        Some(ProposalType::CancelStream) => {
+            // Cancel a stream, returning what hasn't accrued to the vault
+            let stream_account = next_account_info(account_info_iter)?;
+            let stream_account_info = get_stream(program_id, squad_account, stream_account)?;
+
+            if !stream_account_info.is_initialized || stream_account_info.canceled_on != 0 {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            Proposal::save_withdraw(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                stream_account.key,
+                &stream_account_info.recipient,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                0,
+                proposal_nonce,
+            );
+        }
+        Some(ProposalType::VaultPolicy) => {
+            // Set the threshold for proposals moving funds out of a vault, 0 resets it
+            let amount_check = match squad_account_info.allocation_type {
+                2 => {
+                    // MS threshold is limited by the members that can vote
+                    amount <= squad_account_info.voter_count() as u64
+                }
+                1 => {
+                    // TS support is limited to a percent
+                    amount <= 100
+                }
+                _ => false,
+            };
+
+            if !amount_check {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+
+            Proposal::save_core(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+        }
+        Some(ProposalType::SetMemberRole) => {
+            // Set the role bitmask of a member, amount is the new role
+            if squad_account_info.allocation_type != AllocationType::Multisig as u8 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let member = next_account_info(account_info_iter)?;
+
+            if !Squad::member_exists(&squad_account_info, member.key) {
+                return Err(ProgramError::InvalidArgument);
+            }
+            if amount > MEMBER_ROLE_ALL as u64 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            // the squad has to keep enough voters to reach its thresholds
+            if !squad_account_info.role_keeps_thresholds(member.key, amount as u8) {
+                msg!("SQDS: Not enough voters left to reach the squad thresholds");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            Proposal::save_member(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                member.key,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                actual_timestamp,
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+        }
+        Some(ProposalType::SetCoreMember) => {
+            // Grant (amount 1) or revoke (amount 0) a core council seat
+            if squad_account_info.allocation_type != AllocationType::TeamCoordination as u8 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let member = next_account_info(account_info_iter)?;
+
+            if !Squad::member_exists(&squad_account_info, member.key) {
+                return Err(ProgramError::InvalidArgument);
+            }
+            if amount > 1 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            if amount == 0 && !squad_account_info.core_seat_removable(member.key) {
+                msg!("SQDS: Not enough core members left to reach the core threshold");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            Proposal::save_member(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                member.key,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                start_timestamp,
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+        }
+        Some(ProposalType::CoreWithdrawLimit) => {
+            // withdrawals above the amount need the core council
+            if squad_account_info.allocation_type != AllocationType::TeamCoordination as u8 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Proposal::save_core(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                start_timestamp,
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+        }
+        Some(ProposalType::TypeThreshold) => {
+            // threshold (amount, 0 to clear) for the proposal type given in minimum_out
+            let target_type: Option<ProposalType> = FromPrimitive::from_u64(minimum_out);
+            let type_check = target_type.is_some() && minimum_out < MAX_PROPOSAL_TYPES as u64;
+            let amount_check = match squad_account_info.allocation_type {
+                // MS thresholds are limited by the members that can vote
+                2 => amount <= squad_account_info.voter_count() as u64,
+                // TS thresholds are support percents
+                1 => amount <= 100,
+                _ => false,
+            };
+            if !type_check || !amount_check {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Proposal::save_core(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+            proposal_account_info.execution_amount_out = minimum_out;
         }
+        Some(ProposalType::OptimisticType) => {
+            // veto threshold (amount, 0 to clear) making the proposal type given in
+            // minimum_out optimistic
+            if minimum_out > u8::MAX as u64 || !optimistic_eligible(minimum_out as u8) {
+                msg!("SQDS: Proposal type can't be optimistic");
+                return Err(ProgramError::InvalidArgument);
+            }
+            let amount_check = match squad_account_info.allocation_type {
+                // MS vetoes are limited by the members that can vote
+                2 => amount <= squad_account_info.voter_count() as u64,
+                // TS vetoes are percents of the supply
+                1 => amount <= 100,
+                _ => false,
+            };
+            if !amount_check {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Proposal::save_core(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+            proposal_account_info.execution_amount_out = minimum_out;
+        }
+        Some(ProposalType::OptimisticWindow) => {
+            // challenge window in seconds (amount, 0 disables optimistic proposals) and
+            // the largest amount an optimistic proposal can move (minimum_out, 0 disables them too)
+            if amount > i64::MAX as u64 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Proposal::save_core(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+            proposal_account_info.execution_amount_out = minimum_out;
+        }
+        Some(ProposalType::ProposalExpiry) => {
+            // default expiry of Multisig proposals in seconds (amount) and the
+            // window to execute them once approved (minimum_out), 0 for none
+            if squad_account_info.allocation_type != AllocationType::Multisig as u8 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            if amount > i64::MAX as u64 || minimum_out > i64::MAX as u64 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Proposal::save_core(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                actual_timestamp,
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+            proposal_account_info.execution_amount_out = minimum_out;
+        }
+        Some(ProposalType::KeeperPolicy) => {
+            // let any signer execute approved proposals (minimum_out 1, 0 turns it off)
+            // and cap the tip a keeper takes from the vault in lamports (amount)
+            if minimum_out > 1 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Proposal::save_core(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+            proposal_account_info.execution_amount_out = minimum_out;
+        }
+        Some(ProposalType::CreateStake)
+        | Some(ProposalType::DelegateStake)
+        | Some(ProposalType::DeactivateStake)
+        | Some(ProposalType::WithdrawStake)
+        | Some(ProposalType::SplitStake)
+        | Some(ProposalType::MergeStake) => {
+            // native stake of the proposal vault, the source is the stake account the
+            // vault has authority over (the vault itself to create one) and the destination
+            // its counterpart: the new or split stake account, the vote account, the vault
+            // receiving a withdrawal or the stake account a merge goes into
+            let source = next_account_info(account_info_iter)?;
+            let target = next_account_info(account_info_iter)?;
+            let (vault_address, _vault_bump_seed) =
+                get_vault_address_with_seed(squad_account.key, vault_index, program_id);
+            let (stake_address, _stake_bump_seed) =
+                get_stake_address_with_seed(proposal_account.key, program_id);
+
+            let accounts_check = match FromPrimitive::from_u8(proposal_type) {
+                Some(ProposalType::CreateStake) => {
+                    *source.key == vault_address && *target.key == stake_address && amount > 0
+                }
+                Some(ProposalType::SplitStake) => *target.key == stake_address && amount > 0,
+                Some(ProposalType::DeactivateStake) => *target.key == vault_address,
+                Some(ProposalType::WithdrawStake) => *target.key == vault_address && amount > 0,
+                Some(ProposalType::MergeStake) => source.key != target.key,
+                _ => true,
+            };
+            if !accounts_check {
+                msg!("SQDS: Invalid stake proposal accounts");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            Proposal::save_withdraw(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                source.key,
+                target.key,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+        }
+        Some(ProposalType::MintTo)
+        | Some(ProposalType::BurnTokens)
+        | Some(ProposalType::SetTokenAuthority)
+        | Some(ProposalType::FreezeAccount)
+        | Some(ProposalType::ThawAccount) => {
+            // an external mint the proposal vault has authority over, the source is the
+            // mint (MintTo, SetTokenAuthority) or the token account acted on, the
+            // destination the token account minted to, the new authority or the mint
+            let source = next_account_info(account_info_iter)?;
+            let target = next_account_info(account_info_iter)?;
+            let (vault_address, _vault_bump_seed) =
+                get_vault_address_with_seed(squad_account.key, vault_index, program_id);
+            let vault_authority = COption::Some(vault_address);
+
+            let authority_check = match FromPrimitive::from_u8(proposal_type) {
+                Some(ProposalType::MintTo) => {
+                    let mint = get_token_mint(source)?;
+                    let account = get_token_account(target)?;
+                    mint.mint_authority == vault_authority
+                        && account.mint == *source.key
+                        && amount > 0
+                }
+                Some(ProposalType::BurnTokens) => {
+                    let account = get_token_account(source)?;
+                    account.owner == vault_address && account.mint == *target.key && amount > 0
+                }
+                Some(ProposalType::SetTokenAuthority) => {
+                    let mint = get_token_mint(source)?;
+                    match minimum_out {
+                        0 => mint.mint_authority == vault_authority,
+                        1 => mint.freeze_authority == vault_authority,
+                        _ => false,
+                    }
+                }
+                _ => {
+                    // FreezeAccount, ThawAccount
+                    let account = get_token_account(source)?;
+                    let mint = get_token_mint(target)?;
+                    mint.freeze_authority == vault_authority && account.mint == *target.key
+                }
+            };
+            if !authority_check {
+                msg!("SQDS: Vault is not the token authority");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            Proposal::save_withdraw(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                source.key,
+                target.key,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+            proposal_account_info.execution_amount_out = minimum_out;
+        }
+        Some(ProposalType::CloseTokenAccount)
+        | Some(ProposalType::ApproveDelegate)
+        | Some(ProposalType::RevokeDelegate)
+        | Some(ProposalType::SyncNative)
+        | Some(ProposalType::CreateTokenAccount) => {
+            // housekeeping of the proposal vault token accounts, the source is the vault
+            // token account (its mint for CreateTokenAccount) and the destination the
+            // delegate for ApproveDelegate, the vault associated token account for
+            // CreateTokenAccount and otherwise the vault itself
+            let source = next_account_info(account_info_iter)?;
+            let (vault_address, _vault_bump_seed) =
+                get_vault_address_with_seed(squad_account.key, vault_index, program_id);
+            let mut target_address = vault_address;
+
+            let accounts_check = match FromPrimitive::from_u8(proposal_type) {
+                Some(ProposalType::CreateTokenAccount) => {
+                    get_token_mint(source)?;
+                    let ata = next_account_info(account_info_iter)?;
+                    target_address = *ata.key;
+                    *ata.key
+                        == spl_associated_token_account::get_associated_token_address(
+                            &vault_address,
+                            source.key,
+                        )
+                        && ata.data_is_empty()
+                }
+                Some(ProposalType::ApproveDelegate) => {
+                    let account = get_token_account(source)?;
+                    let delegate = next_account_info(account_info_iter)?;
+                    target_address = *delegate.key;
+                    account.owner == vault_address && amount > 0
+                }
+                Some(ProposalType::CloseTokenAccount) => {
+                    // the rent goes back to the vault, never the tokens
+                    let account = get_token_account(source)?;
+                    account.owner == vault_address && account.amount == 0
+                }
+                Some(ProposalType::SyncNative) => {
+                    let account = get_token_account(source)?;
+                    account.owner == vault_address && account.is_native()
+                }
+                _ => {
+                    // RevokeDelegate
+                    let account = get_token_account(source)?;
+                    account.owner == vault_address
+                }
+            };
+            if !accounts_check {
+                msg!("SQDS: Invalid vault token account");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            Proposal::save_withdraw(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                source.key,
+                &target_address,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+        }
+        Some(ProposalType::CreateMint) => {
+            // a new mint with the vault as mint and freeze authority, amount is the
+            // mint index the address derives from and minimum_out its decimals
+            let mint = next_account_info(account_info_iter)?;
+            if amount > u32::MAX as u64 || minimum_out > u8::MAX as u64 {
+                msg!("SQDS: Invalid mint index or decimals");
+                return Err(ProgramError::InvalidArgument);
+            }
+            let (mint_address, _mint_bump_seed) =
+                get_token_mint_address_with_seed(squad_account.key, amount as u32, program_id);
+            if *mint.key != mint_address || !mint.data_is_empty() {
+                msg!("SQDS: Invalid mint account");
+                return Err(ProgramError::InvalidArgument);
+            }
+            // the metadata name is the proposal title
+            let with_metadata = !token_symbol.trim_end_matches(char::from(0)).is_empty();
+            if with_metadata
+                && title.trim_end_matches(char::from(0)).len()
+                    > metaplex_token_metadata::state::MAX_NAME_LENGTH
+            {
+                msg!("SQDS: Title too long for the mint metadata name");
+                return Err(ProgramError::InvalidArgument);
+            }
+            let (vault_address, _vault_bump_seed) =
+                get_vault_address_with_seed(squad_account.key, vault_index, program_id);
+
+            Proposal::save_withdraw(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                &vault_address,
+                mint.key,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+            proposal_account_info.execution_amount_out = minimum_out;
+            proposal_account_info.token_symbol = token_symbol;
+        }
+        Some(ProposalType::UpgradeProgram) | Some(ProposalType::SetUpgradeAuthority) => {
+            // an upgradeable program the vault is the upgrade authority of, the destination
+            // is the buffer holding the new program data or the new upgrade authority
+            let program = next_account_info(account_info_iter)?;
+            let target = next_account_info(account_info_iter)?;
+            if *program.owner != bpf_loader_upgradeable::id() || !program.executable {
+                msg!("SQDS: Invalid upgradeable program account");
+                return Err(ProgramError::InvalidArgument);
+            }
+            let upgrade = proposal_type == ProposalType::UpgradeProgram as u8;
+            if upgrade && *target.owner != bpf_loader_upgradeable::id() {
+                msg!("SQDS: Invalid program buffer account");
+                return Err(ProgramError::InvalidArgument);
+            }
+            // the loader only upgrades from a buffer whose authority is the upgrade authority
+            let (vault_address, _vault_bump_seed) =
+                get_vault_address_with_seed(squad_account.key, vault_index, program_id);
+            if upgrade && get_buffer_authority(&target.data.borrow()) != Some(vault_address) {
+                msg!("SQDS: Program buffer authority is not the vault");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            Proposal::save_withdraw(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                program.key,
+                target.key,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+            if upgrade {
+                // the buffer is pinned to what members vote on, the spill account gets
+                // the buffer lamports back
+                let spill = next_account_info(account_info_iter)?;
+                proposal_account_info.buffer_hash = hash(&target.data.borrow()).to_bytes();
+                proposal_account_info.spill_address = *spill.key;
+            }
+        }
+        Some(ProposalType::AddressBookEntry) => {
+            // add (amount 1) or remove (amount 0) a withdrawal destination
+            let destination = next_account_info(account_info_iter)?;
+            if amount > 1 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Proposal::save_member(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                destination.key,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+        }
+        Some(ProposalType::UnlistedPolicy) => {
+            // threshold (amount, 0 for the usual one) and delay in seconds (minimum_out)
+            // of withdrawals to destinations missing from the address book
+            let amount_check = match squad_account_info.allocation_type {
+                // MS thresholds are limited by the members that can vote
+                2 => amount <= squad_account_info.voter_count() as u64,
+                // TS thresholds are support percents
+                1 => amount <= 100,
+                _ => false,
+            };
+            if !amount_check || minimum_out > i64::MAX as u64 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Proposal::save_core(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+            proposal_account_info.execution_amount_out = minimum_out;
+        }
+        Some(ProposalType::SupportBps) | Some(ProposalType::QuorumBps) => {
+            // support | quorum in basis points
+            if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            if amount < 1 || amount > BPS_DENOMINATOR as u64 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Proposal::save_core(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                start_timestamp,
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+        }
+        Some(ProposalType::CreateChildSquad) => {
+            // a child squad the parent vault creates and takes a seat in, amount is the child
+            // allocation type and the bytes of minimum_out its vote_quorum, vote_support,
+            // core_threshold and number of Multisig members, the title, description and token
+            // symbol are the child name, description and token
+            let settings = minimum_out.to_le_bytes();
+            let (vote_quorum, vote_support, members_num) = (settings[0], settings[1], settings[3]);
+            let child_check = match amount {
+                1 => {
+                    members_num == 0
+                        && (1..=100).contains(&vote_quorum)
+                        && (1..=100).contains(&vote_support)
+                        && token_symbol.trim_end_matches(char::from(0)).len() <= SQUAD_TOKEN_BYTES
+                }
+                // the parent vault is seated on top of the members
+                2 => vote_quorum >= 1 && vote_quorum as u16 <= members_num as u16 + 1,
+                _ => false,
+            };
+            if !child_check
+                || settings[4..].iter().any(|setting| *setting != 0)
+                || vault_index != 0
+                || title.trim_end_matches(char::from(0)).len() > SQUAD_NAME_BYTES
+                || description.trim_end_matches(char::from(0)).len() > SQUAD_DESCRIPTION_BYTES
+            {
+                msg!("SQDS: Invalid child squad settings");
+                return Err(ProgramError::InvalidArgument);
+            }
+            // the Multisig members are pinned to what the squad votes on
+            let mut members = Vec::new();
+            for _i in 0..members_num {
+                members.push(*next_account_info(account_info_iter)?.key);
+            }
+            let (sol_address, _sol_bump_seed) =
+                get_sol_address_with_seed(squad_account.key, program_id);
+
+            // the creator pays for the child when the proposal is executed
+            Proposal::save_withdraw(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                &sol_address,
+                initializer.key,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+            proposal_account_info.execution_amount_out = minimum_out;
+            proposal_account_info.token_symbol = token_symbol;
+            proposal_account_info.buffer_hash = child_members_hash(&members);
+        }
+        Some(ProposalType::ChildVote) => {
+            // the parent vault votes option amount on a proposal of a Multisig child squad
+            let child_squad = next_account_info(account_info_iter)?;
+            let child_proposal = next_account_info(account_info_iter)?;
+            let child_squad_info = get_squad(program_id, child_squad)?;
+            let child_proposal_info = get_proposal(program_id, child_squad, child_proposal)?;
+            if child_squad_info.parent != *squad_account.key
+                || child_squad_info.allocation_type != AllocationType::Multisig as u8
+                || !child_proposal_info.is_initialized
+                || amount >= child_proposal_info.votes_num as u64
+                || vault_index != 0
+            {
+                msg!("SQDS: Invalid child squad proposal or vote");
+                return Err(ProgramError::InvalidArgument);
+            }
+            let (sol_address, _sol_bump_seed) =
+                get_sol_address_with_seed(squad_account.key, program_id);
+
+            Proposal::save_withdraw(
+                &mut proposal_account_info,
+                proposal_type,
+                title,
+                description,
+                link,
+                &sol_address,
+                child_proposal.key,
+                initializer.key,
+                votes_num,
+                squad_account.key,
+                vote_labels,
+                if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+                    actual_timestamp
+                } else {
+                    start_timestamp
+                },
+                close_timestamp,
+                Clock::get().unwrap().unix_timestamp,
+                amount,
+                proposal_nonce,
+            );
+        }
+        None => {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+    proposal_account_info.vault_index = vault_index;
+    // snapshot the threshold set for this proposal type, the config account
+    // follows the type specific accounts once the squad has one
+    let mut optimistic_veto = 0;
+    let mut challenge_window = 0;
+    let mut proposal_expiry = 0;
+    if squad_account_info.has_config {
+        let config_account = next_account_info(account_info_iter)?;
+        let config = squad_config_info(squad_account, config_account, program_id)?;
+        proposal_account_info.type_threshold = config.type_threshold(proposal_type);
+        optimistic_veto =
+            config.optimistic_veto(proposal_type, proposal_account_info.execution_amount);
+        if optimistic_veto > 0 {
+            challenge_window = config.challenge_window;
+        }
+        if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+            proposal_expiry = config.proposal_expiry;
+            proposal_account_info.execution_window = config.execution_window;
+        }
+    }
+    // scheduled proposals can't be executed before the requested time
+    proposal_account_info.execute_after = execute_after;
+    // a Multisig proposal without a close_timestamp is open for the squad default
+    // expiry, or until it is approved if the squad has none
+    if squad_account_info.allocation_type == AllocationType::Multisig as u8
+        && proposal_account_info.close_timestamp == 0
+    {
+        proposal_account_info.close_timestamp = if proposal_expiry > 0 {
+            actual_timestamp.saturating_add(proposal_expiry)
+        } else {
+            UnixTimestamp::MAX
+        };
+    }
+    // withdrawals record whether their destination is in the address book, which
+    // comes after the config account, unlisted ones take its threshold and delay
+    if squad_account_info.has_address_book {
+        let address_book_account = next_account_info(account_info_iter)?;
+        let address_book = address_book_info(squad_account, address_book_account, program_id)?;
+        if address_book_checked(proposal_type) {
+            proposal_account_info.destination_allowlisted =
+                address_book.contains(&proposal_account_info.execution_destination);
+            if !proposal_account_info.destination_allowlisted {
+                proposal_account_info.unlisted_threshold = address_book.unlisted_threshold;
+                // kept apart from execute_after, the delay doesn't open keeper execution
+                proposal_account_info.unlisted_until =
+                    actual_timestamp.saturating_add(address_book.unlisted_delay);
+            }
+        }
+    }
+    // eligible proposals pass once the challenge window closes without enough vetoes,
+    // unless they need the core council or go to an unlisted destination
+    if optimistic_veto > 0
+        && proposal_account_info.unlisted_threshold == 0
+        && squad_account_info.proposal_core_threshold(&proposal_account_info) == 0
+    {
+        proposal_account_info.make_optimistic(optimistic_veto, challenge_window, actual_timestamp);
+    }
+    // snapshot the membership, members added afterwards can't vote on the proposal
+    proposal_account_info.membership_version = squad_account_info.membership_version;
+    proposal_account_info.members_at_creation =
+        if squad_account_info.allocation_type == AllocationType::Multisig as u8 {
+            squad_account_info.voter_count() as u8
+        } else {
+            squad_account_info.members.len() as u8
+        };
+
+    // a proposal whose voting starts later is a draft until then
+    if proposal_account_info.start_timestamp > actual_timestamp {
+        proposal_account_info.status = ProposalStatus::Draft;
     }
 
     Proposal::pack(
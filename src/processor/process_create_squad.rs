@@ -13,7 +13,7 @@ use solana_program::{
 use spl_token::instruction::initialize_mint;
 
 use crate::state::squad::AllocationType;
-use crate::{state::squad::Squad, *};
+use crate::{governance::BPS_DENOMINATOR, state::squad::Squad, *};
 
 pub fn process_create_squad(
     accounts: &[AccountInfo],
@@ -25,6 +25,10 @@ pub fn process_create_squad(
     description: String,
     token: String,
     random_id: String,
+    vote_support_bps: u16,
+    vote_quorum_bps: u16,
+    token_gated: bool,
+    min_deposit: u64,
     program_id: &Pubkey,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -81,41 +85,50 @@ pub fn process_create_squad(
         &[&squad_signer_seeds],
     )?;
 
-    let (mint_owner_address, mint_bump_seed) =
-        get_mint_address_with_seed(&squad_account.key, &program_id);
-    if mint_owner_address != *mint_owner.key {
-        msg!("Error: mint address derivation mismatch");
-        return Err(ProgramError::InvalidAccountData);
+    if token_gated {
+        // adopt an existing mint, members deposit it instead of being minted equity
+        if *mint_owner.owner != spl_token::id() {
+            msg!("SQDS: Governance mint not owned by token program");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        spl_token::state::Mint::unpack(&mint_owner.data.borrow())?;
+    } else {
+        let (mint_owner_address, mint_bump_seed) =
+            get_mint_address_with_seed(squad_account.key, program_id);
+        if mint_owner_address != *mint_owner.key {
+            msg!("Error: mint address derivation mismatch");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mint_signer_seeds: &[&[_]] = &[
+            &squad_account.key.to_bytes(),
+            b"!squadmint",
+            &[mint_bump_seed],
+        ];
+
+        // create the squads governance mint account
+        invoke_signed(
+            &create_account(
+                payer.key,
+                mint_owner.key,
+                1.max(rent.minimum_balance(spl_token::state::Mint::get_packed_len())),
+                spl_token::state::Mint::get_packed_len() as u64,
+                &spl_token::id(),
+            ),
+            &[payer.clone(), mint_owner.clone(), system_account.clone()],
+            &[mint_signer_seeds],
+        )?;
+
+        // initialize the squad governance mint account
+        invoke_signed(
+            &initialize_mint(&spl_token::id(), mint_owner.key, mint_owner.key, None, 0)?,
+            &[
+                token_program_account.clone(),
+                rent_sysvar_info.clone(),
+                mint_owner.clone(),
+            ],
+            &[mint_signer_seeds],
+        )?;
     }
-    let mint_signer_seeds: &[&[_]] = &[
-        &squad_account.key.to_bytes(),
-        b"!squadmint",
-        &[mint_bump_seed],
-    ];
-
-    // create the squads governance mint account
-    invoke_signed(
-        &create_account(
-            payer.key,
-            mint_owner.key,
-            1.max(rent.minimum_balance(spl_token::state::Mint::get_packed_len())),
-            spl_token::state::Mint::get_packed_len() as u64,
-            &spl_token::id(),
-        ),
-        &[payer.clone(), mint_owner.clone(), system_account.clone()],
-        &[&mint_signer_seeds],
-    )?;
-
-    // initialize the squad governance mint account
-    invoke_signed(
-        &initialize_mint(&spl_token::id(), mint_owner.key, mint_owner.key, None, 0)?,
-        &[
-            token_program_account.clone(),
-            rent_sysvar_info.clone(),
-            mint_owner.clone(),
-        ],
-        &[&mint_signer_seeds],
-    )?;
 
     let mut squad_info = get_squad(program_id, squad_account)?;
 
@@ -132,6 +145,11 @@ pub fn process_create_squad(
         return Err(ProgramError::InvalidArgument);
     }
 
+    // the bps settings are optional and take over from the whole percents when set
+    if vote_quorum_bps > BPS_DENOMINATOR || vote_support_bps > BPS_DENOMINATOR {
+        return Err(ProgramError::InvalidArgument);
+    }
+
     let (sol_account_owner_pda, _sol_account_bump_seed) =
         get_sol_address_with_seed(&squad_account.key, &program_id);
 
@@ -149,6 +167,14 @@ pub fn process_create_squad(
         &sol_account_owner_pda,
         random_id,
     );
+    squad_info.vote_support_bps = vote_support_bps;
+    squad_info.vote_quorum_bps = vote_quorum_bps;
+    // token-gated members join by depositing, there is no draft allocation
+    if token_gated {
+        squad_info.token_gated = true;
+        squad_info.open = false;
+        squad_info.min_deposit = min_deposit;
+    }
 
     Squad::pack(squad_info, &mut squad_account.data.borrow_mut())?;
     Ok(())
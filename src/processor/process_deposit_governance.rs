@@ -0,0 +1,210 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::{allocate, assign, create_account, transfer},
+    sysvar::Sysvar,
+};
+use spl_token::{instruction::initialize_account, state::Account};
+
+use crate::processor::process_record_checkpoint;
+use crate::{
+    state::squad::{Member, Squad, MEMBER_ROLE_ALL},
+    *,
+};
+
+pub fn process_deposit_governance(
+    accounts: &[AccountInfo],
+    amount: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let depositor = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    let governance_mint_account = next_account_info(account_info_iter)?;
+    let source_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let escrow_authority = next_account_info(account_info_iter)?;
+    let member_checkpoints_account = next_account_info(account_info_iter)?;
+    let supply_checkpoints_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(rent_account)?;
+
+    if !depositor.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut squad_account_info = get_squad(program_id, squad_account)?;
+    if !squad_account_info.token_gated {
+        msg!("SQDS: Squad isn't token gated");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if *governance_mint_account.key != squad_account_info.mint_address {
+        msg!("SQDS: Incorrect governance mint address");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if *token_program_account.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (escrow_address, escrow_bump_seed) =
+        get_equity_address_with_seed(depositor.key, squad_account.key, program_id);
+    if escrow_address != *escrow_account.key {
+        msg!("SQDS: Invalid member governance address");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // the escrows are held by the squad mint PDA, which is never a mint in a token-gated squad
+    let (escrow_authority_address, _escrow_authority_bump_seed) =
+        get_mint_address_with_seed(squad_account.key, program_id);
+    if escrow_authority_address != *escrow_authority.key {
+        msg!("SQDS: Escrow authority address mismatch");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if escrow_account.data_is_empty() {
+        let escrow_signer_seeds: &[&[_]] = &[
+            &depositor.key.to_bytes(),
+            &squad_account.key.to_bytes(),
+            b"!memberequity",
+            &[escrow_bump_seed],
+        ];
+
+        // DoS check
+        let rent_exempt_lamports = rent.minimum_balance(Account::get_packed_len()).max(1);
+        if escrow_account.lamports() > 0 {
+            let top_up_lamports = rent_exempt_lamports.saturating_sub(escrow_account.lamports());
+
+            if top_up_lamports > 0 {
+                invoke(
+                    &transfer(depositor.key, escrow_account.key, top_up_lamports),
+                    &[
+                        depositor.clone(),
+                        escrow_account.clone(),
+                        system_program_account.clone(),
+                    ],
+                )?;
+            }
+
+            invoke_signed(
+                &allocate(escrow_account.key, Account::get_packed_len() as u64),
+                &[escrow_account.clone(), system_program_account.clone()],
+                &[escrow_signer_seeds],
+            )?;
+
+            invoke_signed(
+                &assign(escrow_account.key, &spl_token::id()),
+                &[escrow_account.clone(), system_program_account.clone()],
+                &[escrow_signer_seeds],
+            )?;
+        } else {
+            invoke_signed(
+                &create_account(
+                    depositor.key,
+                    &escrow_address,
+                    rent_exempt_lamports,
+                    Account::get_packed_len() as u64,
+                    &spl_token::id(),
+                ),
+                &[
+                    depositor.clone(),
+                    escrow_account.clone(),
+                    system_program_account.clone(),
+                ],
+                &[escrow_signer_seeds],
+            )?;
+        }
+
+        invoke(
+            &initialize_account(
+                &spl_token::id(),
+                &escrow_address,
+                governance_mint_account.key,
+                escrow_authority.key,
+            )?,
+            &[
+                token_program_account.clone(),
+                escrow_account.clone(),
+                governance_mint_account.clone(),
+                escrow_authority.clone(),
+                rent_account.clone(),
+            ],
+        )?;
+    }
+
+    let previous_amount = Account::unpack(&escrow_account.data.borrow())?.amount;
+    // dust deposits can't take member seats
+    if !squad_account_info.escrow_balance_allowed(previous_amount + amount) {
+        msg!("SQDS: Deposit below the squad minimum");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    invoke(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            source_account.key,
+            escrow_account.key,
+            depositor.key,
+            &[],
+            amount,
+        )?,
+        &[
+            source_account.clone(),
+            escrow_account.clone(),
+            depositor.clone(),
+            token_program_account.clone(),
+        ],
+    )?;
+
+    // the deposit only weighs on the proposals created after it
+    process_record_checkpoint(
+        depositor,
+        squad_account,
+        member_checkpoints_account,
+        system_program_account,
+        rent,
+        depositor.key,
+        previous_amount,
+        previous_amount + amount,
+        program_id,
+    )?;
+    process_record_checkpoint(
+        depositor,
+        squad_account,
+        supply_checkpoints_account,
+        system_program_account,
+        rent,
+        governance_mint_account.key,
+        squad_account_info.deposited_supply,
+        squad_account_info.deposited_supply + amount,
+        program_id,
+    )?;
+    squad_account_info.deposited_supply += amount;
+
+    // depositing is how a member joins a token-gated squad
+    if !Squad::member_exists(&squad_account_info, depositor.key) {
+        squad_account_info.membership_version += 1;
+        let joined_version = squad_account_info.membership_version;
+        Squad::add_member(
+            &mut squad_account_info,
+            *depositor.key,
+            Member {
+                equity_token_account: escrow_address,
+                role: MEMBER_ROLE_ALL,
+                joined_version,
+            },
+        )?;
+    }
+
+    Squad::pack(squad_account_info, &mut squad_account.data.borrow_mut())?;
+    Ok(())
+}
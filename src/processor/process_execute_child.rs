@@ -0,0 +1,284 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    hash::hashv,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::state::{
+    proposal::Proposal,
+    squad::{
+        AllocationType, SQUAD_DESCRIPTION_BYTES, SQUAD_NAME_BYTES, SQUAD_RANDOM_ID_BYTES,
+        SQUAD_TOKEN_BYTES,
+    },
+};
+use crate::*;
+
+// instruction tags of the child squad instructions the parent vault signs
+const CAST_MULTISIG_VOTE_TAG: u8 = 4;
+const CREATE_CHILD_SQUAD_TAG: u8 = 11;
+
+/// Hash of the Multisig members a CreateChildSquad proposal seats in the child, in order
+pub(crate) fn child_members_hash(members: &[Pubkey]) -> [u8; 32] {
+    let member_bytes: Vec<&[u8]> = members.iter().map(|member| member.as_ref()).collect();
+    hashv(&member_bytes).to_bytes()
+}
+
+// the CreateChildSquad settings are the proposal amount (allocation type) and the bytes of
+// minimum_out: vote_quorum, vote_support, core_threshold and the Multisig members number
+fn create_child_squad_data(proposal: &Proposal) -> Vec<u8> {
+    let settings = proposal.execution_amount_out.to_le_bytes();
+    let mut data = vec![
+        CREATE_CHILD_SQUAD_TAG,
+        proposal.execution_amount as u8,
+        settings[1],
+        settings[0],
+        settings[2],
+    ];
+    // the name, description and token of the child are the proposal title, description
+    // and token symbol, the child gets no random_id
+    for (field, len) in [
+        (&proposal.title, SQUAD_NAME_BYTES),
+        (&proposal.description, SQUAD_DESCRIPTION_BYTES),
+        (&proposal.token_symbol, SQUAD_TOKEN_BYTES),
+    ] {
+        let mut bytes = field.trim_end_matches(char::from(0)).as_bytes().to_vec();
+        bytes.resize(len, 0);
+        data.extend_from_slice(&bytes);
+    }
+    data.extend_from_slice(&[0; SQUAD_RANDOM_ID_BYTES]);
+    data.push(settings[3]);
+    data
+}
+
+// creates the child squad of the proposal with CreateChildSquad signed by the parent vault,
+// the proposal creator pays for the child and drafts the members of a Team child
+pub fn process_execute_create_child_squad(
+    accounts: &[AccountInfo],
+    proposal_account_info: &Proposal,
+    allocation_type: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let _executioner = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    if allocation_type == AllocationType::TeamCoordination as u8 {
+        next_account_info(account_info_iter)?; // squad_mint_account
+    }
+    let _proposal_account = next_account_info(account_info_iter)?;
+    let sol_account = next_account_info(account_info_iter)?;
+    let payer = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let _associated_program_account = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let child_squad_account = next_account_info(account_info_iter)?;
+    let squads_program_account = next_account_info(account_info_iter)?;
+
+    if squads_program_account.key != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !payer.is_signer {
+        msg!("SQDS: The proposal creator has to sign to create the child squad");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (sol_address, sol_bump_seed) = get_sol_address_with_seed(squad_account.key, program_id);
+    if *sol_account.key != sol_address {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut child_metas = vec![
+        AccountMeta::new(*payer.key, true),
+        AccountMeta::new(*squad_account.key, false),
+        AccountMeta::new_readonly(sol_address, true),
+        AccountMeta::new(*child_squad_account.key, false),
+        AccountMeta::new_readonly(*system_program_account.key, false),
+        AccountMeta::new_readonly(*rent_account.key, false),
+    ];
+    let mut child_accounts = vec![
+        payer.clone(),
+        squad_account.clone(),
+        sol_account.clone(),
+        child_squad_account.clone(),
+        system_program_account.clone(),
+        rent_account.clone(),
+    ];
+    if proposal_account_info.execution_amount == AllocationType::TeamCoordination as u64 {
+        let child_mint_account = next_account_info(account_info_iter)?;
+        child_metas.push(AccountMeta::new(*child_mint_account.key, false));
+        child_metas.push(AccountMeta::new_readonly(*token_program_account.key, false));
+        child_accounts.push(child_mint_account.clone());
+        child_accounts.push(token_program_account.clone());
+    } else {
+        // the members are pinned to the ones the parent voted on
+        let members_num = proposal_account_info.execution_amount_out.to_le_bytes()[3];
+        let mut members = Vec::new();
+        for _i in 0..members_num {
+            let member = next_account_info(account_info_iter)?;
+            child_metas.push(AccountMeta::new_readonly(*member.key, false));
+            child_accounts.push(member.clone());
+            members.push(*member.key);
+        }
+        if child_members_hash(&members) != proposal_account_info.buffer_hash {
+            msg!("SQDS: Child squad members don't match the proposal");
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+    child_accounts.push(squads_program_account.clone());
+
+    let sol_seeds = get_vault_signer_seeds(squad_account.key, 0, sol_bump_seed);
+    let sol_seeds_refs: Vec<&[u8]> = sol_seeds.iter().map(|seed| seed.as_slice()).collect();
+    let sol_signer_seeds: &[&[_]] = &sol_seeds_refs;
+
+    invoke_signed(
+        &Instruction {
+            program_id: *program_id,
+            accounts: child_metas,
+            data: create_child_squad_data(proposal_account_info),
+        },
+        &child_accounts,
+        &[sol_signer_seeds],
+    )
+}
+
+// casts the parent vault vote of the proposal on a proposal of a Multisig child squad,
+// the vault pays the rent of its vote record
+pub fn process_execute_child_vote(
+    accounts: &[AccountInfo],
+    proposal_account_info: &Proposal,
+    allocation_type: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let _executioner = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    if allocation_type == AllocationType::TeamCoordination as u8 {
+        next_account_info(account_info_iter)?; // squad_mint_account
+    }
+    let _proposal_account = next_account_info(account_info_iter)?;
+    let sol_account = next_account_info(account_info_iter)?;
+    let child_proposal_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let _token_program_account = next_account_info(account_info_iter)?;
+    let _associated_program_account = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let child_squad_account = next_account_info(account_info_iter)?;
+    let vote_account = next_account_info(account_info_iter)?;
+    let squads_program_account = next_account_info(account_info_iter)?;
+
+    if squads_program_account.key != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (sol_address, sol_bump_seed) = get_sol_address_with_seed(squad_account.key, program_id);
+    if *sol_account.key != sol_address {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // the child proposal has to belong to a child of this squad
+    let child_squad_info = get_squad(program_id, child_squad_account)?;
+    get_proposal(program_id, child_squad_account, child_proposal_account)?;
+    if child_squad_info.parent != *squad_account.key {
+        msg!("SQDS: The proposal squad isn't a child of this squad");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let sol_seeds = get_vault_signer_seeds(squad_account.key, 0, sol_bump_seed);
+    let sol_seeds_refs: Vec<&[u8]> = sol_seeds.iter().map(|seed| seed.as_slice()).collect();
+    let sol_signer_seeds: &[&[_]] = &sol_seeds_refs;
+
+    invoke_signed(
+        &Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(sol_address, true),
+                AccountMeta::new(*child_squad_account.key, false),
+                AccountMeta::new(*child_proposal_account.key, false),
+                AccountMeta::new(*vote_account.key, false),
+                AccountMeta::new_readonly(*system_program_account.key, false),
+                AccountMeta::new_readonly(*rent_account.key, false),
+                AccountMeta::new_readonly(*program_id, false),
+            ],
+            data: vec![
+                CAST_MULTISIG_VOTE_TAG,
+                proposal_account_info.execution_amount as u8,
+            ],
+        },
+        &[
+            sol_account.clone(),
+            child_squad_account.clone(),
+            child_proposal_account.clone(),
+            vote_account.clone(),
+            system_program_account.clone(),
+            rent_account.clone(),
+            squads_program_account.clone(),
+        ],
+        &[sol_signer_seeds],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::SquadInstruction;
+    use solana_program::program_pack::Pack;
+
+    fn child_proposal(allocation_type: u64, settings: [u8; 4]) -> Proposal {
+        let proposal_arr = vec![0; Proposal::get_packed_len()];
+        let mut proposal_info = Proposal::unpack_unchecked(&proposal_arr).unwrap();
+        proposal_info.execution_amount = allocation_type;
+        proposal_info.execution_amount_out = u32::from_le_bytes(settings) as u64;
+        proposal_info.title = String::from("child");
+        proposal_info.description = String::from("a child squad");
+        proposal_info.token_symbol = String::from("CHLD");
+        proposal_info
+    }
+
+    #[test]
+    fn create_child_squad_data_unpacks() {
+        let proposal_info = child_proposal(AllocationType::Multisig as u64, [2, 51, 1, 3]);
+        let data = create_child_squad_data(&proposal_info);
+        match SquadInstruction::unpack(&data).unwrap() {
+            SquadInstruction::CreateChildSquad {
+                allocation_type,
+                vote_support,
+                vote_quorum,
+                core_threshold,
+                squad_name,
+                description,
+                token,
+                random_id,
+                members_num,
+            } => {
+                assert_eq!(allocation_type, AllocationType::Multisig as u8);
+                assert_eq!((vote_quorum, vote_support, core_threshold), (2, 51, 1));
+                assert_eq!(members_num, 3);
+                assert_eq!(squad_name.len(), SQUAD_NAME_BYTES);
+                assert_eq!(squad_name.trim_end_matches(char::from(0)), "child");
+                assert_eq!(description.trim_end_matches(char::from(0)), "a child squad");
+                assert_eq!(token.trim_end_matches(char::from(0)), "CHLD");
+                assert_eq!(random_id, "\0".repeat(SQUAD_RANDOM_ID_BYTES));
+            }
+            _ => panic!("not a CreateChildSquad instruction"),
+        }
+    }
+
+    #[test]
+    fn child_members_hash_follows_order() {
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(
+            child_members_hash(&[first, second]),
+            child_members_hash(&[first, second])
+        );
+        assert_ne!(
+            child_members_hash(&[first, second]),
+            child_members_hash(&[second, first])
+        );
+        assert_ne!(child_members_hash(&[first]), child_members_hash(&[]));
+    }
+}
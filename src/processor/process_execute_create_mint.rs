@@ -0,0 +1,180 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::{allocate, assign, create_account, transfer},
+    sysvar::Sysvar,
+};
+use spl_token::{instruction::initialize_mint, state::Mint};
+
+use crate::state::{proposal::Proposal, squad::AllocationType};
+use crate::*;
+
+// creates the mint of the proposal with the vault as mint and freeze authority, then
+// its metadata account when the proposal has a token symbol, the executioner pays rent
+pub fn process_execute_create_mint(
+    accounts: &[AccountInfo],
+    proposal_account_info: &Proposal,
+    allocation_type: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let executioner = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    if allocation_type == AllocationType::TeamCoordination as u8 {
+        next_account_info(account_info_iter)?; // squad_mint_account
+    }
+    let _proposal_account = next_account_info(account_info_iter)?;
+    let sol_account = next_account_info(account_info_iter)?;
+    let mint_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let _associated_program_account = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(rent_account)?;
+
+    if *token_program_account.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (sol_address, sol_bump_seed) = get_vault_address_with_seed(
+        squad_account.key,
+        proposal_account_info.vault_index,
+        program_id,
+    );
+    if *sol_account.key != sol_address {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mint_index = proposal_account_info.execution_amount as u32;
+    let (mint_address, mint_bump_seed) =
+        get_token_mint_address_with_seed(squad_account.key, mint_index, program_id);
+    if *mint_account.key != mint_address {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let mint_signer_seeds: &[&[_]] = &[
+        &squad_account.key.to_bytes(),
+        &mint_index.to_le_bytes(),
+        b"!mint",
+        &[mint_bump_seed],
+    ];
+
+    // DoS check
+    let rent_exempt_lamports = rent.minimum_balance(Mint::LEN).max(1);
+    if mint_account.lamports() > 0 {
+        let top_up_lamports = rent_exempt_lamports.saturating_sub(mint_account.lamports());
+
+        if top_up_lamports > 0 {
+            invoke(
+                &transfer(executioner.key, mint_account.key, top_up_lamports),
+                &[
+                    executioner.clone(),
+                    mint_account.clone(),
+                    system_program_account.clone(),
+                ],
+            )?;
+        }
+
+        invoke_signed(
+            &allocate(mint_account.key, Mint::LEN as u64),
+            &[mint_account.clone(), system_program_account.clone()],
+            &[mint_signer_seeds],
+        )?;
+
+        invoke_signed(
+            &assign(mint_account.key, &spl_token::id()),
+            &[mint_account.clone(), system_program_account.clone()],
+            &[mint_signer_seeds],
+        )?;
+    } else {
+        invoke_signed(
+            &create_account(
+                executioner.key,
+                &mint_address,
+                rent_exempt_lamports,
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            &[
+                executioner.clone(),
+                mint_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[mint_signer_seeds],
+        )?;
+    }
+
+    invoke(
+        &initialize_mint(
+            &spl_token::id(),
+            &mint_address,
+            &sol_address,
+            Some(&sol_address),
+            proposal_account_info.execution_amount_out as u8,
+        )?,
+        &[
+            mint_account.clone(),
+            rent_account.clone(),
+            token_program_account.clone(),
+        ],
+    )?;
+
+    let token_symbol = proposal_account_info
+        .token_symbol
+        .trim_end_matches(char::from(0));
+    if token_symbol.is_empty() {
+        return Ok(());
+    }
+
+    let metadata_account = next_account_info(account_info_iter)?;
+    let metadata_program_account = next_account_info(account_info_iter)?;
+    if *metadata_program_account.key != metaplex_token_metadata::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let sol_seeds = get_vault_signer_seeds(
+        squad_account.key,
+        proposal_account_info.vault_index,
+        sol_bump_seed,
+    );
+    let sol_seeds_refs: Vec<&[u8]> = sol_seeds.iter().map(|seed| seed.as_slice()).collect();
+    let sol_signer_seeds: &[&[_]] = &sol_seeds_refs;
+
+    // the vault signs as mint authority and keeps the metadata update authority
+    invoke_signed(
+        &metaplex_token_metadata::instruction::create_metadata_accounts(
+            metaplex_token_metadata::id(),
+            *metadata_account.key,
+            mint_address,
+            sol_address,
+            *executioner.key,
+            sol_address,
+            proposal_account_info
+                .title
+                .trim_end_matches(char::from(0))
+                .to_string(),
+            token_symbol.to_string(),
+            proposal_account_info
+                .link
+                .trim_end_matches(char::from(0))
+                .to_string(),
+            None,
+            0,
+            true,
+            true,
+        ),
+        &[
+            metadata_account.clone(),
+            mint_account.clone(),
+            sol_account.clone(),
+            executioner.clone(),
+            system_program_account.clone(),
+            rent_account.clone(),
+            metadata_program_account.clone(),
+        ],
+        &[sol_signer_seeds],
+    )
+}
@@ -3,10 +3,12 @@ use solana_program::{
     account_info::{next_account_info, AccountInfo},
     clock::Clock,
     entrypoint::ProgramResult,
+    msg,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
+    rent::Rent,
     system_instruction::transfer,
     sysvar::Sysvar,
 };
@@ -14,14 +16,22 @@ use solana_program::{
 use spl_associated_token_account::create_associated_token_account;
 
 use crate::{
+    governance::{evaluate, record_outcome, Outcome, SquadParams},
     state::{
-        proposal::Proposal,
-        squad::{Member, Squad},
+        proposal::{Proposal, ProposalStatus},
+        squad::{Member, Squad, MEMBER_ROLE_ALL, MEMBER_ROLE_EXECUTOR},
     },
     *, // error::SquadError
 };
 
-use crate::processor::process_execute_swap;
+use crate::processor::{
+    process_execute_cancel_stream, process_execute_child_vote, process_execute_create_child_squad,
+    process_execute_create_mint, process_execute_create_stream, process_execute_stake,
+    process_execute_swap, process_execute_token_account, process_execute_token_authority,
+    process_execute_upgrade, process_set_address_book_entry, process_set_challenge_window,
+    process_set_optimistic_veto, process_set_proposal_expiry, process_set_type_threshold,
+    process_set_unlisted_policy,
+};
 use crate::state::proposal::ProposalType;
 use crate::state::squad::AllocationType;
 
@@ -69,58 +79,95 @@ pub fn process_execute_multisig_proposal(
         return Err(ProgramError::InvalidAccountData);
     }
 
-    if !Squad::member_exists(&squad_account_info, executioner.key) {
+    // executor members can execute any approved proposal, any other signer is a keeper:
+    // it can execute a scheduled proposal, or any approved one once the squad opts in
+    let keeper =
+        !Squad::member_has_role(&squad_account_info, executioner.key, MEMBER_ROLE_EXECUTOR);
+    if keeper && proposal_account_info.execute_after == 0 && !squad_account_info.keeper_execution {
         return Err(ProgramError::InvalidArgument);
     }
+    // the tip as set before this execution, a KeeperPolicy proposal can change it
+    let keeper_tip = if keeper && squad_account_info.keeper_execution {
+        squad_account_info.keeper_tip
+    } else {
+        0
+    };
 
     // check if the proposal is actually executable
     if proposal_account_info.proposal_type < 1 {
         return Err(ProgramError::InvalidArgument);
     }
 
-    // check if the proposal has already been executed
-    if proposal_account_info.executed == true {
+    // check if the proposal has already been executed or closed
+    if proposal_account_info.is_closed() {
         return Err(ProgramError::InvalidArgument);
     }
 
-    let pass_votes = *proposal_account_info.votes.get(0).unwrap();
-
-    // check threshold
-    let threshold_reached;
-    if proposal_account_info.execute_ready {
-        threshold_reached = pass_votes as f32 >= proposal_account_info.threshold_at_execute as f32;
-    } else {
-        threshold_reached = pass_votes as f32 >= squad_account_info.vote_quorum as f32;
+    // check threshold, as saved once the proposal was execute ready
+    let vote_params = SquadParams::at_execute(&squad_account_info, &proposal_account_info);
+    match evaluate(
+        &proposal_account_info,
+        &vote_params,
+        0,
+        Clock::get().unwrap().unix_timestamp,
+    ) {
+        Outcome::Passed => {}
+        Outcome::Expired => {
+            msg!("SQDS: Proposal has expired");
+            return Err(ProgramError::InvalidArgument);
+        }
+        _ => return Err(ProgramError::InvalidArgument),
     }
 
-    if !threshold_reached {
+    // withdrawals to unlisted destinations wait out the address book delay
+    if proposal_account_info.is_time_locked(Clock::get().unwrap().unix_timestamp) {
+        msg!("SQDS: Proposal is time-locked until its execute_after or address book delay");
         return Err(ProgramError::InvalidArgument);
     }
 
+    let now = Clock::get().unwrap().unix_timestamp;
+    record_outcome(&mut proposal_account_info, Outcome::Passed, now)?;
+    // a vault short of funds fails the execution without closing the proposal,
+    // it can be executed again once the vault is topped up
+    if vault_is_short(&proposal_account_info, source_account)? {
+        msg!("SQDS: Vault can't cover the withdrawal, execution failed");
+        proposal_account_info.set_status(ProposalStatus::ExecutionFailed)?;
+        Proposal::pack(
+            proposal_account_info,
+            &mut proposal_account.data.borrow_mut(),
+        )?;
+        return Ok(());
+    }
+
     match FromPrimitive::from_u8(proposal_account_info.proposal_type) {
+        Some(ProposalType::KeeperPolicy) => {
+            squad_account_info.keeper_execution = proposal_account_info.execution_amount_out == 1;
+            squad_account_info.keeper_tip = proposal_account_info.execution_amount;
+        }
         Some(ProposalType::Quorum) => {
             // change quorum (threshold)
             squad_account_info.vote_quorum = proposal_account_info.execution_amount as u8;
         }
         Some(ProposalType::WithdrawSol) => {
-            // withdraw SOL
-            // check the source account is the squad sol_acccount
-            if source_account.key != &squad_account_info.sol_account {
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let (sol_address, sol_bump_seed) =
-                get_sol_address_with_seed(&squad_account.key, program_id);
+            // withdraw SOL from the proposal vault
+            let (sol_address, sol_bump_seed) = get_vault_address_with_seed(
+                squad_account.key,
+                proposal_account_info.vault_index,
+                program_id,
+            );
+            let sol_seeds = get_vault_signer_seeds(
+                squad_account.key,
+                proposal_account_info.vault_index,
+                sol_bump_seed,
+            );
+            let sol_seeds_refs: Vec<&[u8]> = sol_seeds.iter().map(|seed| seed.as_slice()).collect();
+            let sol_signer_seeds: &[&[_]] = &sol_seeds_refs;
 
-            // check that the derived sol address is the source account
+            // check that the derived vault address is the source account
             if *source_account.key != sol_address {
                 return Err(ProgramError::InvalidAccountData);
             }
 
-            let sol_signer_seeds: &[&[_]] = &[
-                &squad_account.key.to_bytes(),
-                b"!squadsol",
-                &[sol_bump_seed],
-            ];
             let transfer_ix = transfer(
                 &sol_address,
                 &destination_account.key,
@@ -142,7 +189,20 @@ pub fn process_execute_multisig_proposal(
             let token_mint = next_account_info(account_info_iter)?;
             let sol_account = next_account_info(account_info_iter)?;
 
-            if sol_account.key != &squad_account_info.sol_account {
+            // the proposal vault is the authority over the source token account
+            let (sol_address, sol_bump_seed) = get_vault_address_with_seed(
+                squad_account.key,
+                proposal_account_info.vault_index,
+                program_id,
+            );
+            let sol_seeds = get_vault_signer_seeds(
+                squad_account.key,
+                proposal_account_info.vault_index,
+                sol_bump_seed,
+            );
+            let sol_seeds_refs: Vec<&[u8]> = sol_seeds.iter().map(|seed| seed.as_slice()).collect();
+            let sol_signer_seeds: &[&[_]] = &sol_seeds_refs;
+            if sol_account.key != &sol_address {
                 return Err(ProgramError::InvalidInstructionData);
             }
 
@@ -156,14 +216,6 @@ pub fn process_execute_multisig_proposal(
                 return Err(ProgramError::InvalidAccountData);
             }
 
-            let (sol_address, sol_bump_seed) =
-                get_sol_address_with_seed(&squad_account.key, program_id);
-            let sol_signer_seeds: &[&[_]] = &[
-                &squad_account.key.to_bytes(),
-                b"!squadsol",
-                &[sol_bump_seed],
-            ];
-
             if destination_ata.data_is_empty() {
                 invoke(
                     &create_associated_token_account(
@@ -211,13 +263,18 @@ pub fn process_execute_multisig_proposal(
                 return Err(ProgramError::InvalidArgument);
             }
 
+            // pending proposals keep the membership they were created with
+            squad_account_info.membership_version += 1;
+            let joined_version = squad_account_info.membership_version;
             Squad::add_member(
                 &mut squad_account_info,
                 *destination_account.key,
                 Member {
                     equity_token_account: *destination_account.key,
+                    role: MEMBER_ROLE_ALL,
+                    joined_version,
                 },
-            );
+            )?;
         }
         Some(ProposalType::RemoveMember) => {
             // remove member
@@ -225,11 +282,222 @@ pub fn process_execute_multisig_proposal(
                 return Err(ProgramError::InvalidArgument);
             }
 
-            if squad_account_info.vote_quorum == squad_account_info.members.len() as u8 {
-                squad_account_info.vote_quorum -= 1;
+            Squad::remove_member(&mut squad_account_info, destination_account.key);
+            squad_account_info.membership_version += 1;
+
+            // keep the thresholds reachable by the remaining voters
+            let voters = squad_account_info.voter_count() as u8;
+            if squad_account_info.vote_quorum > voters {
+                squad_account_info.vote_quorum = voters;
+            }
+            for vault_threshold in squad_account_info.vault_thresholds.iter_mut() {
+                if *vault_threshold > voters {
+                    *vault_threshold = voters;
+                }
+            }
+        }
+        Some(ProposalType::SetMemberRole) => {
+            // set the member role
+            let role = proposal_account_info.execution_amount as u8;
+            if !Squad::member_exists(&squad_account_info, destination_account.key)
+                || !squad_account_info.role_keeps_thresholds(destination_account.key, role)
+            {
+                return Err(ProgramError::InvalidArgument);
             }
 
-            Squad::remove_member(&mut squad_account_info, &destination_account.key);
+            if let Some(member) = squad_account_info.members.get_mut(destination_account.key) {
+                member.role = role;
+            }
+        }
+        Some(ProposalType::ProposalExpiry) => {
+            // set the default expiry and execution window in the squad config
+            let config_account = next_account_info(account_info_iter)?;
+            process_set_proposal_expiry(
+                executioner,
+                squad_account,
+                config_account,
+                system_program_account,
+                &Rent::from_account_info(rent_account)?,
+                proposal_account_info.execution_amount as i64,
+                proposal_account_info.execution_amount_out as i64,
+                program_id,
+            )?;
+            squad_account_info.has_config = true;
+        }
+        Some(ProposalType::TypeThreshold) => {
+            // set the threshold of a proposal type in the squad config
+            let config_account = next_account_info(account_info_iter)?;
+            process_set_type_threshold(
+                executioner,
+                squad_account,
+                config_account,
+                system_program_account,
+                &Rent::from_account_info(rent_account)?,
+                proposal_account_info.execution_amount_out as u8,
+                proposal_account_info.execution_amount as u8,
+                program_id,
+            )?;
+            squad_account_info.has_config = true;
+        }
+        Some(ProposalType::OptimisticType) => {
+            // set the veto threshold of an optimistic proposal type in the squad config
+            let config_account = next_account_info(account_info_iter)?;
+            process_set_optimistic_veto(
+                executioner,
+                squad_account,
+                config_account,
+                system_program_account,
+                &Rent::from_account_info(rent_account)?,
+                proposal_account_info.execution_amount_out as u8,
+                proposal_account_info.execution_amount as u8,
+                program_id,
+            )?;
+            squad_account_info.has_config = true;
+        }
+        Some(ProposalType::OptimisticWindow) => {
+            // set the challenge window of optimistic proposals in the squad config
+            let config_account = next_account_info(account_info_iter)?;
+            process_set_challenge_window(
+                executioner,
+                squad_account,
+                config_account,
+                system_program_account,
+                &Rent::from_account_info(rent_account)?,
+                proposal_account_info.execution_amount as i64,
+                proposal_account_info.execution_amount_out,
+                program_id,
+            )?;
+            squad_account_info.has_config = true;
+        }
+        Some(ProposalType::AddressBookEntry) => {
+            // add (amount 1) or remove the destination from the address book
+            let address_book_account = next_account_info(account_info_iter)?;
+            process_set_address_book_entry(
+                executioner,
+                squad_account,
+                address_book_account,
+                system_program_account,
+                &Rent::from_account_info(rent_account)?,
+                &proposal_account_info.execution_destination,
+                proposal_account_info.execution_amount == 1,
+                program_id,
+            )?;
+            squad_account_info.has_address_book = true;
+        }
+        Some(ProposalType::UnlistedPolicy) => {
+            // threshold and delay of withdrawals to unlisted destinations
+            let address_book_account = next_account_info(account_info_iter)?;
+            process_set_unlisted_policy(
+                executioner,
+                squad_account,
+                address_book_account,
+                system_program_account,
+                &Rent::from_account_info(rent_account)?,
+                proposal_account_info.execution_amount as u8,
+                proposal_account_info.execution_amount_out as i64,
+                program_id,
+            )?;
+            squad_account_info.has_address_book = true;
+        }
+        Some(ProposalType::VaultPolicy) => {
+            // change the vault threshold
+            squad_account_info.vault_thresholds[proposal_account_info.vault_index as usize] =
+                proposal_account_info.execution_amount as u8;
+        }
+        Some(ProposalType::CreateStake)
+        | Some(ProposalType::DelegateStake)
+        | Some(ProposalType::DeactivateStake)
+        | Some(ProposalType::WithdrawStake)
+        | Some(ProposalType::SplitStake)
+        | Some(ProposalType::MergeStake) => {
+            // native stake of the proposal vault
+            process_execute_stake(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::MintTo)
+        | Some(ProposalType::BurnTokens)
+        | Some(ProposalType::SetTokenAuthority)
+        | Some(ProposalType::FreezeAccount)
+        | Some(ProposalType::ThawAccount) => {
+            // an external mint the proposal vault has authority over
+            process_execute_token_authority(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::CloseTokenAccount)
+        | Some(ProposalType::ApproveDelegate)
+        | Some(ProposalType::RevokeDelegate)
+        | Some(ProposalType::SyncNative)
+        | Some(ProposalType::CreateTokenAccount) => {
+            // housekeeping of a vault token account
+            process_execute_token_account(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::CreateMint) => {
+            // a new mint the proposal vault has authority over
+            process_execute_create_mint(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::CreateChildSquad) => {
+            // a child squad the parent vault takes a seat in
+            process_execute_create_child_squad(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+            // CreateChildSquad moved the child_index of the squad account
+            squad_account_info.child_index = get_squad(program_id, squad_account)?.child_index;
+        }
+        Some(ProposalType::ChildVote) => {
+            // the parent vault votes in a child squad
+            process_execute_child_vote(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::UpgradeProgram) | Some(ProposalType::SetUpgradeAuthority) => {
+            // a program the proposal vault is the upgrade authority of
+            process_execute_upgrade(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::CreateStream) => {
+            // escrow the stream funds out of the vault
+            process_execute_create_stream(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::CancelStream) => {
+            // stop the stream, the unaccrued balance goes back to the vault
+            process_execute_cancel_stream(
+                accounts,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
         }
         Some(ProposalType::Swap) => {
             // swap tokens
@@ -239,8 +507,11 @@ pub fn process_execute_multisig_proposal(
             let wsol_account = next_account_info(account_info_iter)?;
             let wsol_mint = next_account_info(account_info_iter)?;
 
-            let (sol_address, _sol_bump_seed) =
-                get_sol_address_with_seed(&squad_account.key, program_id);
+            let (sol_address, _sol_bump_seed) = get_vault_address_with_seed(
+                squad_account.key,
+                proposal_account_info.vault_index,
+                program_id,
+            );
 
             if sol_account.key != &sol_address {
                 return Err(ProgramError::InvalidAccountData);
@@ -301,6 +572,7 @@ pub fn process_execute_multisig_proposal(
                 accounts,
                 proposal_account_info.execution_amount,
                 proposal_account_info.execution_amount_out,
+                proposal_account_info.vault_index,
                 squad_account_info.allocation_type,
                 random_id,
                 program_id,
@@ -311,9 +583,24 @@ pub fn process_execute_multisig_proposal(
         }
     };
 
+    // the proposal vault is the last account when a keeper gets a tip, some types
+    // read their accounts again from the start
+    if keeper_tip > 0 {
+        let vault_account = accounts.last().ok_or(ProgramError::NotEnoughAccountKeys)?;
+        pay_keeper_tip(
+            squad_account.key,
+            &proposal_account_info,
+            vault_account,
+            executioner,
+            system_program_account,
+            keeper_tip,
+            program_id,
+        )?;
+    }
+
     proposal_account_info.executed_by = *executioner.key;
-    proposal_account_info.executed = true;
-    proposal_account_info.execution_date = Clock::get().unwrap().unix_timestamp;
+    proposal_account_info.set_status(ProposalStatus::Executed)?;
+    proposal_account_info.execution_date = now;
     Proposal::pack(
         proposal_account_info,
         &mut proposal_account.data.borrow_mut(),
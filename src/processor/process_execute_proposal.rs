@@ -18,16 +18,26 @@ use spl_token::{
 };
 
 use spl_associated_token_account::create_associated_token_account;
+use std::convert::TryFrom;
 
 use crate::{
+    governance::{evaluate, record_outcome, Outcome, SquadParams},
     state::{
-        proposal::Proposal,
-        squad::{Member, Squad},
+        proposal::{Proposal, ProposalStatus},
+        squad::{Member, Squad, MEMBER_ROLE_ALL, MEMBER_ROLE_CORE, MEMBER_ROLE_EXECUTOR},
+        vesting::Vesting,
     },
     *,
 };
 
-use crate::processor::process_execute_swap;
+use crate::processor::{
+    process_execute_cancel_stream, process_execute_child_vote, process_execute_create_child_squad,
+    process_execute_create_mint, process_execute_create_stream, process_execute_stake,
+    process_execute_swap, process_execute_token_account, process_execute_token_authority,
+    process_execute_upgrade, process_record_checkpoint, process_set_address_book_entry,
+    process_set_challenge_window, process_set_optimistic_veto, process_set_type_threshold,
+    process_set_unlisted_policy,
+};
 use crate::state::proposal::ProposalType;
 use crate::state::squad::AllocationType;
 
@@ -57,9 +67,9 @@ pub fn process_execute_proposal(
     let mut squad_account_info = get_squad(program_id, squad_account)?;
     let mut proposal_account_info = get_proposal(program_id, squad_account, proposal_account)?;
 
-    // check there isn't a member change lock on this proposal
+    // proposals created before membership snapshots can still be locked by a member change
     // if this proposal index is less than the member_lock_index, no voting allowed
-    if !proposal_account_info.execute_ready
+    if !proposal_account_info.is_approved()
         && proposal_account_info.proposal_index <= squad_account_info.member_lock_index
     {
         return Err(ProgramError::InvalidInstructionData);
@@ -93,93 +103,211 @@ pub fn process_execute_proposal(
     if squad_account_info.allocation_type != AllocationType::TeamCoordination as u8 {
         return Err(ProgramError::InvalidAccountData);
     }
-    // check that the person executing is a member of the squad
-    if !Squad::member_exists(&squad_account_info, executioner.key) {
+    // executor members can execute any approved proposal, any other signer is a keeper:
+    // it can execute a scheduled proposal, or any approved one once the squad opts in
+    let keeper =
+        !Squad::member_has_role(&squad_account_info, executioner.key, MEMBER_ROLE_EXECUTOR);
+    if keeper && proposal_account_info.execute_after == 0 && !squad_account_info.keeper_execution {
         return Err(ProgramError::InvalidArgument);
     }
+    // the tip as set before this execution, a KeeperPolicy proposal can change it
+    let keeper_tip = if keeper && squad_account_info.keeper_execution {
+        squad_account_info.keeper_tip
+    } else {
+        0
+    };
     // check if the proposal is actually executeable
     if proposal_account_info.proposal_type < 1 {
         return Err(ProgramError::InvalidArgument);
     }
-    // check if the proposal has already been executed
-    if proposal_account_info.executed == true {
-        return Err(ProgramError::InvalidArgument);
-    }
-
-    let mut vote_passed = true;
-    // there are only two viable options for executable proposals
-    // 0 pass, 1 reject
-    let pass_votes = *proposal_account_info.votes.get(0).unwrap();
-    let fail_votes = *proposal_account_info.votes.get(1).unwrap();
-    if pass_votes < fail_votes {
-        vote_passed = false;
-    }
-
-    if !vote_passed {
+    // check if the proposal has already been executed or closed
+    if proposal_account_info.is_closed() {
         return Err(ProgramError::InvalidArgument);
     }
 
     // get mint account supply
     let squad_mint_account_info = Mint::unpack_unchecked(&squad_mint_account.data.borrow())?;
 
-    // check quorum & support
-    let curr_quorum_percent;
-    let current_support_percent;
-    if proposal_account_info.execute_ready {
-        curr_quorum_percent = (proposal_account_info.has_voted.len() as f32
-            / proposal_account_info.members_at_execute as f32)
-            * 100.0;
-
-        current_support_percent =
-            (pass_votes as f32 / proposal_account_info.supply_at_execute as f32) * 100.0;
+    // check quorum & support against the values saved once the proposal was execute ready,
+    // vault policies can override the support needed to move funds, votes save the supply
+    // as of the proposal creation
+    let supply = if !proposal_account_info.has_voted.is_empty() {
+        proposal_account_info.supply_at_execute
     } else {
-        curr_quorum_percent = (proposal_account_info.has_voted.len() as f32
-            / squad_account_info.members.len() as f32)
-            * 100.0;
-
-        current_support_percent =
-            (pass_votes as f32 / squad_mint_account_info.supply as f32) * 100.0;
+        squad_account_info.governance_supply(squad_mint_account_info.supply)
+    };
+    let vote_params = SquadParams::at_execute(&squad_account_info, &proposal_account_info);
+    if evaluate(
+        &proposal_account_info,
+        &vote_params,
+        supply,
+        Clock::get().unwrap().unix_timestamp,
+    ) != Outcome::Passed
+    {
+        return Err(ProgramError::InvalidArgument);
     }
 
-    if curr_quorum_percent < squad_account_info.vote_quorum as f32 {
+    // withdrawals to unlisted destinations wait out the address book delay
+    if proposal_account_info.is_time_locked(Clock::get().unwrap().unix_timestamp) {
+        msg!("SQDS: Proposal is time-locked until its execute_after or address book delay");
         return Err(ProgramError::InvalidArgument);
     }
 
-    if current_support_percent < squad_account_info.vote_support as f32 {
-        return Err(ProgramError::InvalidArgument);
+    let now = Clock::get().unwrap().unix_timestamp;
+    record_outcome(&mut proposal_account_info, Outcome::Passed, now)?;
+    // a vault short of funds fails the execution without closing the proposal,
+    // it can be executed again once the vault is topped up
+    if vault_is_short(&proposal_account_info, source_account)? {
+        msg!("SQDS: Vault can't cover the withdrawal, execution failed");
+        proposal_account_info.set_status(ProposalStatus::ExecutionFailed)?;
+        Proposal::pack(
+            proposal_account_info,
+            &mut proposal_account.data.borrow_mut(),
+        )?;
+        return Ok(());
     }
 
     match FromPrimitive::from_u8(proposal_account_info.proposal_type) {
         Some(ProposalType::Support) => {
-            // change support
+            // change support, a whole percent clears the bps setting
             squad_account_info.vote_support = proposal_account_info.execution_amount as u8;
+            squad_account_info.vote_support_bps = 0;
+        }
+        Some(ProposalType::KeeperPolicy) => {
+            squad_account_info.keeper_execution = proposal_account_info.execution_amount_out == 1;
+            squad_account_info.keeper_tip = proposal_account_info.execution_amount;
         }
         Some(ProposalType::Quorum) => {
-            // change quorum
+            // change quorum, a whole percent clears the bps setting
             squad_account_info.vote_quorum = proposal_account_info.execution_amount as u8;
+            squad_account_info.vote_quorum_bps = 0;
         }
-        Some(ProposalType::WithdrawSol) => {
-            // withdraw SOL
-
-            let (sol_address, sol_bump_seed) =
-                get_sol_address_with_seed(&squad_account.key, program_id);
-
-            // check that the sol_address matches the squad sol address
-            if sol_address != squad_account_info.sol_account {
-                return Err(ProgramError::InvalidAccountData);
+        Some(ProposalType::SupportBps) => {
+            // change support in bps, the percent setting keeps the rounded down value
+            squad_account_info.vote_support_bps = proposal_account_info.execution_amount as u16;
+            squad_account_info.vote_support = (proposal_account_info.execution_amount / 100) as u8;
+        }
+        Some(ProposalType::QuorumBps) => {
+            // change quorum in bps, the percent setting keeps the rounded down value
+            squad_account_info.vote_quorum_bps = proposal_account_info.execution_amount as u16;
+            squad_account_info.vote_quorum = (proposal_account_info.execution_amount / 100) as u8;
+        }
+        Some(ProposalType::SetCoreMember) => {
+            // grant or revoke a core council seat, the council may have shrunk since creation
+            if proposal_account_info.execution_amount == 0
+                && !squad_account_info.core_seat_removable(destination_account.key)
+            {
+                msg!("SQDS: Not enough core members left to reach the core threshold");
+                return Err(ProgramError::InvalidArgument);
+            }
+            let member = squad_account_info
+                .members
+                .get_mut(destination_account.key)
+                .ok_or(ProgramError::InvalidArgument)?;
+            if proposal_account_info.execution_amount == 1 {
+                member.role |= MEMBER_ROLE_CORE;
+            } else {
+                member.role &= !MEMBER_ROLE_CORE;
             }
+        }
+        Some(ProposalType::OptimisticType) => {
+            // set the veto threshold of an optimistic proposal type in the squad config
+            let config_account = next_account_info(account_info_iter)?;
+            process_set_optimistic_veto(
+                executioner,
+                squad_account,
+                config_account,
+                system_program_account,
+                rent,
+                proposal_account_info.execution_amount_out as u8,
+                proposal_account_info.execution_amount as u8,
+                program_id,
+            )?;
+            squad_account_info.has_config = true;
+        }
+        Some(ProposalType::OptimisticWindow) => {
+            // set the challenge window of optimistic proposals in the squad config
+            let config_account = next_account_info(account_info_iter)?;
+            process_set_challenge_window(
+                executioner,
+                squad_account,
+                config_account,
+                system_program_account,
+                rent,
+                proposal_account_info.execution_amount as i64,
+                proposal_account_info.execution_amount_out,
+                program_id,
+            )?;
+            squad_account_info.has_config = true;
+        }
+        Some(ProposalType::AddressBookEntry) => {
+            // add (amount 1) or remove the destination from the address book
+            let address_book_account = next_account_info(account_info_iter)?;
+            process_set_address_book_entry(
+                executioner,
+                squad_account,
+                address_book_account,
+                system_program_account,
+                rent,
+                &proposal_account_info.execution_destination,
+                proposal_account_info.execution_amount == 1,
+                program_id,
+            )?;
+            squad_account_info.has_address_book = true;
+        }
+        Some(ProposalType::UnlistedPolicy) => {
+            // threshold and delay of withdrawals to unlisted destinations
+            let address_book_account = next_account_info(account_info_iter)?;
+            process_set_unlisted_policy(
+                executioner,
+                squad_account,
+                address_book_account,
+                system_program_account,
+                rent,
+                proposal_account_info.execution_amount as u8,
+                proposal_account_info.execution_amount_out as i64,
+                program_id,
+            )?;
+            squad_account_info.has_address_book = true;
+        }
+        Some(ProposalType::TypeThreshold) => {
+            // set the threshold of a proposal type in the squad config
+            let config_account = next_account_info(account_info_iter)?;
+            process_set_type_threshold(
+                executioner,
+                squad_account,
+                config_account,
+                system_program_account,
+                rent,
+                proposal_account_info.execution_amount_out as u8,
+                proposal_account_info.execution_amount as u8,
+                program_id,
+            )?;
+            squad_account_info.has_config = true;
+        }
+        Some(ProposalType::CoreWithdrawLimit) => {
+            squad_account_info.core_withdraw_limit = proposal_account_info.execution_amount;
+        }
+        Some(ProposalType::WithdrawSol) => {
+            // withdraw SOL from the proposal vault
+            let (sol_address, sol_bump_seed) = get_vault_address_with_seed(
+                squad_account.key,
+                proposal_account_info.vault_index,
+                program_id,
+            );
+            let sol_seeds = get_vault_signer_seeds(
+                squad_account.key,
+                proposal_account_info.vault_index,
+                sol_bump_seed,
+            );
+            let sol_seeds_refs: Vec<&[u8]> = sol_seeds.iter().map(|seed| seed.as_slice()).collect();
+            let sol_signer_seeds: &[&[_]] = &sol_seeds_refs;
 
-            // check that the sold_address matches the source account
+            // check that the vault address matches the source account
             if sol_address != proposal_account_info.execution_source {
                 return Err(ProgramError::InvalidAccountData);
             }
 
-            // get the pda seeds
-            let sol_signer_seeds: &[&[_]] = &[
-                &squad_account.key.to_bytes(),
-                b"!squadsol",
-                &[sol_bump_seed],
-            ];
             let transfer_ix = transfer(
                 &sol_address,
                 &destination_account.key,
@@ -202,17 +330,23 @@ pub fn process_execute_proposal(
             let destination_ata = next_account_info(account_info_iter)?;
             let token_mint = next_account_info(account_info_iter)?;
             let sol_account = next_account_info(account_info_iter)?;
-            // get the sol address and seed, as this is still the authority over any
-            // of the squads token accounts - they're derived from this
-            let (sol_address, sol_bump_seed) =
-                get_sol_address_with_seed(&squad_account.key, program_id);
+            // get the vault address and seed, as this is still the authority over any
+            // of the vault token accounts - they're derived from this
+            let (sol_address, sol_bump_seed) = get_vault_address_with_seed(
+                squad_account.key,
+                proposal_account_info.vault_index,
+                program_id,
+            );
+            let sol_seeds = get_vault_signer_seeds(
+                squad_account.key,
+                proposal_account_info.vault_index,
+                sol_bump_seed,
+            );
+            let sol_seeds_refs: Vec<&[u8]> = sol_seeds.iter().map(|seed| seed.as_slice()).collect();
+            let sol_signer_seeds: &[&[_]] = &sol_seeds_refs;
 
-            // check that the derived sol PDA (sol_address) matches the squad
-            if sol_address != squad_account_info.sol_account {
-                return Err(ProgramError::InvalidAccountData);
-            }
-            // check that the sol account is the one set to the squad account
-            if sol_account.key != &squad_account_info.sol_account {
+            // check that the sol account is the proposal vault
+            if sol_account.key != &sol_address {
                 return Err(ProgramError::InvalidInstructionData);
             }
             // check that the destination ata that was submitted matches the one that would be derived
@@ -224,12 +358,6 @@ pub fn process_execute_proposal(
                 return Err(ProgramError::InvalidAccountData);
             }
 
-            let sol_signer_seeds: &[&[_]] = &[
-                &squad_account.key.to_bytes(),
-                b"!squadsol",
-                &[sol_bump_seed],
-            ];
-
             if destination_ata.data_is_empty() {
                 invoke(
                     &create_associated_token_account(
@@ -278,6 +406,8 @@ pub fn process_execute_proposal(
             }
             let mint_owner = next_account_info(account_info_iter)?;
             let member_account = next_account_info(account_info_iter)?;
+            let member_checkpoints_account = next_account_info(account_info_iter)?;
+            let supply_checkpoints_account = next_account_info(account_info_iter)?;
 
             let (mint_owner_address, mint_bump_seed) =
                 get_mint_address_with_seed(&squad_account.key, &program_id);
@@ -397,23 +527,58 @@ pub fn process_execute_proposal(
                 &[&mint_signer_seeds],
             )?;
 
+            // the new member held nothing before, record the equity and supply change
+            process_record_checkpoint(
+                executioner,
+                squad_account,
+                member_checkpoints_account,
+                system_program_account,
+                rent,
+                destination_account.key,
+                0,
+                proposal_account_info.execution_amount,
+                program_id,
+            )?;
+            process_record_checkpoint(
+                executioner,
+                squad_account,
+                supply_checkpoints_account,
+                system_program_account,
+                rent,
+                squad_mint_account.key,
+                squad_mint_account_info.supply,
+                squad_mint_account_info.supply + proposal_account_info.execution_amount,
+                program_id,
+            )?;
+
+            // pending proposals keep the membership they were created with
+            squad_account_info.membership_version += 1;
+            let joined_version = squad_account_info.membership_version;
             Squad::add_member(
                 &mut squad_account_info,
                 *destination_account.key,
                 Member {
                     equity_token_account: *member_account.key,
+                    role: MEMBER_ROLE_ALL,
+                    joined_version,
                 },
-            );
+            )?;
         }
         Some(ProposalType::RemoveMember) => {
             // remove member
             if !Squad::member_exists(&squad_account_info, destination_account.key) {
                 return Err(ProgramError::InvalidArgument);
             }
+            if !squad_account_info.core_seat_removable(destination_account.key) {
+                msg!("SQDS: Not enough core members left to reach the core threshold");
+                return Err(ProgramError::InvalidArgument);
+            }
 
             let mint_owner = next_account_info(account_info_iter)?;
             let member_account = next_account_info(account_info_iter)?;
             let sol_account = next_account_info(account_info_iter)?;
+            let member_checkpoints_account = next_account_info(account_info_iter)?;
+            let supply_checkpoints_account = next_account_info(account_info_iter)?;
 
             if sol_account.key != &squad_account_info.sol_account {
                 return Err(ProgramError::InvalidInstructionData);
@@ -477,10 +642,32 @@ pub fn process_execute_proposal(
                 &[&mint_signer_seeds],
             )?;
 
-            Squad::remove_member(&mut squad_account_info, &destination_account.key);
+            // the burned equity leaves the supply
+            process_record_checkpoint(
+                executioner,
+                squad_account,
+                member_checkpoints_account,
+                system_program_account,
+                rent,
+                destination_account.key,
+                member_account_info.amount,
+                0,
+                program_id,
+            )?;
+            process_record_checkpoint(
+                executioner,
+                squad_account,
+                supply_checkpoints_account,
+                system_program_account,
+                rent,
+                squad_mint_account.key,
+                squad_mint_account_info.supply,
+                squad_mint_account_info.supply - member_account_info.amount,
+                program_id,
+            )?;
 
-            // change in membership, lock the proposals
-            squad_account_info.member_lock_index = squad_account_info.proposal_nonce;
+            Squad::remove_member(&mut squad_account_info, &destination_account.key);
+            squad_account_info.membership_version += 1;
         }
         Some(ProposalType::MintMemberToken) => {
             // change member mint allocation
@@ -492,6 +679,8 @@ pub fn process_execute_proposal(
 
             let mint_owner = next_account_info(account_info_iter)?;
             let member_account = next_account_info(account_info_iter)?;
+            let member_checkpoints_account = next_account_info(account_info_iter)?;
+            let supply_checkpoints_account = next_account_info(account_info_iter)?;
 
             if *member_account.key != member_equity {
                 return Err(ProgramError::InvalidAccountData);
@@ -531,8 +720,6 @@ pub fn process_execute_proposal(
                     ],
                     &[&mint_signer_seeds],
                 )?;
-                // change in ownership values, set the member lock
-                squad_account_info.member_lock_index = squad_account_info.proposal_nonce;
             } else if member_account_info.amount > proposal_account_info.execution_amount {
                 invoke_signed(
                     &spl_token::instruction::burn(
@@ -550,9 +737,234 @@ pub fn process_execute_proposal(
                     ],
                     &[&mint_signer_seeds],
                 )?;
-                // change in ownership, set the member lock
-                squad_account_info.member_lock_index = squad_account_info.proposal_nonce;
             }
+
+            // the proposals in flight keep the weights from their creation
+            if member_account_info.amount != proposal_account_info.execution_amount {
+                process_record_checkpoint(
+                    executioner,
+                    squad_account,
+                    member_checkpoints_account,
+                    system_program_account,
+                    rent,
+                    destination_account.key,
+                    member_account_info.amount,
+                    proposal_account_info.execution_amount,
+                    program_id,
+                )?;
+                process_record_checkpoint(
+                    executioner,
+                    squad_account,
+                    supply_checkpoints_account,
+                    system_program_account,
+                    rent,
+                    squad_mint_account.key,
+                    squad_mint_account_info.supply,
+                    squad_mint_account_info.supply + proposal_account_info.execution_amount
+                        - member_account_info.amount,
+                    program_id,
+                )?;
+            }
+        }
+        Some(ProposalType::VestMemberToken) => {
+            // create the vesting grant, tokens get minted as they are claimed
+            if !Squad::member_exists(&squad_account_info, destination_account.key) {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let vesting_account = next_account_info(account_info_iter)?;
+
+            let (vesting_address, vesting_bump_seed) =
+                get_vesting_address_with_seed(proposal_account.key, program_id);
+            if vesting_address != *vesting_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let vesting_signer_seeds: &[&[_]] = &[
+                &proposal_account.key.to_bytes(),
+                b"!vesting",
+                &[vesting_bump_seed],
+            ];
+
+            // DoS check
+            let rent_exempt_lamports = rent.minimum_balance(Vesting::get_packed_len()).max(1);
+            if vesting_account.lamports() > 0 {
+                let top_up_lamports =
+                    rent_exempt_lamports.saturating_sub(vesting_account.lamports());
+
+                if top_up_lamports > 0 {
+                    invoke(
+                        &transfer(executioner.key, vesting_account.key, top_up_lamports),
+                        &[
+                            executioner.clone(),
+                            vesting_account.clone(),
+                            system_program_account.clone(),
+                        ],
+                    )?;
+                }
+
+                invoke_signed(
+                    &allocate(vesting_account.key, Vesting::get_packed_len() as u64),
+                    &[vesting_account.clone(), system_program_account.clone()],
+                    &[vesting_signer_seeds],
+                )?;
+
+                invoke_signed(
+                    &assign(vesting_account.key, program_id),
+                    &[vesting_account.clone(), system_program_account.clone()],
+                    &[vesting_signer_seeds],
+                )?;
+            } else {
+                invoke_signed(
+                    &create_account(
+                        executioner.key,
+                        &vesting_address,
+                        rent_exempt_lamports,
+                        Vesting::get_packed_len() as u64,
+                        program_id,
+                    ),
+                    &[
+                        executioner.clone(),
+                        vesting_account.clone(),
+                        system_program_account.clone(),
+                    ],
+                    &[vesting_signer_seeds],
+                )?;
+            }
+
+            let mut vesting_account_info = get_vesting(program_id, squad_account, vesting_account)?;
+            if vesting_account_info.is_initialized {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+
+            Vesting::save_grant(
+                &mut vesting_account_info,
+                squad_account.key,
+                destination_account.key,
+                proposal_account.key,
+                Clock::get().unwrap().unix_timestamp,
+                proposal_account_info.vesting_cliff,
+                i64::try_from(proposal_account_info.execution_amount_out)
+                    .map_err(|_| ProgramError::InvalidArgument)?,
+                proposal_account_info.execution_amount,
+            )?;
+            Vesting::pack(vesting_account_info, &mut vesting_account.data.borrow_mut())?;
+        }
+        Some(ProposalType::RevokeVesting) => {
+            // the source account is the grant itself
+            let mut vesting_account_info = get_vesting(program_id, squad_account, source_account)?;
+            if !vesting_account_info.is_initialized || vesting_account_info.revoked_on != 0 {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if vesting_account_info.member != *destination_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            // whatever vested up to now can still be claimed
+            Vesting::revoke(
+                &mut vesting_account_info,
+                Clock::get().unwrap().unix_timestamp,
+            );
+            Vesting::pack(vesting_account_info, &mut source_account.data.borrow_mut())?;
+        }
+        Some(ProposalType::VaultPolicy) => {
+            // change the vault threshold
+            squad_account_info.vault_thresholds[proposal_account_info.vault_index as usize] =
+                proposal_account_info.execution_amount as u8;
+        }
+        Some(ProposalType::CreateStake)
+        | Some(ProposalType::DelegateStake)
+        | Some(ProposalType::DeactivateStake)
+        | Some(ProposalType::WithdrawStake)
+        | Some(ProposalType::SplitStake)
+        | Some(ProposalType::MergeStake) => {
+            // native stake of the proposal vault
+            process_execute_stake(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::MintTo)
+        | Some(ProposalType::BurnTokens)
+        | Some(ProposalType::SetTokenAuthority)
+        | Some(ProposalType::FreezeAccount)
+        | Some(ProposalType::ThawAccount) => {
+            // an external mint the proposal vault has authority over
+            process_execute_token_authority(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::CloseTokenAccount)
+        | Some(ProposalType::ApproveDelegate)
+        | Some(ProposalType::RevokeDelegate)
+        | Some(ProposalType::SyncNative)
+        | Some(ProposalType::CreateTokenAccount) => {
+            // housekeeping of a vault token account
+            process_execute_token_account(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::CreateMint) => {
+            // a new mint the proposal vault has authority over
+            process_execute_create_mint(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::CreateChildSquad) => {
+            // a child squad the parent vault takes a seat in
+            process_execute_create_child_squad(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+            // CreateChildSquad moved the child_index of the squad account
+            squad_account_info.child_index = get_squad(program_id, squad_account)?.child_index;
+        }
+        Some(ProposalType::ChildVote) => {
+            // the parent vault votes in a child squad
+            process_execute_child_vote(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::UpgradeProgram) | Some(ProposalType::SetUpgradeAuthority) => {
+            // a program the proposal vault is the upgrade authority of
+            process_execute_upgrade(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::CreateStream) => {
+            // escrow the stream funds out of the vault
+            process_execute_create_stream(
+                accounts,
+                &proposal_account_info,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
+        }
+        Some(ProposalType::CancelStream) => {
+            // stop the stream, the unaccrued balance goes back to the vault
+            process_execute_cancel_stream(
+                accounts,
+                squad_account_info.allocation_type,
+                program_id,
+            )?;
         }
         Some(ProposalType::Swap) => {
             // swap tokens
@@ -562,8 +974,11 @@ pub fn process_execute_proposal(
             let wsol_account = next_account_info(account_info_iter)?;
             let wsol_mint = next_account_info(account_info_iter)?;
 
-            let (sol_address, _sol_bump_seed) =
-                get_sol_address_with_seed(&squad_account.key, program_id);
+            let (sol_address, _sol_bump_seed) = get_vault_address_with_seed(
+                squad_account.key,
+                proposal_account_info.vault_index,
+                program_id,
+            );
 
             if sol_account.key != &sol_address {
                 return Err(ProgramError::InvalidAccountData);
@@ -624,6 +1039,7 @@ pub fn process_execute_proposal(
                 accounts,
                 proposal_account_info.execution_amount,
                 proposal_account_info.execution_amount_out,
+                proposal_account_info.vault_index,
                 squad_account_info.allocation_type,
                 random_id,
                 program_id,
@@ -635,9 +1051,24 @@ pub fn process_execute_proposal(
         }
     };
 
+    // the proposal vault is the last account when a keeper gets a tip, some types
+    // read their accounts again from the start
+    if keeper_tip > 0 {
+        let vault_account = accounts.last().ok_or(ProgramError::NotEnoughAccountKeys)?;
+        pay_keeper_tip(
+            squad_account.key,
+            &proposal_account_info,
+            vault_account,
+            executioner,
+            system_program_account,
+            keeper_tip,
+            program_id,
+        )?;
+    }
+
     proposal_account_info.executed_by = *executioner.key;
-    proposal_account_info.executed = true;
-    proposal_account_info.execution_date = Clock::get().unwrap().unix_timestamp;
+    proposal_account_info.set_status(ProposalStatus::Executed)?;
+    proposal_account_info.execution_date = now;
     Proposal::pack(
         proposal_account_info,
         &mut proposal_account.data.borrow_mut(),
@@ -0,0 +1,239 @@
+use num_traits::FromPrimitive;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        self,
+        state::{Authorized, Lockup, StakeState},
+    },
+    system_instruction::{allocate, assign, transfer},
+};
+
+use crate::state::{
+    proposal::{Proposal, ProposalType},
+    squad::AllocationType,
+};
+use crate::*;
+
+// manages native stake accounts of the proposal vault, the vault is the staker and
+// withdrawer of every stake account and signs the stake instructions with its seeds
+pub fn process_execute_stake(
+    accounts: &[AccountInfo],
+    proposal_account_info: &Proposal,
+    allocation_type: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let _executioner = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    if allocation_type == AllocationType::TeamCoordination as u8 {
+        next_account_info(account_info_iter)?; // squad_mint_account
+    }
+    let proposal_account = next_account_info(account_info_iter)?;
+    let source_account = next_account_info(account_info_iter)?;
+    let destination_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let _token_program_account = next_account_info(account_info_iter)?;
+    let _associated_program_account = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let sol_account = next_account_info(account_info_iter)?;
+    let stake_program_account = next_account_info(account_info_iter)?;
+    let clock_account = next_account_info(account_info_iter)?;
+    let stake_history_account = next_account_info(account_info_iter)?;
+    let stake_config_account = next_account_info(account_info_iter)?;
+
+    if *stake_program_account.key != stake::program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (sol_address, sol_bump_seed) = get_vault_address_with_seed(
+        squad_account.key,
+        proposal_account_info.vault_index,
+        program_id,
+    );
+    if *sol_account.key != sol_address {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let sol_seeds = get_vault_signer_seeds(
+        squad_account.key,
+        proposal_account_info.vault_index,
+        sol_bump_seed,
+    );
+    let sol_seeds_refs: Vec<&[u8]> = sol_seeds.iter().map(|seed| seed.as_slice()).collect();
+    let sol_signer_seeds: &[&[_]] = &sol_seeds_refs;
+
+    let (stake_address, stake_bump_seed) =
+        get_stake_address_with_seed(proposal_account.key, program_id);
+    let stake_signer_seeds: &[&[_]] = &[
+        &proposal_account.key.to_bytes(),
+        b"!stake",
+        &[stake_bump_seed],
+    ];
+
+    match FromPrimitive::from_u8(proposal_account_info.proposal_type) {
+        Some(ProposalType::CreateStake) => {
+            // fund the stake account out of the vault, then make it a stake account
+            // owned by the stake program, lamports sent to it beforehand are kept
+            if *destination_account.key != stake_address {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            invoke_signed(
+                &transfer(
+                    &sol_address,
+                    &stake_address,
+                    proposal_account_info.execution_amount,
+                ),
+                &[
+                    sol_account.clone(),
+                    destination_account.clone(),
+                    system_program_account.clone(),
+                ],
+                &[sol_signer_seeds],
+            )?;
+            allocate_stake_account(
+                destination_account,
+                system_program_account,
+                stake_signer_seeds,
+            )?;
+            invoke(
+                &stake::instruction::initialize(
+                    &stake_address,
+                    &Authorized::auto(&sol_address),
+                    &Lockup::default(),
+                ),
+                &[
+                    destination_account.clone(),
+                    rent_account.clone(),
+                    stake_program_account.clone(),
+                ],
+            )?;
+        }
+        Some(ProposalType::DelegateStake) => {
+            invoke_signed(
+                &stake::instruction::delegate_stake(
+                    source_account.key,
+                    &sol_address,
+                    destination_account.key,
+                ),
+                &[
+                    source_account.clone(),
+                    destination_account.clone(),
+                    clock_account.clone(),
+                    stake_history_account.clone(),
+                    stake_config_account.clone(),
+                    sol_account.clone(),
+                    stake_program_account.clone(),
+                ],
+                &[sol_signer_seeds],
+            )?;
+        }
+        Some(ProposalType::DeactivateStake) => {
+            invoke_signed(
+                &stake::instruction::deactivate_stake(source_account.key, &sol_address),
+                &[
+                    source_account.clone(),
+                    clock_account.clone(),
+                    sol_account.clone(),
+                    stake_program_account.clone(),
+                ],
+                &[sol_signer_seeds],
+            )?;
+        }
+        Some(ProposalType::WithdrawStake) => {
+            // inactive lamports go back to the vault
+            if *destination_account.key != sol_address {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            invoke_signed(
+                &stake::instruction::withdraw(
+                    source_account.key,
+                    &sol_address,
+                    &sol_address,
+                    proposal_account_info.execution_amount,
+                    None,
+                ),
+                &[
+                    source_account.clone(),
+                    destination_account.clone(),
+                    clock_account.clone(),
+                    stake_history_account.clone(),
+                    sol_account.clone(),
+                    stake_program_account.clone(),
+                ],
+                &[sol_signer_seeds],
+            )?;
+        }
+        Some(ProposalType::SplitStake) => {
+            // the split stake account is derived from the proposal like a created one
+            if *destination_account.key != stake_address {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            allocate_stake_account(
+                destination_account,
+                system_program_account,
+                stake_signer_seeds,
+            )?;
+            // the last instruction is the split itself, the others allocate and assign
+            let split_ix = stake::instruction::split(
+                source_account.key,
+                &sol_address,
+                proposal_account_info.execution_amount,
+                &stake_address,
+            )
+            .pop()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+            invoke_signed(
+                &split_ix,
+                &[
+                    source_account.clone(),
+                    destination_account.clone(),
+                    sol_account.clone(),
+                    stake_program_account.clone(),
+                ],
+                &[sol_signer_seeds],
+            )?;
+        }
+        Some(ProposalType::MergeStake) => {
+            // the source stake account is drained into the destination one
+            for merge_ix in
+                stake::instruction::merge(destination_account.key, source_account.key, &sol_address)
+            {
+                invoke_signed(
+                    &merge_ix,
+                    &[
+                        destination_account.clone(),
+                        source_account.clone(),
+                        clock_account.clone(),
+                        stake_history_account.clone(),
+                        sol_account.clone(),
+                        stake_program_account.clone(),
+                    ],
+                    &[sol_signer_seeds],
+                )?;
+            }
+        }
+        _ => return Err(ProgramError::InvalidArgument),
+    }
+    Ok(())
+}
+
+// sizes the stake PDA for the stake program and hands it over, it signs with its seeds
+fn allocate_stake_account<'a>(
+    stake_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    stake_signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    invoke_signed(
+        &allocate(stake_account.key, std::mem::size_of::<StakeState>() as u64),
+        &[stake_account.clone(), system_program_account.clone()],
+        &[stake_signer_seeds],
+    )?;
+    invoke_signed(
+        &assign(stake_account.key, &stake::program::id()),
+        &[stake_account.clone(), system_program_account.clone()],
+        &[stake_signer_seeds],
+    )
+}
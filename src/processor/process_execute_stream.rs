@@ -0,0 +1,293 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::{allocate, assign, create_account, transfer},
+    sysvar::Sysvar,
+};
+
+use spl_associated_token_account::create_associated_token_account;
+use spl_token::state::Account;
+
+use crate::state::{proposal::Proposal, squad::AllocationType, stream::Stream};
+use crate::*;
+
+// escrows the stream funds out of the vault into the stream account
+// SOL streams keep the lamports on the stream account itself, SPL streams
+// use the associated token account of the stream account
+pub fn process_execute_create_stream(
+    accounts: &[AccountInfo],
+    proposal_account_info: &Proposal,
+    allocation_type: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let executioner = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    if allocation_type == AllocationType::TeamCoordination as u8 {
+        next_account_info(account_info_iter)?; // squad_mint_account
+    }
+    let proposal_account = next_account_info(account_info_iter)?;
+    let source_account = next_account_info(account_info_iter)?;
+    let destination_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let associated_program_account = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let stream_account = next_account_info(account_info_iter)?;
+    let sol_account = next_account_info(account_info_iter)?;
+
+    let rent = &Rent::from_account_info(rent_account)?;
+
+    // the stream is funded out of the proposal vault
+    let (sol_address, sol_bump_seed) = get_vault_address_with_seed(
+        squad_account.key,
+        proposal_account_info.vault_index,
+        program_id,
+    );
+    if sol_account.key != &sol_address {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let sol_seeds = get_vault_signer_seeds(
+        squad_account.key,
+        proposal_account_info.vault_index,
+        sol_bump_seed,
+    );
+    let sol_seeds_refs: Vec<&[u8]> = sol_seeds.iter().map(|seed| seed.as_slice()).collect();
+    let sol_signer_seeds: &[&[_]] = &sol_seeds_refs;
+
+    let (stream_address, stream_bump_seed) =
+        get_stream_address_with_seed(proposal_account.key, program_id);
+    if stream_address != *stream_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let stream_signer_seeds: &[&[_]] = &[
+        &proposal_account.key.to_bytes(),
+        b"!stream",
+        &[stream_bump_seed],
+    ];
+
+    // DoS check
+    let rent_exempt_lamports = rent.minimum_balance(Stream::get_packed_len()).max(1);
+    if stream_account.lamports() > 0 {
+        let top_up_lamports = rent_exempt_lamports.saturating_sub(stream_account.lamports());
+
+        if top_up_lamports > 0 {
+            invoke(
+                &transfer(executioner.key, stream_account.key, top_up_lamports),
+                &[
+                    executioner.clone(),
+                    stream_account.clone(),
+                    system_program_account.clone(),
+                ],
+            )?;
+        }
+
+        invoke_signed(
+            &allocate(stream_account.key, Stream::get_packed_len() as u64),
+            &[stream_account.clone(), system_program_account.clone()],
+            &[stream_signer_seeds],
+        )?;
+
+        invoke_signed(
+            &assign(stream_account.key, program_id),
+            &[stream_account.clone(), system_program_account.clone()],
+            &[stream_signer_seeds],
+        )?;
+    } else {
+        invoke_signed(
+            &create_account(
+                executioner.key,
+                &stream_address,
+                rent_exempt_lamports,
+                Stream::get_packed_len() as u64,
+                program_id,
+            ),
+            &[
+                executioner.clone(),
+                stream_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[stream_signer_seeds],
+        )?;
+    }
+
+    let mut stream_account_info = get_stream(program_id, squad_account, stream_account)?;
+    if stream_account_info.is_initialized {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let mint;
+    let escrow;
+    if *source_account.key == sol_address {
+        // SOL stream, the lamports sit on the stream account
+        invoke_signed(
+            &transfer(
+                &sol_address,
+                stream_account.key,
+                proposal_account_info.execution_amount,
+            ),
+            &[
+                source_account.clone(),
+                stream_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[sol_signer_seeds],
+        )?;
+        mint = Pubkey::default();
+        escrow = stream_address;
+    } else {
+        // SPL stream, the source is one of the vault token accounts
+        let token_mint = next_account_info(account_info_iter)?;
+        let stream_token_account = next_account_info(account_info_iter)?;
+
+        let source_account_info = Account::unpack(&source_account.data.borrow())?;
+        if source_account_info.owner != sol_address || source_account_info.mint != *token_mint.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let ata_address = spl_associated_token_account::get_associated_token_address(
+            &stream_address,
+            token_mint.key,
+        );
+        if ata_address != *stream_token_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if stream_token_account.data_is_empty() {
+            invoke(
+                &create_associated_token_account(executioner.key, &stream_address, token_mint.key),
+                &[
+                    executioner.clone(),
+                    stream_token_account.clone(),
+                    stream_account.clone(),
+                    token_mint.clone(),
+                    system_program_account.clone(),
+                    token_program_account.clone(),
+                    rent_account.clone(),
+                    associated_program_account.clone(),
+                ],
+            )?;
+        }
+
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program_account.key,
+                source_account.key,
+                stream_token_account.key,
+                &sol_address,
+                &[],
+                proposal_account_info.execution_amount,
+            )?,
+            &[
+                source_account.clone(),
+                stream_token_account.clone(),
+                sol_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[sol_signer_seeds],
+        )?;
+        mint = *token_mint.key;
+        escrow = ata_address;
+    }
+
+    Stream::save_stream(
+        &mut stream_account_info,
+        squad_account.key,
+        proposal_account.key,
+        destination_account.key,
+        source_account.key,
+        &mint,
+        &escrow,
+        Clock::get().unwrap().unix_timestamp,
+        proposal_account_info.execution_amount_out,
+        proposal_account_info.execution_amount,
+    );
+    Stream::pack(stream_account_info, &mut stream_account.data.borrow_mut())?;
+    Ok(())
+}
+
+// stops a stream and sends the unaccrued balance back to where it came from,
+// whatever accrued up to now stays withdrawable by the recipient
+pub fn process_execute_cancel_stream(
+    accounts: &[AccountInfo],
+    allocation_type: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    next_account_info(account_info_iter)?; // executioner
+    let squad_account = next_account_info(account_info_iter)?;
+    if allocation_type == AllocationType::TeamCoordination as u8 {
+        next_account_info(account_info_iter)?; // squad_mint_account
+    }
+    next_account_info(account_info_iter)?; // proposal_account
+    let stream_account = next_account_info(account_info_iter)?; // source_account
+    let destination_account = next_account_info(account_info_iter)?;
+    next_account_info(account_info_iter)?; // system_program_account
+    let token_program_account = next_account_info(account_info_iter)?;
+    next_account_info(account_info_iter)?; // associated_program_account
+    next_account_info(account_info_iter)?; // rent_account
+    let return_account = next_account_info(account_info_iter)?;
+
+    let mut stream_account_info = get_stream(program_id, squad_account, stream_account)?;
+    if !stream_account_info.is_initialized || stream_account_info.canceled_on != 0 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if stream_account_info.recipient != *destination_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // the balance goes back to the vault (or vault token account) it came from
+    if stream_account_info.source != *return_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let unaccrued = Stream::cancel(
+        &mut stream_account_info,
+        Clock::get().unwrap().unix_timestamp,
+    );
+
+    if unaccrued > 0 {
+        if stream_account_info.is_sol() {
+            **stream_account.try_borrow_mut_lamports()? -= unaccrued;
+            **return_account.try_borrow_mut_lamports()? += unaccrued;
+        } else {
+            let stream_token_account = next_account_info(account_info_iter)?;
+            if stream_account_info.escrow != *stream_token_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let (_stream_address, stream_bump_seed) =
+                get_stream_address_with_seed(&stream_account_info.proposal_address, program_id);
+            let stream_signer_seeds: &[&[_]] = &[
+                &stream_account_info.proposal_address.to_bytes(),
+                b"!stream",
+                &[stream_bump_seed],
+            ];
+
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    token_program_account.key,
+                    stream_token_account.key,
+                    return_account.key,
+                    stream_account.key,
+                    &[],
+                    unaccrued,
+                )?,
+                &[
+                    stream_token_account.clone(),
+                    return_account.clone(),
+                    stream_account.clone(),
+                    token_program_account.clone(),
+                ],
+                &[stream_signer_seeds],
+            )?;
+        }
+    }
+
+    Stream::pack(stream_account_info, &mut stream_account.data.borrow_mut())?;
+    Ok(())
+}
@@ -83,6 +83,7 @@ pub fn process_execute_swap(
     accounts: &[AccountInfo],
     amount: u64,
     amount_out: u64,
+    vault_index: u8,
     allocation_type: u8,
     random_id: String,
     program_id: &Pubkey,
@@ -124,12 +125,11 @@ pub fn process_execute_swap(
 
     let rent = &Rent::from_account_info(rent_account)?;
 
-    let (sol_address, sol_bump_seed) = get_sol_address_with_seed(&squad_account.key, program_id);
-    let sol_signer_seeds: &[&[_]] = &[
-        &squad_account.key.to_bytes(),
-        b"!squadsol",
-        &[sol_bump_seed],
-    ];
+    let (sol_address, sol_bump_seed) =
+        get_vault_address_with_seed(squad_account.key, vault_index, program_id);
+    let sol_seeds = get_vault_signer_seeds(squad_account.key, vault_index, sol_bump_seed);
+    let sol_seeds_refs: Vec<&[u8]> = sol_seeds.iter().map(|seed| seed.as_slice()).collect();
+    let sol_signer_seeds: &[&[_]] = &sol_seeds_refs;
 
     if sol_account.key != &sol_address {
         return Err(ProgramError::InvalidAccountData);
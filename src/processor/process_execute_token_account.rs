@@ -0,0 +1,148 @@
+use num_traits::FromPrimitive;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::create_associated_token_account;
+use spl_token::instruction::{approve, close_account, revoke, sync_native};
+
+use crate::state::{
+    proposal::{Proposal, ProposalType},
+    squad::AllocationType,
+};
+use crate::*;
+
+// housekeeping of a token account of the proposal vault, the vault signs the token
+// instructions with its seeds like for a WithdrawSpl
+pub fn process_execute_token_account(
+    accounts: &[AccountInfo],
+    proposal_account_info: &Proposal,
+    allocation_type: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let executioner = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    if allocation_type == AllocationType::TeamCoordination as u8 {
+        next_account_info(account_info_iter)?; // squad_mint_account
+    }
+    let _proposal_account = next_account_info(account_info_iter)?;
+    let source_account = next_account_info(account_info_iter)?;
+    let destination_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let associated_program_account = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let sol_account = next_account_info(account_info_iter)?;
+
+    if *token_program_account.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if *source_account.key != proposal_account_info.execution_source
+        || *destination_account.key != proposal_account_info.execution_destination
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (sol_address, sol_bump_seed) = get_vault_address_with_seed(
+        squad_account.key,
+        proposal_account_info.vault_index,
+        program_id,
+    );
+    if *sol_account.key != sol_address {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let sol_seeds = get_vault_signer_seeds(
+        squad_account.key,
+        proposal_account_info.vault_index,
+        sol_bump_seed,
+    );
+    let sol_seeds_refs: Vec<&[u8]> = sol_seeds.iter().map(|seed| seed.as_slice()).collect();
+    let sol_signer_seeds: &[&[_]] = &sol_seeds_refs;
+
+    match FromPrimitive::from_u8(proposal_account_info.proposal_type) {
+        Some(ProposalType::CloseTokenAccount) => {
+            // tokens received since the proposal was created keep the account open
+            let token_account = spl_token::state::Account::unpack(&source_account.data.borrow())?;
+            if token_account.amount > 0 {
+                msg!("SQDS: Token account balance is not zero");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            invoke_signed(
+                &close_account(
+                    &spl_token::id(),
+                    source_account.key,
+                    &sol_address,
+                    &sol_address,
+                    &[],
+                )?,
+                &[
+                    source_account.clone(),
+                    sol_account.clone(),
+                    token_program_account.clone(),
+                ],
+                &[sol_signer_seeds],
+            )?;
+        }
+        Some(ProposalType::ApproveDelegate) => {
+            invoke_signed(
+                &approve(
+                    &spl_token::id(),
+                    source_account.key,
+                    destination_account.key,
+                    &sol_address,
+                    &[],
+                    proposal_account_info.execution_amount,
+                )?,
+                &[
+                    source_account.clone(),
+                    destination_account.clone(),
+                    sol_account.clone(),
+                    token_program_account.clone(),
+                ],
+                &[sol_signer_seeds],
+            )?;
+        }
+        Some(ProposalType::RevokeDelegate) => {
+            invoke_signed(
+                &revoke(&spl_token::id(), source_account.key, &sol_address, &[])?,
+                &[
+                    source_account.clone(),
+                    sol_account.clone(),
+                    token_program_account.clone(),
+                ],
+                &[sol_signer_seeds],
+            )?;
+        }
+        Some(ProposalType::SyncNative) => {
+            // lamports sent to the wSOL account become tokens, no signature needed
+            invoke(
+                &sync_native(&spl_token::id(), source_account.key)?,
+                &[source_account.clone(), token_program_account.clone()],
+            )?;
+        }
+        Some(ProposalType::CreateTokenAccount) => {
+            // the executioner pays the rent of the vault associated token account
+            invoke(
+                &create_associated_token_account(executioner.key, &sol_address, source_account.key),
+                &[
+                    executioner.clone(),
+                    destination_account.clone(),
+                    sol_account.clone(),
+                    source_account.clone(),
+                    system_program_account.clone(),
+                    token_program_account.clone(),
+                    rent_account.clone(),
+                    associated_program_account.clone(),
+                ],
+            )?;
+        }
+        _ => return Err(ProgramError::InvalidArgument),
+    }
+    Ok(())
+}
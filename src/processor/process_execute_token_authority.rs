@@ -0,0 +1,133 @@
+use num_traits::FromPrimitive;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use spl_token::instruction::{
+    burn, freeze_account, mint_to, set_authority, thaw_account, AuthorityType,
+};
+
+use crate::state::{
+    proposal::{Proposal, ProposalType},
+    squad::AllocationType,
+};
+use crate::*;
+
+// acts on an external mint with the authority the proposal vault holds over it,
+// the vault signs the token instructions with its seeds
+pub fn process_execute_token_authority(
+    accounts: &[AccountInfo],
+    proposal_account_info: &Proposal,
+    allocation_type: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let _executioner = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    if allocation_type == AllocationType::TeamCoordination as u8 {
+        next_account_info(account_info_iter)?; // squad_mint_account
+    }
+    let _proposal_account = next_account_info(account_info_iter)?;
+    let source_account = next_account_info(account_info_iter)?;
+    let destination_account = next_account_info(account_info_iter)?;
+    let _system_program_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let _associated_program_account = next_account_info(account_info_iter)?;
+    let _rent_account = next_account_info(account_info_iter)?;
+    let sol_account = next_account_info(account_info_iter)?;
+
+    if *token_program_account.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if *source_account.key != proposal_account_info.execution_source
+        || *destination_account.key != proposal_account_info.execution_destination
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (sol_address, sol_bump_seed) = get_vault_address_with_seed(
+        squad_account.key,
+        proposal_account_info.vault_index,
+        program_id,
+    );
+    if *sol_account.key != sol_address {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let sol_seeds = get_vault_signer_seeds(
+        squad_account.key,
+        proposal_account_info.vault_index,
+        sol_bump_seed,
+    );
+    let sol_seeds_refs: Vec<&[u8]> = sol_seeds.iter().map(|seed| seed.as_slice()).collect();
+    let sol_signer_seeds: &[&[_]] = &sol_seeds_refs;
+
+    let amount = proposal_account_info.execution_amount;
+    let instruction = match FromPrimitive::from_u8(proposal_account_info.proposal_type) {
+        Some(ProposalType::MintTo) => mint_to(
+            &spl_token::id(),
+            source_account.key,
+            destination_account.key,
+            &sol_address,
+            &[],
+            amount,
+        )?,
+        Some(ProposalType::BurnTokens) => burn(
+            &spl_token::id(),
+            source_account.key,
+            destination_account.key,
+            &sol_address,
+            &[],
+            amount,
+        )?,
+        Some(ProposalType::SetTokenAuthority) => {
+            let authority_type = match proposal_account_info.execution_amount_out {
+                0 => AuthorityType::MintTokens,
+                1 => AuthorityType::FreezeAccount,
+                _ => return Err(ProgramError::InvalidArgument),
+            };
+            // the default key removes the authority for good
+            let new_authority = if *destination_account.key == Pubkey::default() {
+                None
+            } else {
+                Some(destination_account.key)
+            };
+            set_authority(
+                &spl_token::id(),
+                source_account.key,
+                new_authority,
+                authority_type,
+                &sol_address,
+                &[],
+            )?
+        }
+        Some(ProposalType::FreezeAccount) => freeze_account(
+            &spl_token::id(),
+            source_account.key,
+            destination_account.key,
+            &sol_address,
+            &[],
+        )?,
+        Some(ProposalType::ThawAccount) => thaw_account(
+            &spl_token::id(),
+            source_account.key,
+            destination_account.key,
+            &sol_address,
+            &[],
+        )?,
+        _ => return Err(ProgramError::InvalidArgument),
+    };
+
+    invoke_signed(
+        &instruction,
+        &[
+            source_account.clone(),
+            destination_account.clone(),
+            sol_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[sol_signer_seeds],
+    )
+}
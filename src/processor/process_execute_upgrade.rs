@@ -0,0 +1,122 @@
+use num_traits::FromPrimitive;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable,
+    entrypoint::ProgramResult,
+    hash::hash,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::state::{
+    proposal::{Proposal, ProposalType},
+    squad::AllocationType,
+};
+use crate::*;
+
+// upgrades a program or hands over its upgrade authority, the proposal vault is the
+// upgrade authority and signs the loader instructions with its seeds
+pub fn process_execute_upgrade(
+    accounts: &[AccountInfo],
+    proposal_account_info: &Proposal,
+    allocation_type: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let _executioner = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    if allocation_type == AllocationType::TeamCoordination as u8 {
+        next_account_info(account_info_iter)?; // squad_mint_account
+    }
+    let _proposal_account = next_account_info(account_info_iter)?;
+    let program_account = next_account_info(account_info_iter)?;
+    let destination_account = next_account_info(account_info_iter)?;
+    let _system_program_account = next_account_info(account_info_iter)?;
+    let _token_program_account = next_account_info(account_info_iter)?;
+    let _associated_program_account = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let sol_account = next_account_info(account_info_iter)?;
+    let loader_program_account = next_account_info(account_info_iter)?;
+    let clock_account = next_account_info(account_info_iter)?;
+    let program_data_account = next_account_info(account_info_iter)?;
+
+    if *loader_program_account.key != bpf_loader_upgradeable::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if *program_account.key != proposal_account_info.execution_source
+        || *destination_account.key != proposal_account_info.execution_destination
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (sol_address, sol_bump_seed) = get_vault_address_with_seed(
+        squad_account.key,
+        proposal_account_info.vault_index,
+        program_id,
+    );
+    if *sol_account.key != sol_address {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let sol_seeds = get_vault_signer_seeds(
+        squad_account.key,
+        proposal_account_info.vault_index,
+        sol_bump_seed,
+    );
+    let sol_seeds_refs: Vec<&[u8]> = sol_seeds.iter().map(|seed| seed.as_slice()).collect();
+    let sol_signer_seeds: &[&[_]] = &sol_seeds_refs;
+
+    match FromPrimitive::from_u8(proposal_account_info.proposal_type) {
+        Some(ProposalType::UpgradeProgram) => {
+            let spill_account = next_account_info(account_info_iter)?;
+            if *spill_account.key != proposal_account_info.spill_address {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            // the buffer has to be the one members voted on
+            if hash(&destination_account.data.borrow()).to_bytes()
+                != proposal_account_info.buffer_hash
+            {
+                msg!("SQDS: Program buffer changed since the proposal was created");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            invoke_signed(
+                &bpf_loader_upgradeable::upgrade(
+                    program_account.key,
+                    destination_account.key,
+                    &sol_address,
+                    spill_account.key,
+                ),
+                &[
+                    program_data_account.clone(),
+                    program_account.clone(),
+                    destination_account.clone(),
+                    spill_account.clone(),
+                    rent_account.clone(),
+                    clock_account.clone(),
+                    sol_account.clone(),
+                    loader_program_account.clone(),
+                ],
+                &[sol_signer_seeds],
+            )?;
+        }
+        Some(ProposalType::SetUpgradeAuthority) => {
+            invoke_signed(
+                &bpf_loader_upgradeable::set_upgrade_authority(
+                    program_account.key,
+                    &sol_address,
+                    Some(destination_account.key),
+                ),
+                &[
+                    program_data_account.clone(),
+                    sol_account.clone(),
+                    destination_account.clone(),
+                    loader_program_account.clone(),
+                ],
+                &[sol_signer_seeds],
+            )?;
+        }
+        _ => return Err(ProgramError::InvalidArgument),
+    }
+    Ok(())
+}
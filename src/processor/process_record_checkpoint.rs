@@ -0,0 +1,146 @@
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::{allocate, assign, create_account, transfer},
+    sysvar::Sysvar,
+};
+
+use crate::{state::checkpoint::Checkpoints, *};
+
+// records a balance change of a member equity account (owner = member) or of the
+// squad mint supply (owner = squad mint), the history account is created on the first change
+#[allow(clippy::too_many_arguments)]
+pub fn process_record_checkpoint<'a>(
+    payer: &AccountInfo<'a>,
+    squad_account: &AccountInfo<'a>,
+    checkpoints_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    rent: &Rent,
+    owner: &Pubkey,
+    previous_amount: u64,
+    amount: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let (checkpoints_address, checkpoints_bump_seed) =
+        get_checkpoints_address_with_seed(squad_account.key, owner, program_id);
+    if checkpoints_address != *checkpoints_account.key {
+        msg!("SQDS: Checkpoints account PDA mismatch");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if checkpoints_account.data_is_empty() {
+        let checkpoints_signer_seeds: &[&[_]] = &[
+            &squad_account.key.to_bytes(),
+            &owner.to_bytes(),
+            b"!checkpoint",
+            &[checkpoints_bump_seed],
+        ];
+
+        // DoS check
+        let rent_exempt_lamports = rent.minimum_balance(Checkpoints::get_packed_len()).max(1);
+        if checkpoints_account.lamports() > 0 {
+            let top_up_lamports =
+                rent_exempt_lamports.saturating_sub(checkpoints_account.lamports());
+
+            if top_up_lamports > 0 {
+                invoke(
+                    &transfer(payer.key, checkpoints_account.key, top_up_lamports),
+                    &[
+                        payer.clone(),
+                        checkpoints_account.clone(),
+                        system_program_account.clone(),
+                    ],
+                )?;
+            }
+
+            invoke_signed(
+                &allocate(
+                    checkpoints_account.key,
+                    Checkpoints::get_packed_len() as u64,
+                ),
+                &[checkpoints_account.clone(), system_program_account.clone()],
+                &[checkpoints_signer_seeds],
+            )?;
+
+            invoke_signed(
+                &assign(checkpoints_account.key, program_id),
+                &[checkpoints_account.clone(), system_program_account.clone()],
+                &[checkpoints_signer_seeds],
+            )?;
+        } else {
+            invoke_signed(
+                &create_account(
+                    payer.key,
+                    &checkpoints_address,
+                    rent_exempt_lamports,
+                    Checkpoints::get_packed_len() as u64,
+                    program_id,
+                ),
+                &[
+                    payer.clone(),
+                    checkpoints_account.clone(),
+                    system_program_account.clone(),
+                ],
+                &[checkpoints_signer_seeds],
+            )?;
+        }
+    }
+
+    let mut checkpoints_account_info =
+        get_checkpoints(program_id, squad_account, checkpoints_account)?;
+    if !checkpoints_account_info.is_initialized {
+        checkpoints_account_info.save_checkpoints(squad_account.key, owner);
+    }
+
+    checkpoints_account_info.record(
+        Clock::get().unwrap().unix_timestamp,
+        previous_amount,
+        amount,
+    );
+    Checkpoints::pack(
+        checkpoints_account_info,
+        &mut checkpoints_account.data.borrow_mut(),
+    )?;
+    Ok(())
+}
+
+// balance held right before `timestamp` by the history owner, a balance that never
+// changed has no history yet and is still the live one. Past a pruned history it's the
+// lowest balance dropped, or the highest one for a supply (`highest`)
+pub fn checkpointed_amount(
+    squad_account: &AccountInfo,
+    checkpoints_account: &AccountInfo,
+    owner: &Pubkey,
+    timestamp: i64,
+    live_amount: u64,
+    highest: bool,
+    program_id: &Pubkey,
+) -> Result<u64, ProgramError> {
+    let checkpoints_address = get_checkpoints_address(squad_account.key, owner, program_id);
+    if checkpoints_address != *checkpoints_account.key {
+        msg!("SQDS: Checkpoints account PDA mismatch");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if checkpoints_account.data_is_empty() {
+        return Ok(live_amount);
+    }
+
+    let checkpoints_account_info = get_checkpoints(program_id, squad_account, checkpoints_account)?;
+    if !checkpoints_account_info.is_initialized {
+        return Ok(live_amount);
+    }
+    match checkpoints_account_info.amount_bounded_at(timestamp, highest) {
+        Some(amount) => Ok(amount),
+        None => {
+            msg!("SQDS: Balance history doesn't go back to this proposal");
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::{allocate, assign, create_account, transfer},
+};
+
+use crate::{
+    state::config::{optimistic_eligible, SquadConfig},
+    *,
+};
+
+// sets the threshold of a proposal type (0 to clear it), the config account is
+// created on the first setting, the caller flags the squad as having one
+#[allow(clippy::too_many_arguments)]
+pub fn process_set_type_threshold<'a>(
+    payer: &AccountInfo<'a>,
+    squad_account: &AccountInfo<'a>,
+    config_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    rent: &Rent,
+    proposal_type: u8,
+    threshold: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let mut config_account_info = load_or_create_config(
+        payer,
+        squad_account,
+        config_account,
+        system_program_account,
+        rent,
+        program_id,
+    )?;
+
+    match config_account_info
+        .type_thresholds
+        .get_mut(proposal_type as usize)
+    {
+        Some(type_threshold) => *type_threshold = threshold,
+        None => return Err(ProgramError::InvalidArgument),
+    }
+    SquadConfig::pack(config_account_info, &mut config_account.data.borrow_mut())?;
+    Ok(())
+}
+
+// sets the veto threshold making a proposal type optimistic (0 to clear it)
+#[allow(clippy::too_many_arguments)]
+pub fn process_set_optimistic_veto<'a>(
+    payer: &AccountInfo<'a>,
+    squad_account: &AccountInfo<'a>,
+    config_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    rent: &Rent,
+    proposal_type: u8,
+    veto_threshold: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let mut config_account_info = load_or_create_config(
+        payer,
+        squad_account,
+        config_account,
+        system_program_account,
+        rent,
+        program_id,
+    )?;
+
+    // proposals created before the allowlist can still clear a type, not add one
+    if veto_threshold > 0 && !optimistic_eligible(proposal_type) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    match config_account_info
+        .optimistic_vetoes
+        .get_mut(proposal_type as usize)
+    {
+        Some(optimistic_veto) => *optimistic_veto = veto_threshold,
+        None => return Err(ProgramError::InvalidArgument),
+    }
+    SquadConfig::pack(config_account_info, &mut config_account.data.borrow_mut())?;
+    Ok(())
+}
+
+// sets how long optimistic proposals can be vetoed and the largest amount they can move
+#[allow(clippy::too_many_arguments)]
+pub fn process_set_challenge_window<'a>(
+    payer: &AccountInfo<'a>,
+    squad_account: &AccountInfo<'a>,
+    config_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    rent: &Rent,
+    challenge_window: i64,
+    optimistic_max_amount: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let mut config_account_info = load_or_create_config(
+        payer,
+        squad_account,
+        config_account,
+        system_program_account,
+        rent,
+        program_id,
+    )?;
+
+    config_account_info.challenge_window = challenge_window;
+    config_account_info.optimistic_max_amount = optimistic_max_amount;
+    SquadConfig::pack(config_account_info, &mut config_account.data.borrow_mut())?;
+    Ok(())
+}
+
+// sets the default expiry of Multisig proposals and their execution window once approved
+#[allow(clippy::too_many_arguments)]
+pub fn process_set_proposal_expiry<'a>(
+    payer: &AccountInfo<'a>,
+    squad_account: &AccountInfo<'a>,
+    config_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    rent: &Rent,
+    proposal_expiry: i64,
+    execution_window: i64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let mut config_account_info = load_or_create_config(
+        payer,
+        squad_account,
+        config_account,
+        system_program_account,
+        rent,
+        program_id,
+    )?;
+
+    config_account_info.proposal_expiry = proposal_expiry;
+    config_account_info.execution_window = execution_window;
+    SquadConfig::pack(config_account_info, &mut config_account.data.borrow_mut())?;
+    Ok(())
+}
+
+fn load_or_create_config<'a>(
+    payer: &AccountInfo<'a>,
+    squad_account: &AccountInfo<'a>,
+    config_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    rent: &Rent,
+    program_id: &Pubkey,
+) -> Result<SquadConfig, ProgramError> {
+    let (config_address, config_bump_seed) =
+        get_config_address_with_seed(squad_account.key, program_id);
+    if config_address != *config_account.key {
+        msg!("SQDS: Config account PDA mismatch");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if config_account.data_is_empty() {
+        let config_signer_seeds: &[&[_]] = &[
+            &squad_account.key.to_bytes(),
+            b"!config",
+            &[config_bump_seed],
+        ];
+
+        // DoS check
+        let rent_exempt_lamports = rent.minimum_balance(SquadConfig::get_packed_len()).max(1);
+        if config_account.lamports() > 0 {
+            let top_up_lamports = rent_exempt_lamports.saturating_sub(config_account.lamports());
+
+            if top_up_lamports > 0 {
+                invoke(
+                    &transfer(payer.key, config_account.key, top_up_lamports),
+                    &[
+                        payer.clone(),
+                        config_account.clone(),
+                        system_program_account.clone(),
+                    ],
+                )?;
+            }
+
+            invoke_signed(
+                &allocate(config_account.key, SquadConfig::get_packed_len() as u64),
+                &[config_account.clone(), system_program_account.clone()],
+                &[config_signer_seeds],
+            )?;
+
+            invoke_signed(
+                &assign(config_account.key, program_id),
+                &[config_account.clone(), system_program_account.clone()],
+                &[config_signer_seeds],
+            )?;
+        } else {
+            invoke_signed(
+                &create_account(
+                    payer.key,
+                    &config_address,
+                    rent_exempt_lamports,
+                    SquadConfig::get_packed_len() as u64,
+                    program_id,
+                ),
+                &[
+                    payer.clone(),
+                    config_account.clone(),
+                    system_program_account.clone(),
+                ],
+                &[config_signer_seeds],
+            )?;
+        }
+    }
+
+    let mut config_account_info = get_config(program_id, squad_account, config_account)?;
+    if !config_account_info.is_initialized {
+        config_account_info.save_config(squad_account.key);
+    }
+    Ok(config_account_info)
+}
+
+// the squad config after checking its address, empty if it was never created
+pub fn squad_config_info(
+    squad_account: &AccountInfo,
+    config_account: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<SquadConfig, ProgramError> {
+    let config_address = get_config_address(squad_account.key, program_id);
+    if config_address != *config_account.key {
+        msg!("SQDS: Config account PDA mismatch");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    get_config(program_id, squad_account, config_account)
+}
@@ -0,0 +1,150 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account;
+
+use crate::processor::process_record_checkpoint;
+use crate::{state::squad::Squad, *};
+
+pub fn process_withdraw_governance(
+    accounts: &[AccountInfo],
+    amount: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let member = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    let governance_mint_account = next_account_info(account_info_iter)?;
+    let destination_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let escrow_authority = next_account_info(account_info_iter)?;
+    let member_checkpoints_account = next_account_info(account_info_iter)?;
+    let supply_checkpoints_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(rent_account)?;
+
+    if !member.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut squad_account_info = get_squad(program_id, squad_account)?;
+    if !squad_account_info.token_gated {
+        msg!("SQDS: Squad isn't token gated");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if !Squad::member_exists(&squad_account_info, member.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if *governance_mint_account.key != squad_account_info.mint_address {
+        msg!("SQDS: Incorrect governance mint address");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if *token_program_account.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let escrow_address = get_equity_address(member.key, squad_account.key, program_id);
+    if escrow_address != *escrow_account.key {
+        msg!("SQDS: Invalid member governance address");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (escrow_authority_address, escrow_authority_bump_seed) =
+        get_mint_address_with_seed(squad_account.key, program_id);
+    if escrow_authority_address != *escrow_authority.key {
+        msg!("SQDS: Escrow authority address mismatch");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // every deposit records a checkpoint, so the member history is there
+    let checkpoints_address = get_checkpoints_address(squad_account.key, member.key, program_id);
+    if checkpoints_address != *member_checkpoints_account.key {
+        msg!("SQDS: Checkpoints account PDA mismatch");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let member_checkpoints_info =
+        get_checkpoints(program_id, squad_account, member_checkpoints_account)?;
+    if member_checkpoints_info.votes_locked_until >= Clock::get().unwrap().unix_timestamp {
+        msg!("SQDS: Deposit is locked until the proposals voted on are closed");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let previous_amount = Account::unpack(&escrow_account.data.borrow())?.amount;
+    if amount == 0 || amount > previous_amount {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    if !squad_account_info.escrow_balance_allowed(previous_amount - amount) {
+        msg!("SQDS: Withdrawal leaves less than the squad minimum deposit");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let escrow_authority_signer_seeds: &[&[_]] = &[
+        &squad_account.key.to_bytes(),
+        b"!squadmint",
+        &[escrow_authority_bump_seed],
+    ];
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            escrow_account.key,
+            destination_account.key,
+            escrow_authority.key,
+            &[],
+            amount,
+        )?,
+        &[
+            escrow_account.clone(),
+            destination_account.clone(),
+            escrow_authority.clone(),
+            token_program_account.clone(),
+        ],
+        &[escrow_authority_signer_seeds],
+    )?;
+
+    process_record_checkpoint(
+        member,
+        squad_account,
+        member_checkpoints_account,
+        system_program_account,
+        rent,
+        member.key,
+        previous_amount,
+        previous_amount - amount,
+        program_id,
+    )?;
+    process_record_checkpoint(
+        member,
+        squad_account,
+        supply_checkpoints_account,
+        system_program_account,
+        rent,
+        governance_mint_account.key,
+        squad_account_info.deposited_supply,
+        squad_account_info.deposited_supply - amount,
+        program_id,
+    )?;
+    squad_account_info.deposited_supply -= amount;
+
+    // withdrawing everything leaves the squad, pending proposals keep their snapshot
+    if previous_amount == amount {
+        if !squad_account_info.core_seat_removable(member.key) {
+            msg!("SQDS: Not enough core members left to reach the core threshold");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Squad::remove_member(&mut squad_account_info, member.key);
+        squad_account_info.membership_version += 1;
+    }
+
+    Squad::pack(squad_account_info, &mut squad_account.data.borrow_mut())?;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::{state::stream::Stream, *};
+
+// pulls whatever accrued on a stream since the last withdrawal to the recipient
+pub fn process_withdraw_stream(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let initializer = next_account_info(account_info_iter)?;
+    let squad_account = next_account_info(account_info_iter)?;
+    let stream_account = next_account_info(account_info_iter)?;
+    let destination_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // check the token program
+    if *token_program_account.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut stream_account_info = get_stream(program_id, squad_account, stream_account)?;
+    if !stream_account_info.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    // only the recipient can pull from the stream
+    if stream_account_info.recipient != *initializer.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let withdrawable =
+        stream_account_info.withdrawable_amount(Clock::get().unwrap().unix_timestamp);
+    if withdrawable == 0 {
+        msg!("SQDS: Nothing has accrued on this stream yet");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if stream_account_info.is_sol() {
+        **stream_account.try_borrow_mut_lamports()? -= withdrawable;
+        **destination_account.try_borrow_mut_lamports()? += withdrawable;
+    } else {
+        let stream_token_account = next_account_info(account_info_iter)?;
+        if stream_account_info.escrow != *stream_token_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (_stream_address, stream_bump_seed) =
+            get_stream_address_with_seed(&stream_account_info.proposal_address, program_id);
+        let stream_signer_seeds: &[&[_]] = &[
+            &stream_account_info.proposal_address.to_bytes(),
+            b"!stream",
+            &[stream_bump_seed],
+        ];
+
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program_account.key,
+                stream_token_account.key,
+                destination_account.key,
+                stream_account.key,
+                &[],
+                withdrawable,
+            )?,
+            &[
+                stream_token_account.clone(),
+                destination_account.clone(),
+                stream_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[stream_signer_seeds],
+        )?;
+    }
+
+    stream_account_info.withdrawn_amount += withdrawable;
+    Stream::pack(stream_account_info, &mut stream_account.data.borrow_mut())?;
+    Ok(())
+}
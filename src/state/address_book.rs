@@ -0,0 +1,232 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use borsh::{BorshDeserialize, BorshSerialize};
+use num_traits::FromPrimitive;
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use crate::state::proposal::ProposalType;
+use crate::UnixTimestamp;
+
+/// Number of destinations an address book can hold
+pub const MAX_ADDRESS_BOOK_ENTRIES: usize = 64;
+const ADDRESS_BOOK_INITIALIZED_BYTES: usize = 1;
+const PUBLIC_KEY_BYTES: usize = 32;
+const UNLISTED_THRESHOLD_BYTES: usize = 1;
+const TIMESTAMP_BYTES: usize = 8;
+const ADDRESS_BOOK_ENTRIES_BYTES: usize = 4 + PUBLIC_KEY_BYTES * MAX_ADDRESS_BOOK_ENTRIES;
+const ADDRESS_BOOK_RESERVED_BYTES: usize = 8 * 4;
+
+const ADDRESS_BOOK_TOTAL_BYTES: usize = ADDRESS_BOOK_INITIALIZED_BYTES + // is_initialized 1
+    PUBLIC_KEY_BYTES +                  // squad address 32
+    UNLISTED_THRESHOLD_BYTES +          // unlisted_threshold 1
+    TIMESTAMP_BYTES +                   // unlisted_delay 8
+    ADDRESS_BOOK_ENTRIES_BYTES +        // entries 4 + 32 * 64
+    ADDRESS_BOOK_RESERVED_BYTES; // reserved for updates
+
+/// Whether the destination of proposals of the type goes through the address book,
+/// every proposal that sends value out of the vault or hands it to someone else
+pub fn address_book_checked(proposal_type: u8) -> bool {
+    matches!(
+        FromPrimitive::from_u8(proposal_type),
+        Some(ProposalType::WithdrawSol)
+            | Some(ProposalType::WithdrawSpl)
+            | Some(ProposalType::MintTo)
+            | Some(ProposalType::ApproveDelegate)
+    )
+}
+
+/// Withdrawal destinations approved by the squad, withdrawals anywhere else
+/// need the unlisted threshold and wait out the unlisted delay
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct AddressBook {
+    pub is_initialized: bool,
+    pub squad_address: Pubkey,
+    // approvals for a Multisig, support percent for a Team, 0 for the usual threshold
+    pub unlisted_threshold: u8,
+    // seconds after creation before an unlisted withdrawal can execute
+    pub unlisted_delay: UnixTimestamp,
+    // wallets for SOL withdrawals, token accounts for SPL withdrawals and mints,
+    // delegates for token approvals
+    pub entries: Vec<Pubkey>,
+
+    // reserved for future updates
+    pub reserved: [u64; 4],
+}
+
+impl Sealed for AddressBook {}
+
+impl IsInitialized for AddressBook {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl AddressBook {
+    pub fn save_address_book(&mut self, squad_address: &Pubkey) {
+        self.is_initialized = true;
+        self.squad_address = *squad_address;
+        self.entries = Vec::new();
+    }
+
+    pub fn contains(&self, destination: &Pubkey) -> bool {
+        self.entries.contains(destination)
+    }
+
+    /// Add or remove a destination, false if the book is full
+    pub fn set_entry(&mut self, destination: &Pubkey, listed: bool) -> bool {
+        if !listed {
+            self.entries.retain(|entry| entry != destination);
+            return true;
+        }
+        if self.contains(destination) {
+            return true;
+        }
+        if self.entries.len() >= MAX_ADDRESS_BOOK_ENTRIES {
+            return false;
+        }
+        self.entries.push(*destination);
+        true
+    }
+}
+
+impl Pack for AddressBook {
+    const LEN: usize = ADDRESS_BOOK_TOTAL_BYTES;
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, ADDRESS_BOOK_TOTAL_BYTES];
+
+        let (
+            is_initialized_dst,
+            squad_address_dst,
+            unlisted_threshold_dst,
+            unlisted_delay_dst,
+            entries_dst,
+            _reserved,
+        ) = mut_array_refs![
+            dst,
+            ADDRESS_BOOK_INITIALIZED_BYTES, // is_initialized 1
+            PUBLIC_KEY_BYTES,               // squad address 32
+            UNLISTED_THRESHOLD_BYTES,       // unlisted_threshold 1
+            TIMESTAMP_BYTES,                // unlisted_delay 8
+            ADDRESS_BOOK_ENTRIES_BYTES,     // entries
+            ADDRESS_BOOK_RESERVED_BYTES     // reserved
+        ];
+
+        let AddressBook {
+            is_initialized,
+            squad_address,
+            unlisted_threshold,
+            unlisted_delay,
+            entries,
+            reserved: _,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        squad_address_dst.copy_from_slice(squad_address.as_ref());
+        unlisted_threshold_dst[0] = *unlisted_threshold;
+        *unlisted_delay_dst = unlisted_delay.to_le_bytes();
+
+        // clear the bytes of removed entries
+        entries_dst.fill(0);
+        let entries_ser = entries.try_to_vec().unwrap();
+        entries_dst[..entries_ser.len()].copy_from_slice(&entries_ser);
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, ADDRESS_BOOK_TOTAL_BYTES];
+        let (
+            is_initialized,
+            squad_address,
+            unlisted_threshold,
+            unlisted_delay,
+            entries_src,
+            _reserved,
+        ) = array_refs![
+            src,
+            ADDRESS_BOOK_INITIALIZED_BYTES, // is_initialized 1
+            PUBLIC_KEY_BYTES,               // squad address 32
+            UNLISTED_THRESHOLD_BYTES,       // unlisted_threshold 1
+            TIMESTAMP_BYTES,                // unlisted_delay 8
+            ADDRESS_BOOK_ENTRIES_BYTES,     // entries
+            ADDRESS_BOOK_RESERVED_BYTES     // reserved
+        ];
+
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        // the entries are length prefixed, the unused bytes after them are ignored
+        let entries = Vec::<Pubkey>::deserialize(&mut &entries_src[..])
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+
+        Ok(AddressBook {
+            is_initialized,
+            squad_address: Pubkey::new(squad_address),
+            unlisted_threshold: unlisted_threshold[0],
+            unlisted_delay: i64::from_le_bytes(*unlisted_delay),
+            entries,
+            reserved: [0; 4],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address_book_entries() {
+        let address_book_arr = [0; ADDRESS_BOOK_TOTAL_BYTES];
+        let mut address_book_info = AddressBook::unpack_unchecked(&address_book_arr).unwrap();
+        address_book_info.save_address_book(&Pubkey::new_unique());
+        address_book_info.unlisted_threshold = 4;
+        address_book_info.unlisted_delay = 86_400;
+
+        let listed = Pubkey::new_unique();
+        let removed = Pubkey::new_unique();
+        assert!(address_book_info.set_entry(&listed, true));
+        assert!(address_book_info.set_entry(&removed, true));
+        assert!(address_book_info.set_entry(&listed, true));
+        assert!(address_book_info.set_entry(&removed, false));
+
+        let mut address_book_dst = [0; ADDRESS_BOOK_TOTAL_BYTES];
+        AddressBook::pack(address_book_info, &mut address_book_dst).unwrap();
+        let mut unpacked = AddressBook::unpack_unchecked(&address_book_dst).unwrap();
+        assert_eq!(unpacked.entries, vec![listed]);
+        assert!(!unpacked.contains(&removed));
+        assert_eq!(unpacked.unlisted_threshold, 4);
+        assert_eq!(unpacked.unlisted_delay, 86_400);
+
+        // a full book rejects new destinations
+        while unpacked.entries.len() < MAX_ADDRESS_BOOK_ENTRIES {
+            assert!(unpacked.set_entry(&Pubkey::new_unique(), true));
+        }
+        assert!(!unpacked.set_entry(&Pubkey::new_unique(), true));
+        let mut full_dst = [0; ADDRESS_BOOK_TOTAL_BYTES];
+        AddressBook::pack(unpacked, &mut full_dst).unwrap();
+    }
+
+    #[test]
+    fn address_book_checked_types() {
+        for proposal_type in [
+            ProposalType::WithdrawSol,
+            ProposalType::WithdrawSpl,
+            ProposalType::MintTo,
+            ProposalType::ApproveDelegate,
+        ] {
+            assert!(address_book_checked(proposal_type as u8));
+        }
+        for proposal_type in [
+            ProposalType::Text,
+            ProposalType::RevokeDelegate,
+            ProposalType::BurnTokens,
+            ProposalType::CloseTokenAccount,
+        ] {
+            assert!(!address_book_checked(proposal_type as u8));
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use crate::UnixTimestamp;
+
+/// Number of balance changes kept, the oldest one is dropped past that and only
+/// the range of the dropped balances is kept
+pub const MAX_CHECKPOINTS: usize = 64;
+const CHECKPOINTS_INITIALIZED_BYTES: usize = 1;
+const PUBLIC_KEY_BYTES: usize = 32;
+const CHECKPOINT_BYTES: usize = 8 + 8;
+const CHECKPOINTS_HISTORY_BYTES: usize = 4 + CHECKPOINT_BYTES * MAX_CHECKPOINTS;
+const TIMESTAMP_BYTES: usize = 8;
+const AMOUNT_BYTES: usize = 8;
+const CHECKPOINTS_RESERVED_BYTES: usize = 8;
+
+const CHECKPOINTS_TOTAL_BYTES: usize = CHECKPOINTS_INITIALIZED_BYTES + // is_initialized 1
+    PUBLIC_KEY_BYTES +                  // squad address 32
+    PUBLIC_KEY_BYTES +                  // owner 32
+    CHECKPOINTS_HISTORY_BYTES +         // history 4 + 16 * 64
+    TIMESTAMP_BYTES +                   // votes_locked_until 8
+    AMOUNT_BYTES +                      // pruned_low 8
+    AMOUNT_BYTES +                      // pruned_high 8
+    CHECKPOINTS_RESERVED_BYTES; // reserved for updates
+
+/// A balance and the time it took effect
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, Copy)]
+pub struct Checkpoint {
+    pub timestamp: UnixTimestamp,
+    pub amount: u64,
+}
+
+/// Balance history of a member equity account, or of the squad mint supply,
+/// so votes are weighted as of the proposal creation. In a token-gated squad it
+/// also keeps the member deposit locked while their votes are active
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct Checkpoints {
+    pub is_initialized: bool,
+    pub squad_address: Pubkey,
+    // the member whose equity is tracked, the squad mint for the supply
+    pub owner: Pubkey,
+    // oldest first
+    pub history: Vec<Checkpoint>,
+    // close of the latest proposal the member voted on, deposits can't be withdrawn before
+    pub votes_locked_until: UnixTimestamp,
+    // lowest and highest balance among the changes dropped from a full history
+    pub pruned_low: u64,
+    pub pruned_high: u64,
+
+    // reserved for future updates
+    pub reserved: [u64; 1],
+}
+
+impl Sealed for Checkpoints {}
+
+impl IsInitialized for Checkpoints {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Checkpoints {
+    pub fn save_checkpoints(&mut self, squad_address: &Pubkey, owner: &Pubkey) {
+        self.is_initialized = true;
+        self.squad_address = *squad_address;
+        self.owner = *owner;
+        self.history = Vec::new();
+    }
+
+    /// Record a balance change, the first one also records the balance held until then
+    pub fn record(&mut self, timestamp: UnixTimestamp, previous_amount: u64, amount: u64) {
+        if self.history.is_empty() {
+            self.history.push(Checkpoint {
+                timestamp: 0,
+                amount: previous_amount,
+            });
+        }
+        match self.history.last_mut() {
+            // several changes within the same second only keep the last balance
+            Some(last) if last.timestamp == timestamp => last.amount = amount,
+            _ => self.history.push(Checkpoint { timestamp, amount }),
+        }
+        if self.history.len() > MAX_CHECKPOINTS {
+            // the balance held from the start goes first, it opens the pruned range
+            let dropped = self.history.remove(0);
+            if dropped.timestamp == 0 {
+                self.pruned_low = dropped.amount;
+                self.pruned_high = dropped.amount;
+            } else {
+                self.pruned_low = self.pruned_low.min(dropped.amount);
+                self.pruned_high = self.pruned_high.max(dropped.amount);
+            }
+        }
+    }
+
+    /// Keep the deposit locked until the proposal voted on closes
+    pub fn lock_votes_until(&mut self, close_timestamp: UnixTimestamp) {
+        self.votes_locked_until = self.votes_locked_until.max(close_timestamp);
+    }
+
+    /// Balance held right before `timestamp`, None if the history doesn't go back that far
+    pub fn amount_at(&self, timestamp: UnixTimestamp) -> Option<u64> {
+        self.history
+            .iter()
+            .rev()
+            .find(|checkpoint| checkpoint.timestamp < timestamp)
+            .map(|checkpoint| checkpoint.amount)
+    }
+
+    /// Whether changes were dropped from the history
+    pub fn is_pruned(&self) -> bool {
+        matches!(self.history.first(), Some(checkpoint) if checkpoint.timestamp != 0)
+    }
+
+    /// Balance held right before `timestamp`, or past the start of the history the
+    /// lowest (highest) balance it dropped, so a vote weight is never overstated and
+    /// the supply it's measured against never understated
+    pub fn amount_bounded_at(&self, timestamp: UnixTimestamp, highest: bool) -> Option<u64> {
+        match self.amount_at(timestamp) {
+            Some(amount) => Some(amount),
+            None if self.is_pruned() && highest => Some(self.pruned_high),
+            None if self.is_pruned() => Some(self.pruned_low),
+            None => None,
+        }
+    }
+}
+
+impl Pack for Checkpoints {
+    const LEN: usize = CHECKPOINTS_TOTAL_BYTES;
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, CHECKPOINTS_TOTAL_BYTES];
+
+        let (
+            is_initialized_dst,
+            squad_address_dst,
+            owner_dst,
+            history_dst,
+            votes_locked_until_dst,
+            pruned_low_dst,
+            pruned_high_dst,
+            _reserved,
+        ) = mut_array_refs![
+            dst,
+            CHECKPOINTS_INITIALIZED_BYTES, // is_initialized 1
+            PUBLIC_KEY_BYTES,              // squad address 32
+            PUBLIC_KEY_BYTES,              // owner 32
+            CHECKPOINTS_HISTORY_BYTES,     // history
+            TIMESTAMP_BYTES,               // votes_locked_until 8
+            AMOUNT_BYTES,                  // pruned_low 8
+            AMOUNT_BYTES,                  // pruned_high 8
+            CHECKPOINTS_RESERVED_BYTES     // reserved
+        ];
+
+        let Checkpoints {
+            is_initialized,
+            squad_address,
+            owner,
+            history,
+            votes_locked_until,
+            pruned_low,
+            pruned_high,
+            reserved: _,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        squad_address_dst.copy_from_slice(squad_address.as_ref());
+        owner_dst.copy_from_slice(owner.as_ref());
+
+        let history_ser = history.try_to_vec().unwrap();
+        history_dst[..history_ser.len()].copy_from_slice(&history_ser);
+        *votes_locked_until_dst = votes_locked_until.to_le_bytes();
+        *pruned_low_dst = pruned_low.to_le_bytes();
+        *pruned_high_dst = pruned_high.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, CHECKPOINTS_TOTAL_BYTES];
+        let (
+            is_initialized,
+            squad_address,
+            owner,
+            history_src,
+            votes_locked_until,
+            pruned_low,
+            pruned_high,
+            _reserved,
+        ) = array_refs![
+            src,
+            CHECKPOINTS_INITIALIZED_BYTES, // is_initialized 1
+            PUBLIC_KEY_BYTES,              // squad address 32
+            PUBLIC_KEY_BYTES,              // owner 32
+            CHECKPOINTS_HISTORY_BYTES,     // history
+            TIMESTAMP_BYTES,               // votes_locked_until 8
+            AMOUNT_BYTES,                  // pruned_low 8
+            AMOUNT_BYTES,                  // pruned_high 8
+            CHECKPOINTS_RESERVED_BYTES     // reserved
+        ];
+
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        // the history is length prefixed, the unused bytes after it are ignored
+        let history = Vec::<Checkpoint>::deserialize(&mut &history_src[..])
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+
+        Ok(Checkpoints {
+            is_initialized,
+            squad_address: Pubkey::new(squad_address),
+            owner: Pubkey::new(owner),
+            history,
+            votes_locked_until: i64::from_le_bytes(*votes_locked_until),
+            pruned_low: u64::from_le_bytes(*pruned_low),
+            pruned_high: u64::from_le_bytes(*pruned_high),
+            reserved: [0; 1],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoints_history() {
+        let checkpoints_arr = [0; CHECKPOINTS_TOTAL_BYTES];
+        let mut checkpoints_info = Checkpoints::unpack_unchecked(&checkpoints_arr).unwrap();
+        checkpoints_info.save_checkpoints(&Pubkey::new_unique(), &Pubkey::new_unique());
+
+        checkpoints_info.record(100, 10, 30);
+        checkpoints_info.record(200, 30, 5);
+        checkpoints_info.record(200, 5, 0);
+
+        // the balance before the first change is kept from the start
+        assert_eq!(checkpoints_info.amount_at(50), Some(10));
+        assert_eq!(checkpoints_info.amount_at(100), Some(10));
+        assert_eq!(checkpoints_info.amount_at(101), Some(30));
+        assert_eq!(checkpoints_info.amount_at(300), Some(0));
+
+        let mut checkpoints_dst = [0; CHECKPOINTS_TOTAL_BYTES];
+        Checkpoints::pack(checkpoints_info, &mut checkpoints_dst).unwrap();
+        let mut unpacked = Checkpoints::unpack_unchecked(&checkpoints_dst).unwrap();
+        assert_eq!(unpacked.history.len(), 3);
+
+        // a full history drops the oldest change
+        for i in 0..MAX_CHECKPOINTS as i64 {
+            unpacked.record(1_000 + i, 0, i as u64);
+        }
+        assert_eq!(unpacked.history.len(), MAX_CHECKPOINTS);
+        assert_eq!(unpacked.amount_at(150), None);
+        assert_eq!(unpacked.amount_at(1_010), Some(9));
+
+        // a vote only ever extends the deposit lock
+        unpacked.lock_votes_until(500);
+        unpacked.lock_votes_until(400);
+        let mut full_dst = [0; CHECKPOINTS_TOTAL_BYTES];
+        Checkpoints::pack(unpacked, &mut full_dst).unwrap();
+        let repacked = Checkpoints::unpack_unchecked(&full_dst).unwrap();
+        assert_eq!(repacked.votes_locked_until, 500);
+    }
+
+    #[test]
+    fn checkpoints_pruned_history() {
+        let checkpoints_arr = [0; CHECKPOINTS_TOTAL_BYTES];
+        let mut checkpoints_info = Checkpoints::unpack_unchecked(&checkpoints_arr).unwrap();
+        checkpoints_info.save_checkpoints(&Pubkey::new_unique(), &Pubkey::new_unique());
+
+        // 10 until 100, then a deposit of 1_000 before a run of small changes
+        checkpoints_info.record(100, 10, 1_010);
+        for i in 0..MAX_CHECKPOINTS as i64 {
+            checkpoints_info.record(200 + i, 0, 1_010 + i as u64);
+        }
+        assert!(checkpoints_info.is_pruned());
+        assert_eq!(checkpoints_info.amount_at(50), None);
+        assert_eq!(checkpoints_info.amount_at(150), None);
+
+        // a proposal created before the kept history is still votable, its weight
+        // can't exceed and its supply can't fall below what the dropped changes held
+        assert_eq!(checkpoints_info.amount_bounded_at(50, false), Some(10));
+        assert_eq!(checkpoints_info.amount_bounded_at(150, false), Some(10));
+        assert_eq!(checkpoints_info.amount_bounded_at(150, true), Some(1_010));
+        // the kept history stays exact
+        assert_eq!(checkpoints_info.amount_bounded_at(210, false), Some(1_019));
+
+        let mut checkpoints_dst = [0; CHECKPOINTS_TOTAL_BYTES];
+        Checkpoints::pack(checkpoints_info, &mut checkpoints_dst).unwrap();
+        let unpacked = Checkpoints::unpack_unchecked(&checkpoints_dst).unwrap();
+        assert_eq!(unpacked.pruned_low, 10);
+        assert_eq!(unpacked.pruned_high, 1_010);
+
+        // a history that never filled up doesn't reach back past its start
+        let mut fresh = Checkpoints::unpack_unchecked(&checkpoints_arr).unwrap();
+        fresh.save_checkpoints(&Pubkey::new_unique(), &Pubkey::new_unique());
+        assert_eq!(fresh.amount_bounded_at(50, true), None);
+        fresh.record(100, 10, 30);
+        assert!(!fresh.is_pruned());
+        assert_eq!(fresh.amount_bounded_at(50, true), Some(10));
+    }
+}
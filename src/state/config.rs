@@ -0,0 +1,326 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use num_traits::FromPrimitive;
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+/// Number of proposal types the threshold tables have room for
+pub const MAX_PROPOSAL_TYPES: usize = 48;
+// the first tables only had room for 32 types, the rest is stored after them
+const LEGACY_PROPOSAL_TYPES: usize = 32;
+const EXTENDED_PROPOSAL_TYPES: usize = MAX_PROPOSAL_TYPES - LEGACY_PROPOSAL_TYPES;
+const CONFIG_INITIALIZED_BYTES: usize = 1;
+const PUBLIC_KEY_BYTES: usize = 32;
+const AMOUNT_BYTES: usize = 8;
+const TIMESTAMP_BYTES: usize = 8;
+const CONFIG_RESERVED_BYTES: usize = 8 * 4;
+
+// every proposal type needs a slot in the tables, grow them with ProposalType
+const _: () = assert!((ProposalType::ChildVote as usize) < MAX_PROPOSAL_TYPES);
+
+const CONFIG_TOTAL_BYTES: usize = CONFIG_INITIALIZED_BYTES + // is_initialized 1
+    PUBLIC_KEY_BYTES +                  // squad address 32
+    LEGACY_PROPOSAL_TYPES +             // type_thresholds 32
+    LEGACY_PROPOSAL_TYPES +             // optimistic_vetoes 32
+    AMOUNT_BYTES +                      // optimistic_max_amount 8
+    TIMESTAMP_BYTES +                   // challenge_window 8
+    TIMESTAMP_BYTES +                   // proposal_expiry 8
+    TIMESTAMP_BYTES +                   // execution_window 8
+    EXTENDED_PROPOSAL_TYPES +           // type_thresholds from type 32, 16
+    EXTENDED_PROPOSAL_TYPES +           // optimistic_vetoes from type 32, 16
+    CONFIG_RESERVED_BYTES; // reserved for updates
+
+use crate::state::proposal::ProposalType;
+
+/// Whether proposals of the type can be made optimistic, only withdrawals can,
+/// and only up to the optimistic_max_amount of the squad
+pub fn optimistic_eligible(proposal_type: u8) -> bool {
+    matches!(
+        FromPrimitive::from_u8(proposal_type),
+        Some(ProposalType::WithdrawSol) | Some(ProposalType::WithdrawSpl)
+    )
+}
+
+/// Squad settings that don't fit the squad account, created by the first proposal using them
+#[derive(PartialEq, Debug)]
+pub struct SquadConfig {
+    pub is_initialized: bool,
+    pub squad_address: Pubkey,
+    // threshold per ProposalType, approvals for a Multisig and the support percent
+    // for a Team, 0 to use the squad settings
+    pub type_thresholds: [u8; MAX_PROPOSAL_TYPES],
+    // veto threshold per ProposalType eligible for optimistic mode, vetoes for a Multisig
+    // and the percent of the supply for a Team, 0 if the type isn't eligible
+    pub optimistic_vetoes: [u8; MAX_PROPOSAL_TYPES],
+    // largest execution amount an optimistic proposal can have, 0 disables optimistic mode
+    pub optimistic_max_amount: u64,
+    // seconds optimistic proposals can be vetoed for, 0 disables optimistic mode
+    pub challenge_window: i64,
+    // seconds a Multisig proposal is open for approvals when its creator sets no
+    // close_timestamp, 0 to never expire
+    pub proposal_expiry: i64,
+    // seconds an approved Multisig proposal can be executed for, 0 for no deadline
+    pub execution_window: i64,
+
+    // reserved for future updates
+    pub reserved: [u64; 4],
+}
+
+impl Sealed for SquadConfig {}
+
+impl IsInitialized for SquadConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl SquadConfig {
+    pub fn save_config(&mut self, squad_address: &Pubkey) {
+        self.is_initialized = true;
+        self.squad_address = *squad_address;
+    }
+
+    /// Threshold set for the proposal type, 0 if the squad settings apply
+    pub fn type_threshold(&self, proposal_type: u8) -> u8 {
+        self.type_thresholds
+            .get(proposal_type as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Veto threshold of an optimistic proposal of the type and amount, 0 if it
+    /// has to collect approvals as usual
+    pub fn optimistic_veto(&self, proposal_type: u8, amount: u64) -> u8 {
+        if self.challenge_window <= 0
+            || amount > self.optimistic_max_amount
+            || !optimistic_eligible(proposal_type)
+        {
+            return 0;
+        }
+        self.optimistic_vetoes
+            .get(proposal_type as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+impl Pack for SquadConfig {
+    const LEN: usize = CONFIG_TOTAL_BYTES;
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, CONFIG_TOTAL_BYTES];
+
+        let (
+            is_initialized_dst,
+            squad_address_dst,
+            type_thresholds_dst,
+            optimistic_vetoes_dst,
+            optimistic_max_amount_dst,
+            challenge_window_dst,
+            proposal_expiry_dst,
+            execution_window_dst,
+            type_thresholds_ext_dst,
+            optimistic_vetoes_ext_dst,
+            _reserved,
+        ) = mut_array_refs![
+            dst,
+            CONFIG_INITIALIZED_BYTES, // is_initialized 1
+            PUBLIC_KEY_BYTES,         // squad address 32
+            LEGACY_PROPOSAL_TYPES,    // type_thresholds 32
+            LEGACY_PROPOSAL_TYPES,    // optimistic_vetoes 32
+            AMOUNT_BYTES,             // optimistic_max_amount 8
+            TIMESTAMP_BYTES,          // challenge_window 8
+            TIMESTAMP_BYTES,          // proposal_expiry 8
+            TIMESTAMP_BYTES,          // execution_window 8
+            EXTENDED_PROPOSAL_TYPES,  // type_thresholds from type 32, 16
+            EXTENDED_PROPOSAL_TYPES,  // optimistic_vetoes from type 32, 16
+            CONFIG_RESERVED_BYTES     // reserved
+        ];
+
+        let SquadConfig {
+            is_initialized,
+            squad_address,
+            type_thresholds,
+            optimistic_vetoes,
+            optimistic_max_amount,
+            challenge_window,
+            proposal_expiry,
+            execution_window,
+            reserved: _,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        squad_address_dst.copy_from_slice(squad_address.as_ref());
+        type_thresholds_dst.copy_from_slice(&type_thresholds[..LEGACY_PROPOSAL_TYPES]);
+        type_thresholds_ext_dst.copy_from_slice(&type_thresholds[LEGACY_PROPOSAL_TYPES..]);
+        optimistic_vetoes_dst.copy_from_slice(&optimistic_vetoes[..LEGACY_PROPOSAL_TYPES]);
+        optimistic_vetoes_ext_dst.copy_from_slice(&optimistic_vetoes[LEGACY_PROPOSAL_TYPES..]);
+        *optimistic_max_amount_dst = optimistic_max_amount.to_le_bytes();
+        *challenge_window_dst = challenge_window.to_le_bytes();
+        *proposal_expiry_dst = proposal_expiry.to_le_bytes();
+        *execution_window_dst = execution_window.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, CONFIG_TOTAL_BYTES];
+        let (
+            is_initialized,
+            squad_address,
+            type_thresholds,
+            optimistic_vetoes,
+            optimistic_max_amount,
+            challenge_window,
+            proposal_expiry,
+            execution_window,
+            type_thresholds_ext,
+            optimistic_vetoes_ext,
+            _reserved,
+        ) = array_refs![
+            src,
+            CONFIG_INITIALIZED_BYTES, // is_initialized 1
+            PUBLIC_KEY_BYTES,         // squad address 32
+            LEGACY_PROPOSAL_TYPES,    // type_thresholds 32
+            LEGACY_PROPOSAL_TYPES,    // optimistic_vetoes 32
+            AMOUNT_BYTES,             // optimistic_max_amount 8
+            TIMESTAMP_BYTES,          // challenge_window 8
+            TIMESTAMP_BYTES,          // proposal_expiry 8
+            TIMESTAMP_BYTES,          // execution_window 8
+            EXTENDED_PROPOSAL_TYPES,  // type_thresholds from type 32, 16
+            EXTENDED_PROPOSAL_TYPES,  // optimistic_vetoes from type 32, 16
+            CONFIG_RESERVED_BYTES     // reserved
+        ];
+
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        let mut type_thresholds_all = [0; MAX_PROPOSAL_TYPES];
+        type_thresholds_all[..LEGACY_PROPOSAL_TYPES].copy_from_slice(type_thresholds);
+        type_thresholds_all[LEGACY_PROPOSAL_TYPES..].copy_from_slice(type_thresholds_ext);
+        let mut optimistic_vetoes_all = [0; MAX_PROPOSAL_TYPES];
+        optimistic_vetoes_all[..LEGACY_PROPOSAL_TYPES].copy_from_slice(optimistic_vetoes);
+        optimistic_vetoes_all[LEGACY_PROPOSAL_TYPES..].copy_from_slice(optimistic_vetoes_ext);
+
+        Ok(SquadConfig {
+            is_initialized,
+            squad_address: Pubkey::new(squad_address),
+            type_thresholds: type_thresholds_all,
+            optimistic_vetoes: optimistic_vetoes_all,
+            optimistic_max_amount: u64::from_le_bytes(*optimistic_max_amount),
+            challenge_window: i64::from_le_bytes(*challenge_window),
+            proposal_expiry: i64::from_le_bytes(*proposal_expiry),
+            execution_window: i64::from_le_bytes(*execution_window),
+            reserved: [0; 4],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_type_thresholds() {
+        let config_arr = [0; CONFIG_TOTAL_BYTES];
+        let mut config_info = SquadConfig::unpack_unchecked(&config_arr).unwrap();
+        config_info.save_config(&Pubkey::new_unique());
+        config_info.type_thresholds[5] = 4;
+
+        let mut config_dst = [0; CONFIG_TOTAL_BYTES];
+        SquadConfig::pack(config_info, &mut config_dst).unwrap();
+        let unpacked = SquadConfig::unpack_unchecked(&config_dst).unwrap();
+        assert!(unpacked.is_initialized);
+        assert_eq!(unpacked.type_threshold(5), 4);
+        assert_eq!(unpacked.type_threshold(3), 0);
+        assert_eq!(unpacked.type_threshold(200), 0);
+    }
+
+    #[test]
+    fn config_covers_every_proposal_type() {
+        for proposal_type in MAX_PROPOSAL_TYPES..=u8::MAX as usize {
+            let unlisted: Option<ProposalType> = FromPrimitive::from_usize(proposal_type);
+            assert!(unlisted.is_none());
+        }
+
+        // the types after the first 32 are stored past the old tables
+        let config_arr = [0; CONFIG_TOTAL_BYTES];
+        let mut config_info = SquadConfig::unpack_unchecked(&config_arr).unwrap();
+        config_info.type_thresholds[5] = 2;
+        config_info.type_thresholds[ProposalType::UpgradeProgram as usize] = 4;
+        config_info.type_thresholds[ProposalType::CreateTokenAccount as usize] = 3;
+        config_info.optimistic_vetoes[ProposalType::WithdrawSol as usize] = 1;
+
+        let mut config_dst = [0; CONFIG_TOTAL_BYTES];
+        SquadConfig::pack(config_info, &mut config_dst).unwrap();
+        assert_eq!(config_dst[PUBLIC_KEY_BYTES + 1 + 5], 2);
+        let unpacked = SquadConfig::unpack_unchecked(&config_dst).unwrap();
+        assert_eq!(unpacked.type_threshold(5), 2);
+        assert_eq!(
+            unpacked.type_threshold(ProposalType::UpgradeProgram as u8),
+            4
+        );
+        assert_eq!(
+            unpacked.type_threshold(ProposalType::CreateTokenAccount as u8),
+            3
+        );
+        assert_eq!(
+            unpacked.optimistic_vetoes[ProposalType::WithdrawSol as usize],
+            1
+        );
+    }
+
+    #[test]
+    fn config_optimistic_veto() {
+        let config_arr = [0; CONFIG_TOTAL_BYTES];
+        let mut config_info = SquadConfig::unpack_unchecked(&config_arr).unwrap();
+        config_info.save_config(&Pubkey::new_unique());
+        config_info.optimistic_vetoes[3] = 2;
+        config_info.optimistic_max_amount = 1_000;
+
+        // no challenge window, no optimistic proposals
+        assert_eq!(config_info.optimistic_veto(3, 10), 0);
+        config_info.challenge_window = 86_400;
+
+        let mut config_dst = [0; CONFIG_TOTAL_BYTES];
+        SquadConfig::pack(config_info, &mut config_dst).unwrap();
+        let unpacked = SquadConfig::unpack_unchecked(&config_dst).unwrap();
+        assert_eq!(unpacked.challenge_window, 86_400);
+        assert_eq!(unpacked.optimistic_veto(3, 1_000), 2);
+        assert_eq!(unpacked.optimistic_veto(3, 1_001), 0);
+        assert_eq!(unpacked.optimistic_veto(4, 10), 0);
+    }
+
+    #[test]
+    fn config_optimistic_allowlist() {
+        assert!(optimistic_eligible(ProposalType::WithdrawSol as u8));
+        assert!(optimistic_eligible(ProposalType::WithdrawSpl as u8));
+        assert!(!optimistic_eligible(ProposalType::TypeThreshold as u8));
+        assert!(!optimistic_eligible(ProposalType::OptimisticType as u8));
+        assert!(!optimistic_eligible(ProposalType::AddMember as u8));
+        assert!(!optimistic_eligible(ProposalType::UpgradeProgram as u8));
+
+        let config_arr = [0; CONFIG_TOTAL_BYTES];
+        let mut config_info = SquadConfig::unpack_unchecked(&config_arr).unwrap();
+        config_info.challenge_window = 86_400;
+        config_info.optimistic_vetoes[ProposalType::WithdrawSol as usize] = 2;
+        config_info.optimistic_vetoes[ProposalType::AddMember as usize] = 2;
+        // without an amount cap nothing is optimistic
+        assert_eq!(
+            config_info.optimistic_veto(ProposalType::WithdrawSol as u8, 10),
+            0
+        );
+        config_info.optimistic_max_amount = 1_000;
+        assert_eq!(
+            config_info.optimistic_veto(ProposalType::WithdrawSol as u8, 10),
+            2
+        );
+        // a type set before the allowlist existed stays non optimistic
+        assert_eq!(
+            config_info.optimistic_veto(ProposalType::AddMember as u8, 0),
+            0
+        );
+    }
+}
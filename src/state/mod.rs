@@ -1,3 +1,8 @@
+pub mod address_book;
+pub mod checkpoint;
+pub mod config;
 pub mod proposal;
 pub mod squad;
+pub mod stream;
+pub mod vesting;
 pub mod vote;
@@ -1,12 +1,14 @@
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
+    hash::hashv,
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::Pubkey,
 };
 
 use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
 use std::convert::TryInto;
 
 use crate::UnixTimestamp;
@@ -27,11 +29,14 @@ const PROPOSAL_HAS_VOTED_NUM_BYTES: usize = 1;
 const PROPOSAL_HAS_VOTED_BYTES: usize = (150 * PUBLIC_KEY_BYTES) + 4;
 const PROPOSAL_OPTIONS_BYTES: usize = PROPOSAL_VOTE_OPTIONS_NUM * 8;
 const PROPOSAL_OPTIONS_LABELS_BYTES: usize = PROPOSAL_VOTE_OPTIONS_NUM * 44;
-const PROPOSAL_RESERVED_BYTES: usize = 8 * 16;
+const PROPOSAL_BUFFER_HASH_BYTES: usize = 32;
+const PROPOSAL_TOKEN_SYMBOL_BYTES: usize = 10;
+const PROPOSAL_RESERVED_BYTES: usize = 2;
 const SUPPLY_AT_EXECUTE_BYTES: usize = 8;
 const MEMBERS_AT_EXECUTE_BYTES: usize = 1;
 const THRESHOLD_AT_EXECUTE_BYTES: usize = 1;
 const PROPOSAL_INDEX_BYTES: usize = 4;
+const MEMBERSHIP_VERSION_BYTES: usize = 4;
 
 #[derive(FromPrimitive)]
 pub enum ProposalType {
@@ -44,6 +49,104 @@ pub enum ProposalType {
     RemoveMember = 6,
     MintMemberToken = 7,
     Swap = 8,
+    VestMemberToken = 9,
+    RevokeVesting = 10,
+    CreateStream = 11,
+    CancelStream = 12,
+    VaultPolicy = 13,
+    SetMemberRole = 14,
+    SupportBps = 15,
+    QuorumBps = 16,
+    SetCoreMember = 17,
+    CoreWithdrawLimit = 18,
+    TypeThreshold = 19,
+    AddressBookEntry = 20,
+    UnlistedPolicy = 21,
+    OptimisticType = 22,
+    OptimisticWindow = 23,
+    ProposalExpiry = 24,
+    KeeperPolicy = 25,
+    CreateStake = 26,
+    DelegateStake = 27,
+    DeactivateStake = 28,
+    WithdrawStake = 29,
+    SplitStake = 30,
+    MergeStake = 31,
+    UpgradeProgram = 32,
+    SetUpgradeAuthority = 33,
+    MintTo = 34,
+    BurnTokens = 35,
+    SetTokenAuthority = 36,
+    FreezeAccount = 37,
+    ThawAccount = 38,
+    CreateMint = 39,
+    CloseTokenAccount = 40,
+    ApproveDelegate = 41,
+    RevokeDelegate = 42,
+    SyncNative = 43,
+    CreateTokenAccount = 44,
+    CreateChildSquad = 45,
+    ChildVote = 46,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, FromPrimitive, BorshSerialize, BorshDeserialize)]
+pub enum ProposalStatus {
+    // created, voting hasn't started yet
+    Draft = 1,
+    // open for votes
+    Active = 2,
+    // reached its threshold, waiting for execution
+    Approved = 3,
+    Rejected = 4,
+    Executed = 5,
+    // withdrawn by its creator before anyone voted
+    Cancelled = 6,
+    // closed without approval, or not executed in time
+    Expired = 7,
+    // an execution found the vault short, it can be executed again
+    ExecutionFailed = 8,
+}
+
+impl ProposalStatus {
+    pub fn is_approved(self) -> bool {
+        matches!(
+            self,
+            ProposalStatus::Approved | ProposalStatus::Executed | ProposalStatus::ExecutionFailed
+        )
+    }
+
+    pub fn is_closed(self) -> bool {
+        matches!(
+            self,
+            ProposalStatus::Rejected
+                | ProposalStatus::Executed
+                | ProposalStatus::Cancelled
+                | ProposalStatus::Expired
+        )
+    }
+
+    /// Transitions allowed by the proposal lifecycle
+    pub fn can_become(self, status: ProposalStatus) -> bool {
+        use ProposalStatus::*;
+        matches!(
+            (self, status),
+            (Draft, Active)
+                | (Draft, Cancelled)
+                | (Active, Approved)
+                | (Active, Rejected)
+                | (Active, Cancelled)
+                | (Active, Expired)
+                // later Team votes can still reject an approved proposal
+                | (Approved, Rejected)
+                | (Approved, Executed)
+                | (Approved, ExecutionFailed)
+                | (Approved, Expired)
+                | (ExecutionFailed, Rejected)
+                | (ExecutionFailed, Executed)
+                | (ExecutionFailed, ExecutionFailed)
+                | (ExecutionFailed, Expired)
+        )
+    }
 }
 
 // PROPOSAL STRUCT
@@ -69,13 +172,30 @@ const PROPOSAL_TOTAL_BYTES: usize = PROPOSAL_SETTING_BYTES +                // i
     SUPPLY_AT_EXECUTE_BYTES +               // supply_at_execute 8
     MEMBERS_AT_EXECUTE_BYTES +              // members_at_execute 1
     THRESHOLD_AT_EXECUTE_BYTES +              // members_at_execute 1
-    PROPOSAL_SETTING_BYTES +                // executed 1
-    PROPOSAL_SETTING_BYTES +                // execute_ready 1
+    PROPOSAL_SETTING_BYTES +                // executed 1, legacy status
+    PROPOSAL_SETTING_BYTES +                // execute_ready 1, legacy status
     TIMESTAMP_BYTES +                       // execution_date bytes 8
     PROPOSAL_SETTING_BYTES +                // instruction_index 1
     PROPOSAL_SETTING_BYTES +                // multiple_choice 1
     PUBLIC_KEY_BYTES +                      // executed_by 32
     PROPOSAL_INDEX_BYTES +                  // the proposal index
+    TIMESTAMP_BYTES +                       // vesting_cliff 8
+    PROPOSAL_SETTING_BYTES +                // vault_index 1
+    MEMBERSHIP_VERSION_BYTES +              // membership_version 4
+    MEMBERS_AT_EXECUTE_BYTES +              // members_at_creation 1
+    MEMBERS_AT_EXECUTE_BYTES +              // core_votes 1
+    THRESHOLD_AT_EXECUTE_BYTES +            // type_threshold 1
+    PROPOSAL_SETTING_BYTES +                // destination_allowlisted 1
+    THRESHOLD_AT_EXECUTE_BYTES +            // unlisted_threshold 1
+    TIMESTAMP_BYTES +                       // execute_after 8
+    THRESHOLD_AT_EXECUTE_BYTES +            // veto_threshold 1
+    TIMESTAMP_BYTES +                       // execution_window 8
+    TIMESTAMP_BYTES +                       // execute_before 8
+    PROPOSAL_SETTING_BYTES +                // status 1
+    PROPOSAL_BUFFER_HASH_BYTES +            // buffer_hash 32
+    PUBLIC_KEY_BYTES +                      // spill_address 32
+    PROPOSAL_TOKEN_SYMBOL_BYTES +           // token_symbol 10
+    TIMESTAMP_BYTES +                       // unlisted_until 8
     PROPOSAL_RESERVED_BYTES; // reserved for updates
 
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
@@ -90,6 +210,14 @@ pub struct Proposal {
     // 6 - add member
     // 7 - remove member
     // 8 - mint more tokens to a member
+    // 9 - grant vesting member tokens
+    // 10 - revoke the unvested part of a grant
+    // 11 - stream from a vault
+    // 12 - cancel a stream
+    // 13 - set a vault threshold policy
+    // 14 - set the role bitmask of a member
+    // 15 - change support in basis points
+    // 16 - change quorum in basis points
     pub proposal_type: u8,
     pub execution_amount: u64,
     pub execution_amount_out: u64,
@@ -113,8 +241,8 @@ pub struct Proposal {
     pub supply_at_execute: u64,
     pub members_at_execute: u8,
     pub threshold_at_execute: u8,
-    pub executed: bool,
-    pub execute_ready: bool,
+    // where the proposal is in its lifecycle, changed through set_status
+    pub status: ProposalStatus,
     pub execution_date: UnixTimestamp,
 
     pub instruction_index: u8,
@@ -122,8 +250,43 @@ pub struct Proposal {
 
     pub executed_by: Pubkey,
     pub proposal_index: u32,
+    // seconds after the grant start before anything vests (VestMemberToken)
+    pub vesting_cliff: i64,
+    // the vault the proposal applies to, 0 is the original sol account
+    pub vault_index: u8,
+    // squad membership_version when the proposal was created, later members can't vote on it
+    pub membership_version: u32,
+    // members counted for the quorum when the proposal was created, 0 for older proposals
+    pub members_at_creation: u8,
+    // core council members who voted for the first option
+    pub core_votes: u8,
+    // threshold the squad config set for this proposal type at creation, 0 if none
+    pub type_threshold: u8,
+    // whether the withdrawal destination was in the squad address book at creation
+    pub destination_allowlisted: bool,
+    // threshold the address book set for an unlisted destination at creation, 0 if none
+    pub unlisted_threshold: u8,
+    // the proposal can't be executed before this time, 0 if no time-lock
+    pub execute_after: UnixTimestamp,
+    // vetoes that reject an optimistic proposal, which passes once its voting closes
+    // without them, 0 for a proposal collecting approvals
+    pub veto_threshold: u8,
+    // seconds the proposal can be executed for once approved, 0 for no deadline
+    pub execution_window: i64,
+    // the approved proposal expires after this time, 0 if it has no deadline yet
+    pub execute_before: UnixTimestamp,
+    // hash of the buffer account data at creation (UpgradeProgram), the upgrade
+    // fails if the buffer changed after members voted on it
+    pub buffer_hash: [u8; 32],
+    // receives the buffer lamports once the program is upgraded (UpgradeProgram)
+    pub spill_address: Pubkey,
+    // metadata symbol of the mint created (CreateMint), empty for no metadata account
+    pub token_symbol: String,
+    // end of the address book delay of an unlisted destination, 0 if none, unlike
+    // execute_after it doesn't let keepers execute the proposal
+    pub unlisted_until: UnixTimestamp,
     // reserved for future updates
-    pub reserved: [u64; 16],
+    pub reserved: [u8; 2],
 }
 
 impl Sealed for Proposal {}
@@ -135,6 +298,84 @@ impl IsInitialized for Proposal {
 }
 
 impl Proposal {
+    /// Whether executing the proposal moves funds out of the vault at vault_index
+    pub fn is_vault_proposal(&self) -> bool {
+        matches!(
+            FromPrimitive::from_u8(self.proposal_type),
+            Some(ProposalType::WithdrawSol)
+                | Some(ProposalType::WithdrawSpl)
+                | Some(ProposalType::Swap)
+                | Some(ProposalType::CreateStream)
+        )
+    }
+
+    /// Whether the proposal reached its threshold, executed or not
+    pub fn is_approved(&self) -> bool {
+        self.status.is_approved()
+    }
+
+    /// Whether the proposal is over, nothing can vote on or execute it anymore
+    pub fn is_closed(&self) -> bool {
+        self.status.is_closed()
+    }
+
+    /// Whether the proposal has to wait for its execute_after timestamp or
+    /// the address book delay
+    pub fn is_time_locked(&self, now: UnixTimestamp) -> bool {
+        now < self.execute_after || now < self.unlisted_until
+    }
+
+    /// Moves the proposal to `status`, every lifecycle change goes through here
+    pub fn set_status(&mut self, status: ProposalStatus) -> Result<(), ProgramError> {
+        if !self.status.can_become(status) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        self.status = status;
+        Ok(())
+    }
+
+    /// Marks the proposal approved, its execution window starts now
+    /// or once its time-lock ends
+    pub fn approve(&mut self, now: UnixTimestamp) -> Result<(), ProgramError> {
+        self.set_status(ProposalStatus::Approved)?;
+        if self.execution_window > 0 {
+            self.execute_before = now
+                .max(self.execute_after)
+                .max(self.unlisted_until)
+                .saturating_add(self.execution_window);
+        }
+        Ok(())
+    }
+
+    /// Makes the proposal optimistic, it can be vetoed for the challenge window from
+    /// now or from its start if later, a backdated start can't shorten the window
+    pub fn make_optimistic(
+        &mut self,
+        veto_threshold: u8,
+        challenge_window: i64,
+        now: UnixTimestamp,
+    ) {
+        self.veto_threshold = veto_threshold;
+        self.close_timestamp = now
+            .max(self.start_timestamp)
+            .saturating_add(challenge_window);
+    }
+
+    /// Message members sign off-chain to approve the proposal, it commits to the
+    /// program and proposal addresses so a signature can't be replayed elsewhere
+    pub fn approval_digest(&self, proposal_address: &Pubkey, program_id: &Pubkey) -> [u8; 32] {
+        hashv(&[
+            b"squads-approval",
+            program_id.as_ref(),
+            proposal_address.as_ref(),
+            &[self.proposal_type],
+            &self.execution_amount.to_le_bytes(),
+            self.execution_destination.as_ref(),
+            &self.proposal_index.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
+
     pub fn save_text(
         &mut self,
         proposal_type: u8,
@@ -162,9 +403,8 @@ impl Proposal {
         self.start_timestamp = start_timestamp;
         self.close_timestamp = close_timestamp;
         self.created_timestamp = created_timestamp;
-        self.executed = false;
+        self.status = ProposalStatus::Active;
         self.execution_amount = 0;
-        self.execute_ready = false;
         self.execution_date = 0 as i64;
         self.proposal_index = proposal_index;
     }
@@ -198,8 +438,7 @@ impl Proposal {
         self.close_timestamp = close_timestamp;
         self.execution_amount = amount;
         self.created_timestamp = created_timestamp;
-        self.executed = false;
-        self.execute_ready = false;
+        self.status = ProposalStatus::Active;
         self.execution_date = 0 as i64;
         self.proposal_index = proposal_index;
     }
@@ -237,8 +476,7 @@ impl Proposal {
         self.close_timestamp = close_timestamp;
         self.execution_amount = amount;
         self.created_timestamp = created_timestamp;
-        self.executed = false;
-        self.execute_ready = false;
+        self.status = ProposalStatus::Active;
         self.execution_date = 0 as i64;
         self.proposal_index = proposal_index;
     }
@@ -274,13 +512,54 @@ impl Proposal {
         self.start_timestamp = start_timestamp;
         self.close_timestamp = close_timestamp;
         self.created_timestamp = created_timestamp;
-        self.executed = false;
+        self.status = ProposalStatus::Active;
         self.execution_amount = amount;
-        self.execute_ready = false;
         self.execution_date = 0 as i64;
         self.proposal_index = proposal_index;
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_vesting(
+        &mut self,
+        proposal_type: u8,
+        title: String,
+        description: String,
+        link: String,
+        member: &Pubkey,
+        initializer: &Pubkey,
+        votes_num: u8,
+        squad_account: &Pubkey,
+        vote_labels: Vec<String>,
+        start_timestamp: i64,
+        close_timestamp: i64,
+        created_timestamp: i64,
+        amount: u64,
+        duration: u64,
+        cliff: i64,
+        proposal_index: u32,
+    ) {
+        self.is_initialized = true;
+        self.proposal_type = proposal_type;
+        self.title = title;
+        self.description = description;
+        self.link = link;
+        self.execution_source = *squad_account;
+        self.execution_destination = *member;
+        self.creator = *initializer;
+        self.votes_num = votes_num;
+        self.squad_address = *squad_account;
+        self.votes_labels = vote_labels;
+        self.start_timestamp = start_timestamp;
+        self.close_timestamp = close_timestamp;
+        self.created_timestamp = created_timestamp;
+        self.status = ProposalStatus::Active;
+        self.execution_amount = amount;
+        self.execution_amount_out = duration;
+        self.vesting_cliff = cliff;
+        self.execution_date = 0;
+        self.proposal_index = proposal_index;
+    }
+
     pub fn save_swap(
         &mut self,
         proposal_type: u8,
@@ -316,8 +595,7 @@ impl Proposal {
         self.execution_amount = amount;
         self.execution_amount_out = minimum_out;
         self.created_timestamp = created_timestamp;
-        self.executed = false;
-        self.execute_ready = false;
+        self.status = ProposalStatus::Active;
         self.execution_date = 0 as i64;
         self.proposal_index = proposal_index;
     }
@@ -361,6 +639,23 @@ impl Pack for Proposal {
             multiple_choice_dst,
             executed_by_dst,
             proposal_index_dst,
+            vesting_cliff_dst,
+            vault_index_dst,
+            membership_version_dst,
+            members_at_creation_dst,
+            core_votes_dst,
+            type_threshold_dst,
+            destination_allowlisted_dst,
+            unlisted_threshold_dst,
+            execute_after_dst,
+            veto_threshold_dst,
+            execution_window_dst,
+            execute_before_dst,
+            status_dst,
+            buffer_hash_dst,
+            spill_address_dst,
+            token_symbol_dst,
+            unlisted_until_dst,
             _reserved,
         ) = mut_array_refs![
             dst,
@@ -393,6 +688,23 @@ impl Pack for Proposal {
             PROPOSAL_SETTING_BYTES,               // multiple_choice 1
             PUBLIC_KEY_BYTES,                     // executed_by 32
             PROPOSAL_INDEX_BYTES,                 // proposal index
+            TIMESTAMP_BYTES,                      // vesting_cliff 8
+            PROPOSAL_SETTING_BYTES,               // vault_index 1
+            MEMBERSHIP_VERSION_BYTES,             // membership_version 4
+            MEMBERS_AT_EXECUTE_BYTES,             // members_at_creation 1
+            MEMBERS_AT_EXECUTE_BYTES,             // core_votes 1
+            THRESHOLD_AT_EXECUTE_BYTES,           // type_threshold 1
+            PROPOSAL_SETTING_BYTES,               // destination_allowlisted 1
+            THRESHOLD_AT_EXECUTE_BYTES,           // unlisted_threshold 1
+            TIMESTAMP_BYTES,                      // execute_after 8
+            THRESHOLD_AT_EXECUTE_BYTES,           // veto_threshold 1
+            TIMESTAMP_BYTES,                      // execution_window 8
+            TIMESTAMP_BYTES,                      // execute_before 8
+            PROPOSAL_SETTING_BYTES,               // status 1
+            PROPOSAL_BUFFER_HASH_BYTES,           // buffer_hash 32
+            PUBLIC_KEY_BYTES,                     // spill_address 32
+            PROPOSAL_TOKEN_SYMBOL_BYTES,          // token_symbol 10
+            TIMESTAMP_BYTES,                      // unlisted_until 8
             PROPOSAL_RESERVED_BYTES
         ];
 
@@ -420,13 +732,28 @@ impl Pack for Proposal {
             supply_at_execute,
             members_at_execute,
             threshold_at_execute,
-            executed,
-            execute_ready,
+            status,
             execution_date,
             instruction_index,
             multiple_choice,
             executed_by,
             proposal_index,
+            vesting_cliff,
+            vault_index,
+            membership_version,
+            members_at_creation,
+            core_votes,
+            type_threshold,
+            destination_allowlisted,
+            unlisted_threshold,
+            execute_after,
+            veto_threshold,
+            execution_window,
+            execute_before,
+            buffer_hash,
+            spill_address,
+            token_symbol,
+            unlisted_until,
             reserved: _,
         } = self;
 
@@ -480,8 +807,9 @@ impl Pack for Proposal {
         *members_at_execute_dst = members_at_execute.to_le_bytes();
         *threshold_at_execute_dst = threshold_at_execute.to_le_bytes();
 
-        executed_dst[0] = *executed as u8;
-        execute_ready_dst[0] = *execute_ready as u8;
+        // the legacy booleans are still written for older clients
+        executed_dst[0] = status.is_closed() as u8;
+        execute_ready_dst[0] = status.is_approved() as u8;
         *execution_date_dst = execution_date.to_le_bytes();
 
         *instruction_index_dst = instruction_index.to_le_bytes();
@@ -513,6 +841,24 @@ impl Pack for Proposal {
         votes_labels_dst[..].copy_from_slice(votes_labels_ser.as_slice());
 
         *proposal_index_dst = proposal_index.to_le_bytes();
+        *vesting_cliff_dst = vesting_cliff.to_le_bytes();
+        *vault_index_dst = vault_index.to_le_bytes();
+        *membership_version_dst = membership_version.to_le_bytes();
+        *members_at_creation_dst = members_at_creation.to_le_bytes();
+        *core_votes_dst = core_votes.to_le_bytes();
+        *type_threshold_dst = type_threshold.to_le_bytes();
+        destination_allowlisted_dst[0] = *destination_allowlisted as u8;
+        *unlisted_threshold_dst = unlisted_threshold.to_le_bytes();
+        *execute_after_dst = execute_after.to_le_bytes();
+        *veto_threshold_dst = veto_threshold.to_le_bytes();
+        *execution_window_dst = execution_window.to_le_bytes();
+        *execute_before_dst = execute_before.to_le_bytes();
+        status_dst[0] = *status as u8;
+        *buffer_hash_dst = *buffer_hash;
+        spill_address_dst.copy_from_slice(spill_address.as_ref());
+        let token_symbol_ser = token_symbol.as_bytes();
+        token_symbol_dst[..token_symbol_ser.len()].copy_from_slice(token_symbol_ser);
+        *unlisted_until_dst = unlisted_until.to_le_bytes();
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
@@ -548,6 +894,23 @@ impl Pack for Proposal {
             multiple_choice,
             executed_by,
             proposal_index,
+            vesting_cliff,
+            vault_index,
+            membership_version,
+            members_at_creation,
+            core_votes,
+            type_threshold,
+            destination_allowlisted,
+            unlisted_threshold,
+            execute_after,
+            veto_threshold,
+            execution_window,
+            execute_before,
+            status,
+            buffer_hash,
+            spill_address,
+            token_symbol_src,
+            unlisted_until,
             _reserved,
         ) = array_refs![
             src,
@@ -567,19 +930,36 @@ impl Pack for Proposal {
             PROPOSAL_HAS_VOTED_BYTES,     // bytes for Vec Pubkey
             PROPOSAL_OPTIONS_BYTES,       // bytes for BTreeMap buckets
             PROPOSAL_OPTIONS_LABELS_BYTES,
-            TIMESTAMP_BYTES,            // start proposal
-            TIMESTAMP_BYTES,            // close proposal
-            TIMESTAMP_BYTES,            // created on
-            SUPPLY_AT_EXECUTE_BYTES,    // supply_at_execute 8
-            MEMBERS_AT_EXECUTE_BYTES,   // members_at_execute 1
-            THRESHOLD_AT_EXECUTE_BYTES, // threshold_at_execute 1
-            PROPOSAL_SETTING_BYTES,     // executed 1
-            PROPOSAL_SETTING_BYTES,     // execute_ready 1
-            TIMESTAMP_BYTES,            // execution_date
-            PROPOSAL_SETTING_BYTES,     // instruction_index 1
-            PROPOSAL_SETTING_BYTES,     // multiple_choice 1
-            PUBLIC_KEY_BYTES,           // executed_by 32
-            PROPOSAL_INDEX_BYTES,       // proposal index
+            TIMESTAMP_BYTES,             // start proposal
+            TIMESTAMP_BYTES,             // close proposal
+            TIMESTAMP_BYTES,             // created on
+            SUPPLY_AT_EXECUTE_BYTES,     // supply_at_execute 8
+            MEMBERS_AT_EXECUTE_BYTES,    // members_at_execute 1
+            THRESHOLD_AT_EXECUTE_BYTES,  // threshold_at_execute 1
+            PROPOSAL_SETTING_BYTES,      // executed 1
+            PROPOSAL_SETTING_BYTES,      // execute_ready 1
+            TIMESTAMP_BYTES,             // execution_date
+            PROPOSAL_SETTING_BYTES,      // instruction_index 1
+            PROPOSAL_SETTING_BYTES,      // multiple_choice 1
+            PUBLIC_KEY_BYTES,            // executed_by 32
+            PROPOSAL_INDEX_BYTES,        // proposal index
+            TIMESTAMP_BYTES,             // vesting_cliff 8
+            PROPOSAL_SETTING_BYTES,      // vault_index 1
+            MEMBERSHIP_VERSION_BYTES,    // membership_version 4
+            MEMBERS_AT_EXECUTE_BYTES,    // members_at_creation 1
+            MEMBERS_AT_EXECUTE_BYTES,    // core_votes 1
+            THRESHOLD_AT_EXECUTE_BYTES,  // type_threshold 1
+            PROPOSAL_SETTING_BYTES,      // destination_allowlisted 1
+            THRESHOLD_AT_EXECUTE_BYTES,  // unlisted_threshold 1
+            TIMESTAMP_BYTES,             // execute_after 8
+            THRESHOLD_AT_EXECUTE_BYTES,  // veto_threshold 1
+            TIMESTAMP_BYTES,             // execution_window 8
+            TIMESTAMP_BYTES,             // execute_before 8
+            PROPOSAL_SETTING_BYTES,      // status 1
+            PROPOSAL_BUFFER_HASH_BYTES,  // buffer_hash 32
+            PUBLIC_KEY_BYTES,            // spill_address 32
+            PROPOSAL_TOKEN_SYMBOL_BYTES, // token_symbol 10
+            TIMESTAMP_BYTES,             // unlisted_until 8
             PROPOSAL_RESERVED_BYTES
         ];
 
@@ -601,12 +981,30 @@ impl Pack for Proposal {
             _ => return Err(ProgramError::InvalidAccountData),
         };
 
+        // accounts written before the status byte only have the booleans, where
+        // executed also meant rejected when nobody executed the proposal
+        let status = match status[0] {
+            0 if executed && Pubkey::new(executed_by) != Pubkey::default() => {
+                ProposalStatus::Executed
+            }
+            0 if executed => ProposalStatus::Rejected,
+            0 if execute_ready => ProposalStatus::Approved,
+            0 => ProposalStatus::Active,
+            status => FromPrimitive::from_u8(status).ok_or(ProgramError::InvalidAccountData)?,
+        };
+
         let multiple_choice = match multiple_choice {
             [0] => false,
             [1] => true,
             _ => return Err(ProgramError::InvalidAccountData),
         };
 
+        let destination_allowlisted = match destination_allowlisted {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
         let title_deser = String::from_utf8(title_src.to_vec()).unwrap();
         let description_deser = String::from_utf8(description_src.to_vec()).unwrap();
         let link_deser = String::from_utf8(link_src.to_vec()).unwrap();
@@ -659,14 +1057,29 @@ impl Pack for Proposal {
             supply_at_execute: u64::from_le_bytes(*supply_at_execute),
             members_at_execute: u8::from_le_bytes(*members_at_execute),
             threshold_at_execute: u8::from_le_bytes(*threshold_at_execute),
-            executed,
-            execute_ready,
+            status,
             execution_date: i64::from_le_bytes(*execution_date),
             instruction_index: u8::from_le_bytes(*instruction_index),
             multiple_choice,
             executed_by: Pubkey::new(executed_by),
             proposal_index: u32::from_le_bytes(*proposal_index),
-            reserved: [0; 16],
+            vesting_cliff: i64::from_le_bytes(*vesting_cliff),
+            vault_index: u8::from_le_bytes(*vault_index),
+            membership_version: u32::from_le_bytes(*membership_version),
+            members_at_creation: u8::from_le_bytes(*members_at_creation),
+            core_votes: u8::from_le_bytes(*core_votes),
+            type_threshold: u8::from_le_bytes(*type_threshold),
+            destination_allowlisted,
+            unlisted_threshold: u8::from_le_bytes(*unlisted_threshold),
+            execute_after: i64::from_le_bytes(*execute_after),
+            veto_threshold: u8::from_le_bytes(*veto_threshold),
+            execution_window: i64::from_le_bytes(*execution_window),
+            execute_before: i64::from_le_bytes(*execute_before),
+            buffer_hash: *buffer_hash,
+            spill_address: Pubkey::new(spill_address),
+            token_symbol: String::from_utf8(token_symbol_src.to_vec()).unwrap(),
+            unlisted_until: i64::from_le_bytes(*unlisted_until),
+            reserved: [0; 2],
         })
     }
 }
@@ -726,14 +1139,29 @@ mod tests {
             supply_at_execute: 0,
             members_at_execute: 0,
             threshold_at_execute: 0,
-            executed: false,
-            execute_ready: false,
+            status: ProposalStatus::Active,
             execution_date: chrono::offset::Utc::now().timestamp(),
             instruction_index: 0,
             multiple_choice: false,
             executed_by: Pubkey::new_unique(),
             proposal_index: 0,
-            reserved: [0; 16],
+            vesting_cliff: 0,
+            vault_index: 0,
+            membership_version: 0,
+            members_at_creation: 0,
+            core_votes: 0,
+            type_threshold: 0,
+            destination_allowlisted: false,
+            unlisted_threshold: 0,
+            execute_after: 0,
+            veto_threshold: 0,
+            execution_window: 0,
+            execute_before: 0,
+            buffer_hash: [7; 32],
+            spill_address: Pubkey::new_unique(),
+            token_symbol: String::from("SQDS"),
+            unlisted_until: 0,
+            reserved: [0; 2],
         };
 
         Proposal::pack(test_proposal, &mut test_dst);
@@ -751,4 +1179,87 @@ mod tests {
         println!("proposal packed len: {:?}", Proposal::get_packed_len());
         println!("total proposal size: {:?}", PROPOSAL_TOTAL_BYTES);
     }
+
+    #[test]
+    fn proposal_approval_digest() {
+        let proposal_arr = vec![0; PROPOSAL_TOTAL_BYTES];
+        let mut proposal_info = Proposal::unpack_unchecked(&proposal_arr).unwrap();
+        proposal_info.execution_amount = 10;
+        let program_id = Pubkey::new_unique();
+        let proposal_address = Pubkey::new_unique();
+        let digest = proposal_info.approval_digest(&proposal_address, &program_id);
+
+        // another proposal, amount or program gives another digest
+        assert_ne!(
+            digest,
+            proposal_info.approval_digest(&Pubkey::new_unique(), &program_id)
+        );
+        assert_ne!(
+            digest,
+            proposal_info.approval_digest(&proposal_address, &Pubkey::new_unique())
+        );
+        proposal_info.execution_amount = 11;
+        assert_ne!(
+            digest,
+            proposal_info.approval_digest(&proposal_address, &program_id)
+        );
+    }
+
+    #[test]
+    fn proposal_status_migration() {
+        let status_at = PROPOSAL_TOTAL_BYTES
+            - PROPOSAL_RESERVED_BYTES
+            - TIMESTAMP_BYTES
+            - PROPOSAL_TOKEN_SYMBOL_BYTES
+            - PUBLIC_KEY_BYTES
+            - PROPOSAL_BUFFER_HASH_BYTES
+            - 1;
+        let mut proposal_arr = vec![0; PROPOSAL_TOTAL_BYTES];
+        let mut proposal_info = Proposal::unpack_unchecked(&proposal_arr).unwrap();
+        assert_eq!(proposal_info.status, ProposalStatus::Active);
+
+        // an account written before the status byte is read from its booleans
+        proposal_info.approve(0).unwrap();
+        Proposal::pack(proposal_info, &mut proposal_arr).unwrap();
+        proposal_arr[status_at] = 0;
+        let mut migrated = Proposal::unpack_unchecked(&proposal_arr).unwrap();
+        assert_eq!(migrated.status, ProposalStatus::Approved);
+
+        migrated.set_status(ProposalStatus::Executed).unwrap();
+        migrated.executed_by = Pubkey::new_unique();
+        Proposal::pack(migrated, &mut proposal_arr).unwrap();
+        proposal_arr[status_at] = 0;
+        let mut migrated = Proposal::unpack_unchecked(&proposal_arr).unwrap();
+        assert_eq!(migrated.status, ProposalStatus::Executed);
+
+        // executed without an executor meant rejected
+        migrated.executed_by = Pubkey::default();
+        Proposal::pack(migrated, &mut proposal_arr).unwrap();
+        proposal_arr[status_at] = 0;
+        let migrated = Proposal::unpack_unchecked(&proposal_arr).unwrap();
+        assert_eq!(migrated.status, ProposalStatus::Rejected);
+
+        proposal_arr[status_at] = 9;
+        assert!(Proposal::unpack_unchecked(&proposal_arr).is_err());
+    }
+
+    #[test]
+    fn proposal_status_transitions() {
+        let proposal_arr = vec![0; PROPOSAL_TOTAL_BYTES];
+        let mut proposal_info = Proposal::unpack_unchecked(&proposal_arr).unwrap();
+        proposal_info.status = ProposalStatus::Draft;
+        assert!(proposal_info.set_status(ProposalStatus::Approved).is_err());
+        proposal_info.set_status(ProposalStatus::Active).unwrap();
+        proposal_info.approve(0).unwrap();
+        assert!(proposal_info.approve(0).is_err());
+        assert!(proposal_info.set_status(ProposalStatus::Cancelled).is_err());
+
+        // a failed execution can be retried
+        proposal_info
+            .set_status(ProposalStatus::ExecutionFailed)
+            .unwrap();
+        proposal_info.set_status(ProposalStatus::Executed).unwrap();
+        assert!(proposal_info.is_approved() && proposal_info.is_closed());
+        assert!(proposal_info.set_status(ProposalStatus::Executed).is_err());
+    }
 }
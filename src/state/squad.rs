@@ -11,7 +11,10 @@ use solana_program::{
 use std::collections::BTreeMap;
 
 use crate::count_from_le;
+use crate::error::SquadError;
+use crate::state::proposal::{Proposal, ProposalType};
 use crate::transform_u32_to_array_of_u8;
+use num_traits::FromPrimitive;
 
 #[derive(FromPrimitive)]
 pub enum AllocationType {
@@ -20,19 +23,44 @@ pub enum AllocationType {
 }
 
 // Squad Bytes
+// 150 legacy members fit the member bytes, 139 once the members carry their role and join version
 const SQUAD_MAX_MEMBERS: usize = 150;
+const SQUAD_MEMBERS_BYTES: usize = ((PUBLIC_KEY_BYTES * 2) * SQUAD_MAX_MEMBERS) + 4;
+// borsh bytes of a Member: key, equity_token_account, role and joined_version
+const MEMBER_BYTES: usize = PUBLIC_KEY_BYTES * 2 + 1 + 4;
+/// Number of members a squad can have with the current member encoding
+pub const MAX_MEMBERS: usize = (SQUAD_MEMBERS_BYTES - MEMBER_LENGTH_BYTES) / MEMBER_BYTES;
+/// Number of vaults a squad can use, indexed 0..MAX_VAULTS
+pub const MAX_VAULTS: u8 = 8;
 const SQUAD_SETTING_BYTES: usize = 1;
-const SQUAD_NAME_BYTES: usize = 24;
-const SQUAD_DESCRIPTION_BYTES: usize = 36;
-const SQUAD_TOKEN_BYTES: usize = 6;
+pub const SQUAD_NAME_BYTES: usize = 24;
+pub const SQUAD_DESCRIPTION_BYTES: usize = 36;
+pub const SQUAD_TOKEN_BYTES: usize = 6;
 const PUBLIC_KEY_BYTES: usize = 32;
 const PROPOSAL_NONCE_BYTES: usize = 4;
 const MEMBER_LENGTH_BYTES: usize = 4;
 const TIMESTAMP_BYTES: usize = 8;
-const SQUAD_RESERVED_BYTES: usize = 8 * 32;
-const SQUAD_RANDOM_ID_BYTES: usize = 10;
+const SQUAD_RESERVED_BYTES: usize = 8 * 22;
+pub const SQUAD_RANDOM_ID_BYTES: usize = 10;
 const CHILD_INDEX_BYTES: usize = 4;
 const MEMBER_LOCK_BYTES: usize = 4;
+const VOTE_BPS_BYTES: usize = 2;
+const MEMBERSHIP_VERSION_BYTES: usize = 4;
+const DEPOSITED_SUPPLY_BYTES: usize = 8;
+const CORE_WITHDRAW_LIMIT_BYTES: usize = 8;
+const KEEPER_TIP_BYTES: usize = 8;
+const MIN_DEPOSIT_BYTES: usize = 8;
+// encoding of the members map, 0 for squads created before member roles,
+// 1 before members recorded their join version
+const MEMBERS_ENCODING: u8 = 2;
+
+/// Member role bits, a member can hold any combination of them
+pub const MEMBER_ROLE_PROPOSER: u8 = 1;
+pub const MEMBER_ROLE_VOTER: u8 = 1 << 1;
+pub const MEMBER_ROLE_EXECUTOR: u8 = 1 << 2;
+pub const MEMBER_ROLE_ALL: u8 = MEMBER_ROLE_PROPOSER | MEMBER_ROLE_VOTER | MEMBER_ROLE_EXECUTOR;
+/// Team core council seat, granted and revoked by SetCoreMember proposals only
+pub const MEMBER_ROLE_CORE: u8 = 1 << 3;
 
 // SQUAD STRUCT
 const SQUAD_TOTAL_BYTES: usize = SQUAD_SETTING_BYTES +  // is_initialized
@@ -45,11 +73,11 @@ const SQUAD_TOTAL_BYTES: usize = SQUAD_SETTING_BYTES +  // is_initialized
     SQUAD_NAME_BYTES +          // bytes for the name
     SQUAD_DESCRIPTION_BYTES +
     SQUAD_TOKEN_BYTES +
-    SQUAD_SETTING_BYTES +       // future_setting_1
-    SQUAD_SETTING_BYTES +       // future_setting_2
-    SQUAD_SETTING_BYTES +       // future_setting_3
-    SQUAD_SETTING_BYTES +       // future_setting_4
-    SQUAD_SETTING_BYTES +       // future_setting_5
+    SQUAD_SETTING_BYTES +       // members_encoding
+    SQUAD_SETTING_BYTES +       // token_gated
+    SQUAD_SETTING_BYTES +       // has_config
+    SQUAD_SETTING_BYTES +       // has_address_book
+    SQUAD_SETTING_BYTES +       // keeper_execution
     PUBLIC_KEY_BYTES +          // admin
     PUBLIC_KEY_BYTES +          // mint pda
     PUBLIC_KEY_BYTES +          // sol pda
@@ -61,16 +89,42 @@ const SQUAD_TOTAL_BYTES: usize = SQUAD_SETTING_BYTES +  // is_initialized
     PROPOSAL_NONCE_BYTES +      // proposal
     TIMESTAMP_BYTES +           // created on
     MEMBER_LENGTH_BYTES +       // bytes for the length num
-    ((PUBLIC_KEY_BYTES * 2) * SQUAD_MAX_MEMBERS) + 4 +  // MEMBER STRUCTS
+    SQUAD_MEMBERS_BYTES +       // MEMBER STRUCTS
     SQUAD_RANDOM_ID_BYTES +     // random_id 10
     CHILD_INDEX_BYTES +         // child_index 4
     MEMBER_LOCK_BYTES +       // member lock bytes
+    PUBLIC_KEY_BYTES +          // parent squad
+    MAX_VAULTS as usize +       // vault thresholds 8
+    VOTE_BPS_BYTES +            // vote_support_bps 2
+    VOTE_BPS_BYTES +            // vote_quorum_bps 2
+    MEMBERSHIP_VERSION_BYTES +  // membership_version 4
+    DEPOSITED_SUPPLY_BYTES +    // deposited_supply 8
+    CORE_WITHDRAW_LIMIT_BYTES + // core_withdraw_limit 8
+    KEEPER_TIP_BYTES +          // keeper_tip 8
+    MIN_DEPOSIT_BYTES +         // min_deposit 8
     SQUAD_RESERVED_BYTES;
 
 /// Member struct for a Squad, used in the members BTreeMap
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
 pub struct Member {
     pub equity_token_account: Pubkey, // contributions_account: [u8; 32], // need to expand for each mint
+    // bitmask of MEMBER_ROLE_* flags
+    pub role: u8,
+    // squad membership_version the member was added at, 0 for the founding members
+    pub joined_version: u32,
+}
+
+// Member encoding before roles, every legacy member holds all roles
+#[derive(BorshSerialize, BorshDeserialize)]
+struct LegacyMember {
+    equity_token_account: Pubkey,
+}
+
+// Member encoding before join versions, every one of them counts as a founding member
+#[derive(BorshSerialize, BorshDeserialize)]
+struct RoleMember {
+    equity_token_account: Pubkey,
+    role: u8,
 }
 
 /// The Squad Account struct
@@ -92,12 +146,16 @@ pub struct Squad {
     pub description: String,
     pub token: String,
 
-    // future settings placeholders
-    pub future_setting_1: u8,
-    pub future_setting_2: u8,
-    pub future_setting_3: u8,
-    pub future_setting_4: u8,
-    pub future_setting_5: u8,
+    // encoding version of the members map
+    pub members_encoding: u8,
+    // Team squad governed by an existing SPL mint (mint_address) deposited by its members
+    pub token_gated: bool,
+    // whether the config extension account exists, proposals then have to pass it
+    pub has_config: bool,
+    // whether the address book account exists, proposals then have to pass it
+    pub has_address_book: bool,
+    // whether any signer can execute an approved proposal, not only executor members
+    pub keeper_execution: bool,
 
     /// misc address for squad specific settings
     // admin address for draft mode (open=true) only
@@ -118,10 +176,28 @@ pub struct Squad {
 
     pub random_id: String,
 
+    // number of child squads created, children are derived from this squad and their index
     pub child_index: u32,
     pub member_lock_index: u32,
+    // the squad this one was created as a child of, default pubkey if none
+    pub parent: Pubkey,
+    // threshold policy per vault index, 0 to use the squad threshold
+    pub vault_thresholds: [u8; MAX_VAULTS as usize],
+    // Team support and quorum in basis points, 0 to use the whole percent settings
+    pub vote_support_bps: u16,
+    pub vote_quorum_bps: u16,
+    // bumped on every member added or removed, proposals snapshot it at creation
+    pub membership_version: u32,
+    // governance tokens held in the member escrows of a token-gated squad
+    pub deposited_supply: u64,
+    // Team withdrawals above this amount need the core council approval
+    pub core_withdraw_limit: u64,
+    // lamports a keeper execution can take from the proposal vault to cover its fees
+    pub keeper_tip: u64,
+    // governance tokens a member of a token-gated squad has to keep in escrow
+    pub min_deposit: u64,
     // reserved for future updates
-    pub reserved: [u64; 32],
+    pub reserved: [u64; 22],
 }
 
 impl Sealed for Squad {}
@@ -133,8 +209,61 @@ impl IsInitialized for Squad {
 }
 
 impl Squad {
-    pub fn add_member(&mut self, key: Pubkey, value: Member) {
+    /// Adds or replaces a member, a new member is rejected once the squad has MAX_MEMBERS
+    pub fn add_member(&mut self, key: Pubkey, value: Member) -> Result<(), ProgramError> {
+        if !self.members.contains_key(&key) && self.members.len() >= MAX_MEMBERS {
+            return Err(SquadError::SquadFull.into());
+        }
         self.members.insert(key, value);
+        Ok(())
+    }
+
+    // the members in the newest encoding they fit the member bytes in, squads
+    // migrated with more than MAX_MEMBERS members keep an older encoding as long
+    // as it holds everything their members carry
+    fn members_encoded(&self) -> (u8, Vec<u8>) {
+        let members_ser = self.members.try_to_vec().unwrap();
+        if members_ser.len() <= SQUAD_MEMBERS_BYTES
+            || self
+                .members
+                .values()
+                .any(|member| member.joined_version != 0)
+        {
+            return (MEMBERS_ENCODING, members_ser);
+        }
+        if self
+            .members
+            .values()
+            .any(|member| member.role != MEMBER_ROLE_ALL)
+        {
+            let role_members: BTreeMap<&Pubkey, RoleMember> = self
+                .members
+                .iter()
+                .map(|(key, member)| {
+                    (
+                        key,
+                        RoleMember {
+                            equity_token_account: member.equity_token_account,
+                            role: member.role,
+                        },
+                    )
+                })
+                .collect();
+            return (1, role_members.try_to_vec().unwrap());
+        }
+        let legacy_members: BTreeMap<&Pubkey, LegacyMember> = self
+            .members
+            .iter()
+            .map(|(key, member)| {
+                (
+                    key,
+                    LegacyMember {
+                        equity_token_account: member.equity_token_account,
+                    },
+                )
+            })
+            .collect();
+        (0, legacy_members.try_to_vec().unwrap())
     }
 
     pub fn remove_member(&mut self, key: &Pubkey) {
@@ -145,6 +274,207 @@ impl Squad {
         self.members.contains_key(key)
     }
 
+    /// Whether the key is a member holding every bit of `role`
+    pub fn member_has_role(&self, key: &Pubkey, role: u8) -> bool {
+        match self.members.get(key) {
+            Some(member) => member.role & role == role,
+            None => false,
+        }
+    }
+
+    /// Whether the key was already a member at the given membership_version
+    pub fn member_joined_by(&self, key: &Pubkey, membership_version: u32) -> bool {
+        match self.members.get(key) {
+            Some(member) => member.joined_version <= membership_version,
+            None => false,
+        }
+    }
+
+    /// Number of members that can vote
+    pub fn voter_count(&self) -> usize {
+        self.members
+            .values()
+            .filter(|member| member.role & MEMBER_ROLE_VOTER != 0)
+            .count()
+    }
+
+    /// Number of members holding a core council seat
+    pub fn core_count(&self) -> usize {
+        self.members
+            .values()
+            .filter(|member| member.role & MEMBER_ROLE_CORE != 0)
+            .count()
+    }
+
+    /// Whether `key` can lose its core seat, a seated council can't shrink below core_threshold
+    pub fn core_seat_removable(&self, key: &Pubkey) -> bool {
+        !self.member_has_role(key, MEMBER_ROLE_CORE)
+            || self.core_threshold == 0
+            || self.core_count() != self.core_threshold as usize
+    }
+
+    /// Core approvals the Team proposal needs, 0 unless it is sensitive and the council
+    /// has at least core_threshold seats. Sensitive proposals are withdrawals above
+    /// core_withdraw_limit, membership and governance rule changes, and changes to the
+    /// authorities the squad holds over programs and mints
+    pub fn proposal_core_threshold(&self, proposal: &Proposal) -> u8 {
+        if self.allocation_type != AllocationType::TeamCoordination as u8
+            || self.core_threshold == 0
+            || self.core_count() < self.core_threshold as usize
+        {
+            return 0;
+        }
+        let sensitive = match FromPrimitive::from_u8(proposal.proposal_type) {
+            Some(ProposalType::WithdrawSol) | Some(ProposalType::WithdrawSpl) => {
+                proposal.execution_amount > self.core_withdraw_limit
+            }
+            Some(ProposalType::AddMember)
+            | Some(ProposalType::RemoveMember)
+            | Some(ProposalType::SetMemberRole)
+            | Some(ProposalType::Support)
+            | Some(ProposalType::Quorum)
+            | Some(ProposalType::SupportBps)
+            | Some(ProposalType::QuorumBps)
+            | Some(ProposalType::SetCoreMember)
+            | Some(ProposalType::CoreWithdrawLimit)
+            | Some(ProposalType::VaultPolicy)
+            | Some(ProposalType::TypeThreshold)
+            | Some(ProposalType::AddressBookEntry)
+            | Some(ProposalType::UnlistedPolicy)
+            | Some(ProposalType::OptimisticType)
+            | Some(ProposalType::OptimisticWindow)
+            | Some(ProposalType::ProposalExpiry)
+            | Some(ProposalType::KeeperPolicy)
+            | Some(ProposalType::UpgradeProgram)
+            | Some(ProposalType::SetUpgradeAuthority)
+            | Some(ProposalType::MintTo)
+            | Some(ProposalType::SetTokenAuthority) => true,
+            _ => false,
+        };
+        if sensitive {
+            self.core_threshold
+        } else {
+            0
+        }
+    }
+
+    /// Whether the Multisig quorum and vault thresholds stay reachable if `key` held `role`
+    pub fn role_keeps_thresholds(&self, key: &Pubkey, role: u8) -> bool {
+        let voters = self
+            .members
+            .iter()
+            .filter(|(member_key, member)| {
+                let member_role = if *member_key == key {
+                    role
+                } else {
+                    member.role
+                };
+                member_role & MEMBER_ROLE_VOTER != 0
+            })
+            .count();
+        self.vote_quorum as usize <= voters
+            && self
+                .vault_thresholds
+                .iter()
+                .all(|threshold| *threshold as usize <= voters)
+    }
+
+    /// Threshold the proposal has to reach: vote_quorum for a Multisig, vote_support for a Team,
+    /// unless the proposal moves funds out of a vault that has its own policy, or its type
+    /// had a threshold in the squad config when it was created. Withdrawals to a destination
+    /// missing from the address book need at least its unlisted threshold
+    pub fn proposal_threshold(&self, proposal: &Proposal) -> u8 {
+        let threshold = self.base_threshold(proposal);
+        if proposal.unlisted_threshold <= threshold {
+            return threshold;
+        }
+        if self.allocation_type == AllocationType::Multisig as u8 {
+            // members may have left since, keep the threshold reachable
+            return proposal
+                .unlisted_threshold
+                .min(self.voter_count() as u8)
+                .max(threshold);
+        }
+        proposal.unlisted_threshold
+    }
+
+    fn base_threshold(&self, proposal: &Proposal) -> u8 {
+        let default_threshold = if self.allocation_type == AllocationType::Multisig as u8 {
+            self.vote_quorum
+        } else {
+            self.vote_support
+        };
+        if let Some(threshold) = self.vault_policy(proposal) {
+            return threshold;
+        }
+        if proposal.type_threshold > 0 {
+            if self.allocation_type == AllocationType::Multisig as u8 {
+                // members may have left since, keep the threshold reachable
+                return proposal.type_threshold.min(self.voter_count() as u8).max(1);
+            }
+            return proposal.type_threshold;
+        }
+        default_threshold
+    }
+
+    // threshold of the vault the proposal moves funds out of, if that vault has a policy
+    fn vault_policy(&self, proposal: &Proposal) -> Option<u8> {
+        if !proposal.is_vault_proposal() {
+            return None;
+        }
+        match self.vault_thresholds.get(proposal.vault_index as usize) {
+            Some(threshold) if *threshold > 0 => Some(*threshold),
+            _ => None,
+        }
+    }
+
+    /// Team support in basis points, squads without a bps setting use their whole percent
+    pub fn support_bps(&self) -> u16 {
+        if self.vote_support_bps > 0 {
+            self.vote_support_bps
+        } else {
+            self.vote_support as u16 * 100
+        }
+    }
+
+    /// Team quorum in basis points, squads without a bps setting use their whole percent
+    pub fn quorum_bps(&self) -> u16 {
+        if self.vote_quorum_bps > 0 {
+            self.vote_quorum_bps
+        } else {
+            self.vote_quorum as u16 * 100
+        }
+    }
+
+    /// Governance token supply votes are weighed against, the deposited tokens
+    /// of a token-gated squad or the whole supply of the squad mint
+    pub fn governance_supply(&self, mint_supply: u64) -> u64 {
+        if self.token_gated {
+            self.deposited_supply
+        } else {
+            mint_supply
+        }
+    }
+
+    /// Whether a member escrow can hold this balance, a token-gated member keeps at
+    /// least the squad minimum deposit or leaves with nothing
+    pub fn escrow_balance_allowed(&self, balance: u64) -> bool {
+        balance == 0 || balance >= self.min_deposit.max(1)
+    }
+
+    /// Team support the proposal needs in basis points, vault policies and
+    /// proposal type thresholds are whole percents
+    pub fn proposal_support_bps(&self, proposal: &Proposal) -> u16 {
+        let support_bps = if let Some(threshold) = self.vault_policy(proposal) {
+            threshold as u16 * 100
+        } else if proposal.type_threshold > 0 {
+            proposal.type_threshold as u16 * 100
+        } else {
+            self.support_bps()
+        };
+        support_bps.max(proposal.unlisted_threshold as u16 * 100)
+    }
+
     pub fn setup_tc(
         &mut self,
         allocation_type: u8,
@@ -202,6 +532,20 @@ impl Squad {
 
 impl Pack for Squad {
     const LEN: usize = SQUAD_TOTAL_BYTES;
+
+    // fails instead of overflowing the member bytes, which only a migrated squad
+    // with more than MAX_MEMBERS members can do once they need the newest encoding
+    fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if src.members_encoded().1.len() > SQUAD_MEMBERS_BYTES {
+            return Err(SquadError::SquadFull.into());
+        }
+        src.pack_into_slice(dst);
+        Ok(())
+    }
+
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
         let src = array_ref![src, 0, SQUAD_TOTAL_BYTES];
         let (
@@ -216,12 +560,12 @@ impl Pack for Squad {
             squad_name_src,
             description_src,
             token_src,
+            members_encoding,
             // future settings placeholders
-            future_setting_1,
-            future_setting_2,
-            future_setting_3,
-            future_setting_4,
-            future_setting_5,
+            token_gated,
+            has_config,
+            has_address_book,
+            keeper_execution,
             // misc address for squad specific settings
             // admin address for draft mode (open=true) only
             admin,
@@ -237,8 +581,17 @@ impl Pack for Squad {
             members_len,
             members_src,
             random_id,
-            _child_index,
+            child_index,
             member_lock_index,
+            parent,
+            vault_thresholds,
+            vote_support_bps,
+            vote_quorum_bps,
+            membership_version,
+            deposited_supply,
+            core_withdraw_limit,
+            keeper_tip,
+            min_deposit,
             _reserved,
         ) = array_refs![
             src,
@@ -252,27 +605,36 @@ impl Pack for Squad {
             SQUAD_NAME_BYTES,    // bytes for the name
             SQUAD_DESCRIPTION_BYTES,
             SQUAD_TOKEN_BYTES,
-            SQUAD_SETTING_BYTES,                              // future_setting_1
-            SQUAD_SETTING_BYTES,                              // future_setting_2
-            SQUAD_SETTING_BYTES,                              // future_setting_3
-            SQUAD_SETTING_BYTES,                              // future_setting_4
-            SQUAD_SETTING_BYTES,                              // future_setting_5
-            PUBLIC_KEY_BYTES,                                 // admin
-            PUBLIC_KEY_BYTES,                                 // mint pda
-            PUBLIC_KEY_BYTES,                                 // sol pda
-            PUBLIC_KEY_BYTES,                                 // future_address 1
-            PUBLIC_KEY_BYTES,                                 // future_address 2
-            PUBLIC_KEY_BYTES,                                 // future_address 3
-            PUBLIC_KEY_BYTES,                                 // future_address 4
-            PUBLIC_KEY_BYTES,                                 // future_address 5
-            PROPOSAL_NONCE_BYTES,                             // proposal
-            TIMESTAMP_BYTES,                                  // created on
-            MEMBER_LENGTH_BYTES,                              // bytes for the length num
-            ((PUBLIC_KEY_BYTES * 2) * SQUAD_MAX_MEMBERS) + 4, // Member structs
+            SQUAD_SETTING_BYTES,  // members_encoding
+            SQUAD_SETTING_BYTES,  // token_gated
+            SQUAD_SETTING_BYTES,  // has_config
+            SQUAD_SETTING_BYTES,  // has_address_book
+            SQUAD_SETTING_BYTES,  // keeper_execution
+            PUBLIC_KEY_BYTES,     // admin
+            PUBLIC_KEY_BYTES,     // mint pda
+            PUBLIC_KEY_BYTES,     // sol pda
+            PUBLIC_KEY_BYTES,     // future_address 1
+            PUBLIC_KEY_BYTES,     // future_address 2
+            PUBLIC_KEY_BYTES,     // future_address 3
+            PUBLIC_KEY_BYTES,     // future_address 4
+            PUBLIC_KEY_BYTES,     // future_address 5
+            PROPOSAL_NONCE_BYTES, // proposal
+            TIMESTAMP_BYTES,      // created on
+            MEMBER_LENGTH_BYTES,  // bytes for the length num
+            SQUAD_MEMBERS_BYTES,  // Member structs
             SQUAD_RANDOM_ID_BYTES,
             CHILD_INDEX_BYTES,
-            MEMBER_LOCK_BYTES,    // Member lock index
-            SQUAD_RESERVED_BYTES  // reserved for future
+            MEMBER_LOCK_BYTES,         // Member lock index
+            PUBLIC_KEY_BYTES,          // parent squad
+            MAX_VAULTS as usize,       // vault thresholds
+            VOTE_BPS_BYTES,            // vote_support_bps
+            VOTE_BPS_BYTES,            // vote_quorum_bps
+            MEMBERSHIP_VERSION_BYTES,  // membership_version
+            DEPOSITED_SUPPLY_BYTES,    // deposited_supply
+            CORE_WITHDRAW_LIMIT_BYTES, // core_withdraw_limit
+            KEEPER_TIP_BYTES,          // keeper_tip
+            MIN_DEPOSIT_BYTES,         // min_deposit
+            SQUAD_RESERVED_BYTES       // reserved for future
         ];
 
         let is_initialized = match is_initialized {
@@ -293,11 +655,74 @@ impl Pack for Squad {
             _ => return Err(ProgramError::InvalidAccountData),
         };
 
+        let token_gated = match token_gated {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        let has_config = match has_config {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        let has_address_book = match has_address_book {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        let keeper_execution = match keeper_execution {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        let members_encoding = u8::from_le_bytes(*members_encoding);
         let mut member_dser = BTreeMap::<Pubkey, Member>::new();
         let member_length = count_from_le(members_len);
         if member_length > 0 {
-            member_dser =
-                BTreeMap::<Pubkey, Member>::try_from_slice(&members_src[0..member_length]).unwrap()
+            if members_encoding == 0 {
+                // members stored before roles existed
+                member_dser = BTreeMap::<Pubkey, LegacyMember>::try_from_slice(
+                    &members_src[0..member_length],
+                )
+                .unwrap()
+                .into_iter()
+                .map(|(key, member)| {
+                    (
+                        key,
+                        Member {
+                            equity_token_account: member.equity_token_account,
+                            role: MEMBER_ROLE_ALL,
+                            joined_version: 0,
+                        },
+                    )
+                })
+                .collect();
+            } else if members_encoding == 1 {
+                // members stored before join versions existed
+                member_dser =
+                    BTreeMap::<Pubkey, RoleMember>::try_from_slice(&members_src[0..member_length])
+                        .unwrap()
+                        .into_iter()
+                        .map(|(key, member)| {
+                            (
+                                key,
+                                Member {
+                                    equity_token_account: member.equity_token_account,
+                                    role: member.role,
+                                    joined_version: 0,
+                                },
+                            )
+                        })
+                        .collect();
+            } else {
+                member_dser =
+                    BTreeMap::<Pubkey, Member>::try_from_slice(&members_src[0..member_length])
+                        .unwrap()
+            }
         }
 
         // deserialize the string
@@ -320,12 +745,12 @@ impl Pack for Squad {
             description: description_deser,
             token: token_deser,
 
+            members_encoding,
             // reserved
-            future_setting_1: u8::from_le_bytes(*future_setting_1),
-            future_setting_2: u8::from_le_bytes(*future_setting_2),
-            future_setting_3: u8::from_le_bytes(*future_setting_3),
-            future_setting_4: u8::from_le_bytes(*future_setting_4),
-            future_setting_5: u8::from_le_bytes(*future_setting_5),
+            token_gated,
+            has_config,
+            has_address_book,
+            keeper_execution,
 
             admin: Pubkey::new(admin),
             mint_address: Pubkey::new(mint_address),
@@ -347,9 +772,18 @@ impl Pack for Squad {
 
             random_id: random_id_deser,
 
-            child_index: 0,
+            child_index: u32::from_le_bytes(*child_index),
             member_lock_index: u32::from_le_bytes(*member_lock_index),
-            reserved: [0; 32],
+            parent: Pubkey::new(parent),
+            vault_thresholds: *vault_thresholds,
+            vote_support_bps: u16::from_le_bytes(*vote_support_bps),
+            vote_quorum_bps: u16::from_le_bytes(*vote_quorum_bps),
+            membership_version: u32::from_le_bytes(*membership_version),
+            deposited_supply: u64::from_le_bytes(*deposited_supply),
+            core_withdraw_limit: u64::from_le_bytes(*core_withdraw_limit),
+            keeper_tip: u64::from_le_bytes(*keeper_tip),
+            min_deposit: u64::from_le_bytes(*min_deposit),
+            reserved: [0; 22],
         })
     }
 
@@ -370,12 +804,12 @@ impl Pack for Squad {
             squad_name_dst,
             description_dst,
             token_dst,
+            member_version_dst,
             // future settings placeholders
-            _future_setting_1_dst,
-            _future_setting_2_dst,
-            _future_setting_3_dst,
-            _future_setting_4_dst,
-            _future_setting_5_dst,
+            token_gated_dst,
+            has_config_dst,
+            has_address_book_dst,
+            keeper_execution_dst,
             // misc address for squad specific settings
             // admin address for draft mode (open=true) only
             admin_dst,
@@ -391,8 +825,17 @@ impl Pack for Squad {
             members_len,
             members_dst,
             random_id_dst,
-            _child_index_dst,
+            child_index_dst,
             member_lock_index_dst,
+            parent_dst,
+            vault_thresholds_dst,
+            vote_support_bps_dst,
+            vote_quorum_bps_dst,
+            membership_version_dst,
+            deposited_supply_dst,
+            core_withdraw_limit_dst,
+            keeper_tip_dst,
+            min_deposit_dst,
             _reserved,
         ) = mut_array_refs![
             dst,
@@ -406,27 +849,36 @@ impl Pack for Squad {
             SQUAD_NAME_BYTES,    // bytes for the name
             SQUAD_DESCRIPTION_BYTES,
             SQUAD_TOKEN_BYTES,
-            SQUAD_SETTING_BYTES,                              // future_setting_1
-            SQUAD_SETTING_BYTES,                              // future_setting_2
-            SQUAD_SETTING_BYTES,                              // future_setting_3
-            SQUAD_SETTING_BYTES,                              // future_setting_4
-            SQUAD_SETTING_BYTES,                              // future_setting_5
-            PUBLIC_KEY_BYTES,                                 // admin
-            PUBLIC_KEY_BYTES,                                 // mint pda
-            PUBLIC_KEY_BYTES,                                 // sol pda
-            PUBLIC_KEY_BYTES,                                 // future_address 1
-            PUBLIC_KEY_BYTES,                                 // future_address 2
-            PUBLIC_KEY_BYTES,                                 // future_address 3
-            PUBLIC_KEY_BYTES,                                 // future_address 4
-            PUBLIC_KEY_BYTES,                                 // future_address 5
-            PROPOSAL_NONCE_BYTES,                             // proposal index
-            TIMESTAMP_BYTES,                                  // created_on bytes
-            MEMBER_LENGTH_BYTES,                              // bytes for the length num
-            ((PUBLIC_KEY_BYTES * 2) * SQUAD_MAX_MEMBERS) + 4, //bytes for the members BTREE itself
+            SQUAD_SETTING_BYTES,  // members_encoding
+            SQUAD_SETTING_BYTES,  // token_gated
+            SQUAD_SETTING_BYTES,  // has_config
+            SQUAD_SETTING_BYTES,  // has_address_book
+            SQUAD_SETTING_BYTES,  // keeper_execution
+            PUBLIC_KEY_BYTES,     // admin
+            PUBLIC_KEY_BYTES,     // mint pda
+            PUBLIC_KEY_BYTES,     // sol pda
+            PUBLIC_KEY_BYTES,     // future_address 1
+            PUBLIC_KEY_BYTES,     // future_address 2
+            PUBLIC_KEY_BYTES,     // future_address 3
+            PUBLIC_KEY_BYTES,     // future_address 4
+            PUBLIC_KEY_BYTES,     // future_address 5
+            PROPOSAL_NONCE_BYTES, // proposal index
+            TIMESTAMP_BYTES,      // created_on bytes
+            MEMBER_LENGTH_BYTES,  // bytes for the length num
+            SQUAD_MEMBERS_BYTES,  //bytes for the members BTREE itself
             SQUAD_RANDOM_ID_BYTES,
             CHILD_INDEX_BYTES,
             MEMBER_LOCK_BYTES,
-            SQUAD_RESERVED_BYTES // reserved for future
+            PUBLIC_KEY_BYTES,          // parent squad
+            MAX_VAULTS as usize,       // vault thresholds
+            VOTE_BPS_BYTES,            // vote_support_bps
+            VOTE_BPS_BYTES,            // vote_quorum_bps
+            MEMBERSHIP_VERSION_BYTES,  // membership_version
+            DEPOSITED_SUPPLY_BYTES,    // deposited_supply
+            CORE_WITHDRAW_LIMIT_BYTES, // core_withdraw_limit
+            KEEPER_TIP_BYTES,          // keeper_tip
+            MIN_DEPOSIT_BYTES,         // min_deposit
+            SQUAD_RESERVED_BYTES       // reserved for future
         ];
 
         let Squad {
@@ -442,12 +894,13 @@ impl Pack for Squad {
             description,
             token,
 
+            // the members are packed with the newest encoding they fit in
+            members_encoding: _,
             // future settings placeholders
-            future_setting_1: _,
-            future_setting_2: _,
-            future_setting_3: _,
-            future_setting_4: _,
-            future_setting_5: _,
+            token_gated,
+            has_config,
+            has_address_book,
+            keeper_execution,
 
             // misc address for squad specific settings
             // admin address for draft mode (open=true) only
@@ -461,16 +914,29 @@ impl Pack for Squad {
             future_address5: _,
             proposal_nonce,
             created_on,
-            members,
+            members: _,
             random_id,
-            child_index: _,
+            child_index,
             member_lock_index,
+            parent,
+            vault_thresholds,
+            vote_support_bps,
+            vote_quorum_bps,
+            membership_version,
+            deposited_supply,
+            core_withdraw_limit,
+            keeper_tip,
+            min_deposit,
             reserved: _,
         } = self;
 
         is_initialized_dst[0] = *is_initialized as u8;
         open_dst[0] = *open as u8;
         emergency_lock_dst[0] = *emergency_lock as u8;
+        token_gated_dst[0] = *token_gated as u8;
+        has_config_dst[0] = *has_config as u8;
+        has_address_book_dst[0] = *has_address_book as u8;
+        keeper_execution_dst[0] = *keeper_execution as u8;
         *allocation_type_dst = allocation_type.to_le_bytes();
         *vote_support_dst = vote_support.to_le_bytes();
         *vote_quorum_dst = vote_quorum.to_le_bytes();
@@ -481,7 +947,8 @@ impl Pack for Squad {
         sol_account_dst.copy_from_slice(sol_account.as_ref());
 
         // pack the squad members
-        let members_ser = members.try_to_vec().unwrap();
+        let (members_encoding, members_ser) = self.members_encoded();
+        member_version_dst[0] = members_encoding;
         members_len[..].copy_from_slice(&transform_u32_to_array_of_u8(members_ser.len() as u32));
         members_dst[..members_ser.len()].copy_from_slice(&members_ser);
 
@@ -495,6 +962,16 @@ impl Pack for Squad {
 
         *proposal_nonce_dst = proposal_nonce.to_le_bytes();
         *member_lock_index_dst = member_lock_index.to_le_bytes();
+        *child_index_dst = child_index.to_le_bytes();
+        parent_dst.copy_from_slice(parent.as_ref());
+        *vault_thresholds_dst = *vault_thresholds;
+        *vote_support_bps_dst = vote_support_bps.to_le_bytes();
+        *vote_quorum_bps_dst = vote_quorum_bps.to_le_bytes();
+        *membership_version_dst = membership_version.to_le_bytes();
+        *deposited_supply_dst = deposited_supply.to_le_bytes();
+        *core_withdraw_limit_dst = core_withdraw_limit.to_le_bytes();
+        *keeper_tip_dst = keeper_tip.to_le_bytes();
+        *min_deposit_dst = min_deposit.to_le_bytes();
         // when packing we can ignore the future stuff
     }
 }
@@ -502,6 +979,7 @@ impl Pack for Squad {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::proposal::ProposalType;
     // use chrono;
     use solana_program::pubkey::Pubkey;
 
@@ -512,18 +990,24 @@ mod tests {
             Pubkey::new_unique(),
             Member {
                 equity_token_account: Pubkey::new_unique(),
+                role: MEMBER_ROLE_ALL,
+                joined_version: 0,
             },
         );
         members.insert(
             Pubkey::new_unique(),
             Member {
                 equity_token_account: Pubkey::new_unique(),
+                role: MEMBER_ROLE_ALL,
+                joined_version: 0,
             },
         );
         members.insert(
             Pubkey::new_unique(),
             Member {
                 equity_token_account: Pubkey::new_unique(),
+                role: MEMBER_ROLE_ALL,
+                joined_version: 0,
             },
         );
         let members_enc = members.try_to_vec().unwrap();
@@ -553,12 +1037,12 @@ mod tests {
             squad_name: String::from("THIS IS MY SQUAD"),
             description: String::from("THIS IS A TEST DESCRIPTION"),
             token: String::from("TOKENS"),
+            members_encoding: MEMBERS_ENCODING,
             // future settings placeholders
-            future_setting_1: 0,
-            future_setting_2: 0,
-            future_setting_3: 0,
-            future_setting_4: 0,
-            future_setting_5: 0,
+            token_gated: false,
+            has_config: false,
+            has_address_book: false,
+            keeper_execution: false,
 
             /// misc address for squad specific settings
             // admin address for draft mode (open=true) only
@@ -580,10 +1064,409 @@ mod tests {
             members: BTreeMap::<Pubkey, Member>::new(),
             proposal_nonce: 0,
             member_lock_index: 0,
+            parent: Pubkey::default(),
+            vault_thresholds: [0; MAX_VAULTS as usize],
+            vote_support_bps: 0,
+            vote_quorum_bps: 0,
+            membership_version: 0,
+            deposited_supply: 0,
+            core_withdraw_limit: 0,
+            keeper_tip: 0,
+            min_deposit: 0,
             created_on: 0,
-            reserved: [0; 32],
+            reserved: [0; 22],
         };
         let mut squad_dst: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
         let _packed_squad = Squad::pack(squad_info, &mut squad_dst);
     }
+
+    #[test]
+    fn squad_child_fields_roundtrip() {
+        let squad_arr: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        let mut squad_info = Squad::unpack_unchecked(&squad_arr).unwrap();
+        let parent = Pubkey::new_unique();
+        squad_info.child_index = 3;
+        squad_info.parent = parent;
+        squad_info.keeper_execution = true;
+        squad_info.keeper_tip = 5_000;
+
+        let mut squad_dst: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        Squad::pack(squad_info, &mut squad_dst).unwrap();
+        let unpacked = Squad::unpack_unchecked(&squad_dst).unwrap();
+        assert_eq!(unpacked.child_index, 3);
+        assert_eq!(unpacked.parent, parent);
+        assert!(unpacked.keeper_execution);
+        assert_eq!(unpacked.keeper_tip, 5_000);
+    }
+
+    #[test]
+    fn squad_min_deposit() {
+        let squad_arr: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        let mut squad_info = Squad::unpack_unchecked(&squad_arr).unwrap();
+        squad_info.token_gated = true;
+        // older squads have no minimum, any non-zero balance holds a seat
+        assert!(squad_info.escrow_balance_allowed(1));
+        assert!(squad_info.escrow_balance_allowed(0));
+
+        squad_info.min_deposit = 1_000;
+        let mut squad_dst: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        Squad::pack(squad_info, &mut squad_dst).unwrap();
+        let unpacked = Squad::unpack_unchecked(&squad_dst).unwrap();
+        assert_eq!(unpacked.min_deposit, 1_000);
+        assert!(!unpacked.escrow_balance_allowed(1));
+        assert!(!unpacked.escrow_balance_allowed(999));
+        assert!(unpacked.escrow_balance_allowed(1_000));
+        // leaving with nothing is always allowed
+        assert!(unpacked.escrow_balance_allowed(0));
+    }
+
+    #[test]
+    fn squad_vault_threshold() {
+        let squad_arr: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        let mut squad_info = Squad::unpack_unchecked(&squad_arr).unwrap();
+        squad_info.allocation_type = AllocationType::Multisig as u8;
+        squad_info.vote_quorum = 3;
+        squad_info.vault_thresholds[1] = 1;
+
+        let proposal_arr = vec![0; Proposal::get_packed_len()];
+        let mut proposal_info = Proposal::unpack_unchecked(&proposal_arr).unwrap();
+        proposal_info.proposal_type = ProposalType::WithdrawSol as u8;
+        assert_eq!(squad_info.proposal_threshold(&proposal_info), 3);
+
+        proposal_info.vault_index = 1;
+        assert_eq!(squad_info.proposal_threshold(&proposal_info), 1);
+
+        // only proposals moving vault funds use the vault policy
+        proposal_info.proposal_type = ProposalType::AddMember as u8;
+        assert_eq!(squad_info.proposal_threshold(&proposal_info), 3);
+
+        // a type threshold is capped by the voters left
+        proposal_info.type_threshold = 4;
+        assert_eq!(squad_info.proposal_threshold(&proposal_info), 1);
+        for _ in 0..5 {
+            let key = Pubkey::new_unique();
+            squad_info
+                .add_member(
+                    key,
+                    Member {
+                        equity_token_account: key,
+                        role: MEMBER_ROLE_ALL,
+                        joined_version: 0,
+                    },
+                )
+                .unwrap();
+        }
+        assert_eq!(squad_info.proposal_threshold(&proposal_info), 4);
+
+        // an unlisted withdrawal destination can only raise the vault threshold
+        proposal_info.proposal_type = ProposalType::WithdrawSol as u8;
+        proposal_info.unlisted_threshold = 4;
+        assert_eq!(squad_info.proposal_threshold(&proposal_info), 4);
+        proposal_info.unlisted_threshold = 9;
+        assert_eq!(squad_info.proposal_threshold(&proposal_info), 5);
+    }
+
+    #[test]
+    fn squad_legacy_members_get_all_roles() {
+        let member_key = Pubkey::new_unique();
+        let mut legacy_members = BTreeMap::<Pubkey, LegacyMember>::new();
+        legacy_members.insert(
+            member_key,
+            LegacyMember {
+                equity_token_account: member_key,
+            },
+        );
+        let members_ser = legacy_members.try_to_vec().unwrap();
+
+        // a squad packed before roles, members_encoding is still 0
+        let mut squad_arr: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        let members_offset = SQUAD_SETTING_BYTES * 7
+            + SQUAD_NAME_BYTES
+            + SQUAD_DESCRIPTION_BYTES
+            + SQUAD_TOKEN_BYTES
+            + SQUAD_SETTING_BYTES * 5
+            + PUBLIC_KEY_BYTES * 8
+            + PROPOSAL_NONCE_BYTES
+            + TIMESTAMP_BYTES;
+        squad_arr[members_offset..members_offset + MEMBER_LENGTH_BYTES]
+            .copy_from_slice(&transform_u32_to_array_of_u8(members_ser.len() as u32));
+        let members_start = members_offset + MEMBER_LENGTH_BYTES;
+        squad_arr[members_start..members_start + members_ser.len()].copy_from_slice(&members_ser);
+
+        let mut squad_info = Squad::unpack_unchecked(&squad_arr).unwrap();
+        assert!(squad_info.member_has_role(&member_key, MEMBER_ROLE_ALL));
+
+        // repacking moves the squad to the role encoding
+        squad_info.members.get_mut(&member_key).unwrap().role = MEMBER_ROLE_EXECUTOR;
+        let mut squad_dst: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        Squad::pack(squad_info, &mut squad_dst).unwrap();
+        let unpacked = Squad::unpack_unchecked(&squad_dst).unwrap();
+        assert_eq!(unpacked.members_encoding, MEMBERS_ENCODING);
+        assert!(unpacked.member_has_role(&member_key, MEMBER_ROLE_EXECUTOR));
+        assert!(!unpacked.member_has_role(&member_key, MEMBER_ROLE_VOTER));
+        assert_eq!(unpacked.voter_count(), 0);
+    }
+
+    #[test]
+    fn squad_membership_versions() {
+        let founder = Pubkey::new_unique();
+        let mut role_members = BTreeMap::<Pubkey, RoleMember>::new();
+        role_members.insert(
+            founder,
+            RoleMember {
+                equity_token_account: founder,
+                role: MEMBER_ROLE_VOTER,
+            },
+        );
+        let members_ser = role_members.try_to_vec().unwrap();
+
+        // a squad packed before join versions, members_encoding is 1
+        let mut squad_arr: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        let encoding_offset = SQUAD_SETTING_BYTES * 7
+            + SQUAD_NAME_BYTES
+            + SQUAD_DESCRIPTION_BYTES
+            + SQUAD_TOKEN_BYTES;
+        squad_arr[encoding_offset] = 1;
+        let members_offset = encoding_offset
+            + SQUAD_SETTING_BYTES * 5
+            + PUBLIC_KEY_BYTES * 8
+            + PROPOSAL_NONCE_BYTES
+            + TIMESTAMP_BYTES;
+        squad_arr[members_offset..members_offset + MEMBER_LENGTH_BYTES]
+            .copy_from_slice(&transform_u32_to_array_of_u8(members_ser.len() as u32));
+        let members_start = members_offset + MEMBER_LENGTH_BYTES;
+        squad_arr[members_start..members_start + members_ser.len()].copy_from_slice(&members_ser);
+
+        let mut squad_info = Squad::unpack_unchecked(&squad_arr).unwrap();
+        assert!(squad_info.member_has_role(&founder, MEMBER_ROLE_VOTER));
+        assert!(!squad_info.member_has_role(&founder, MEMBER_ROLE_PROPOSER));
+        assert!(squad_info.member_joined_by(&founder, 0));
+
+        // a member added later isn't part of the older snapshots
+        let newcomer = Pubkey::new_unique();
+        squad_info.membership_version = 1;
+        squad_info
+            .add_member(
+                newcomer,
+                Member {
+                    equity_token_account: newcomer,
+                    role: MEMBER_ROLE_ALL,
+                    joined_version: 1,
+                },
+            )
+            .unwrap();
+        let mut squad_dst: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        Squad::pack(squad_info, &mut squad_dst).unwrap();
+        let unpacked = Squad::unpack_unchecked(&squad_dst).unwrap();
+        assert_eq!(unpacked.membership_version, 1);
+        assert!(!unpacked.member_joined_by(&newcomer, 0));
+        assert!(unpacked.member_joined_by(&newcomer, 1));
+        assert!(unpacked.member_joined_by(&founder, 1));
+        assert!(!unpacked.member_joined_by(&Pubkey::new_unique(), 1));
+    }
+
+    #[test]
+    fn squad_member_cap() {
+        assert_eq!(MAX_MEMBERS, 139);
+        let squad_arr: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        let mut squad_info = Squad::unpack_unchecked(&squad_arr).unwrap();
+        for version in 0..MAX_MEMBERS {
+            let key = Pubkey::new_unique();
+            squad_info
+                .add_member(
+                    key,
+                    Member {
+                        equity_token_account: key,
+                        role: MEMBER_ROLE_VOTER,
+                        joined_version: version as u32,
+                    },
+                )
+                .unwrap();
+        }
+        let key = Pubkey::new_unique();
+        let member = Member {
+            equity_token_account: key,
+            role: MEMBER_ROLE_ALL,
+            joined_version: 0,
+        };
+        assert!(squad_info.add_member(key, member).is_err());
+
+        // a full squad still packs, in the newest encoding
+        let mut squad_dst = [0; SQUAD_TOTAL_BYTES];
+        Squad::pack(squad_info, &mut squad_dst).unwrap();
+        let unpacked = Squad::unpack_unchecked(&squad_dst).unwrap();
+        assert_eq!(unpacked.members.len(), MAX_MEMBERS);
+        assert_eq!(unpacked.members_encoding, MEMBERS_ENCODING);
+    }
+
+    #[test]
+    fn squad_oversized_legacy_members() {
+        // 150 members written before roles existed don't fit the newest encoding
+        let squad_arr: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        let mut squad_info = Squad::unpack_unchecked(&squad_arr).unwrap();
+        for _ in 0..SQUAD_MAX_MEMBERS {
+            let key = Pubkey::new_unique();
+            squad_info.members.insert(
+                key,
+                Member {
+                    equity_token_account: key,
+                    role: MEMBER_ROLE_ALL,
+                    joined_version: 0,
+                },
+            );
+        }
+        let mut squad_dst = [0; SQUAD_TOTAL_BYTES];
+        Squad::pack(squad_info, &mut squad_dst).unwrap();
+        let mut migrated = Squad::unpack_unchecked(&squad_dst).unwrap();
+        assert_eq!(migrated.members_encoding, 0);
+        assert_eq!(migrated.members.len(), SQUAD_MAX_MEMBERS);
+
+        // it can't grow, and what the old encoding can't hold is an error, not a panic
+        let key = Pubkey::new_unique();
+        let member = Member {
+            equity_token_account: key,
+            role: MEMBER_ROLE_ALL,
+            joined_version: 1,
+        };
+        assert!(migrated.add_member(key, member).is_err());
+        let first = *migrated.members.keys().next().unwrap();
+        migrated.members.get_mut(&first).unwrap().role = MEMBER_ROLE_VOTER;
+        assert!(Squad::pack(migrated, &mut squad_dst).is_err());
+
+        // once it is back under 147 members it can carry roles
+        let mut migrated = Squad::unpack_unchecked(&squad_dst).unwrap();
+        let keys: Vec<Pubkey> = migrated.members.keys().take(3).copied().collect();
+        for key in keys.iter() {
+            migrated.remove_member(key);
+        }
+        let first = *migrated.members.keys().next().unwrap();
+        migrated.members.get_mut(&first).unwrap().role = MEMBER_ROLE_VOTER;
+        Squad::pack(migrated, &mut squad_dst).unwrap();
+        let migrated = Squad::unpack_unchecked(&squad_dst).unwrap();
+        assert_eq!(migrated.members_encoding, 1);
+        assert!(!migrated.member_has_role(&first, MEMBER_ROLE_EXECUTOR));
+    }
+
+    #[test]
+    fn squad_core_council() {
+        let squad_arr: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        let mut squad_info = Squad::unpack_unchecked(&squad_arr).unwrap();
+        squad_info.allocation_type = AllocationType::TeamCoordination as u8;
+        squad_info.core_threshold = 2;
+        squad_info.core_withdraw_limit = 1_000;
+        let core_keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        for key in core_keys.iter() {
+            squad_info
+                .add_member(
+                    *key,
+                    Member {
+                        equity_token_account: *key,
+                        role: MEMBER_ROLE_ALL | MEMBER_ROLE_CORE,
+                        joined_version: 0,
+                    },
+                )
+                .unwrap();
+        }
+
+        let proposal_arr = vec![0; Proposal::get_packed_len()];
+        let mut proposal_info = Proposal::unpack_unchecked(&proposal_arr).unwrap();
+        proposal_info.proposal_type = ProposalType::WithdrawSol as u8;
+        proposal_info.execution_amount = 1_000;
+        assert_eq!(squad_info.proposal_core_threshold(&proposal_info), 0);
+        proposal_info.execution_amount = 1_001;
+        assert_eq!(squad_info.proposal_core_threshold(&proposal_info), 2);
+        proposal_info.proposal_type = ProposalType::Text as u8;
+        assert_eq!(squad_info.proposal_core_threshold(&proposal_info), 0);
+
+        // the seated council can't shrink below the threshold
+        assert!(!squad_info.core_seat_removable(&core_keys[0]));
+        squad_info.core_threshold = 1;
+        assert!(squad_info.core_seat_removable(&core_keys[0]));
+
+        // a council still being seated doesn't hold proposals back
+        squad_info.core_threshold = 3;
+        proposal_info.proposal_type = ProposalType::RemoveMember as u8;
+        assert_eq!(squad_info.proposal_core_threshold(&proposal_info), 0);
+    }
+
+    #[test]
+    fn squad_core_sensitive_types() {
+        let squad_arr: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        let mut squad_info = Squad::unpack_unchecked(&squad_arr).unwrap();
+        squad_info.allocation_type = AllocationType::TeamCoordination as u8;
+        squad_info.core_threshold = 1;
+        let core_key = Pubkey::new_unique();
+        squad_info
+            .add_member(
+                core_key,
+                Member {
+                    equity_token_account: core_key,
+                    role: MEMBER_ROLE_ALL | MEMBER_ROLE_CORE,
+                    joined_version: 0,
+                },
+            )
+            .unwrap();
+        let proposal_arr = vec![0; Proposal::get_packed_len()];
+        let mut proposal_info = Proposal::unpack_unchecked(&proposal_arr).unwrap();
+
+        // governance and authority changes can't pass on a plain majority
+        for sensitive in [
+            ProposalType::AddMember,
+            ProposalType::RemoveMember,
+            ProposalType::SetMemberRole,
+            ProposalType::Support,
+            ProposalType::Quorum,
+            ProposalType::SupportBps,
+            ProposalType::QuorumBps,
+            ProposalType::SetCoreMember,
+            ProposalType::CoreWithdrawLimit,
+            ProposalType::VaultPolicy,
+            ProposalType::TypeThreshold,
+            ProposalType::AddressBookEntry,
+            ProposalType::UnlistedPolicy,
+            ProposalType::OptimisticType,
+            ProposalType::OptimisticWindow,
+            ProposalType::ProposalExpiry,
+            ProposalType::KeeperPolicy,
+            ProposalType::UpgradeProgram,
+            ProposalType::SetUpgradeAuthority,
+            ProposalType::MintTo,
+            ProposalType::SetTokenAuthority,
+        ] {
+            proposal_info.proposal_type = sensitive as u8;
+            assert_eq!(
+                squad_info.proposal_core_threshold(&proposal_info),
+                1,
+                "type {}",
+                proposal_info.proposal_type
+            );
+        }
+        for routine in [
+            ProposalType::Text,
+            ProposalType::CreateStake,
+            ProposalType::BurnTokens,
+            ProposalType::SyncNative,
+        ] {
+            proposal_info.proposal_type = routine as u8;
+            assert_eq!(squad_info.proposal_core_threshold(&proposal_info), 0);
+        }
+    }
+
+    #[test]
+    fn squad_bps_settings() {
+        let squad_arr: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        let mut squad_info = Squad::unpack_unchecked(&squad_arr).unwrap();
+        squad_info.vote_support = 51;
+        squad_info.vote_quorum = 20;
+        // legacy whole percent settings migrate to bps
+        assert_eq!(squad_info.support_bps(), 5_100);
+        assert_eq!(squad_info.quorum_bps(), 2_000);
+
+        squad_info.vote_support_bps = 5_050;
+        let mut squad_dst: [u8; SQUAD_TOTAL_BYTES] = [0; SQUAD_TOTAL_BYTES];
+        Squad::pack(squad_info, &mut squad_dst).unwrap();
+        let unpacked = Squad::unpack_unchecked(&squad_dst).unwrap();
+        assert_eq!(unpacked.support_bps(), 5_050);
+        assert_eq!(unpacked.quorum_bps(), 2_000);
+    }
 }
@@ -0,0 +1,274 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use crate::UnixTimestamp;
+
+const STREAM_INITIALIZED_BYTES: usize = 1;
+const PUBLIC_KEY_BYTES: usize = 32;
+const TIMESTAMP_BYTES: usize = 8;
+const AMOUNT_BYTES: usize = 8;
+const STREAM_RESERVED_BYTES: usize = 8 * 8;
+
+const STREAM_TOTAL_BYTES: usize = STREAM_INITIALIZED_BYTES + // is_initialized 1
+    PUBLIC_KEY_BYTES +                  // squad address 32
+    PUBLIC_KEY_BYTES +                  // proposal address 32
+    PUBLIC_KEY_BYTES +                  // recipient 32
+    PUBLIC_KEY_BYTES +                  // source 32
+    PUBLIC_KEY_BYTES +                  // mint 32
+    PUBLIC_KEY_BYTES +                  // escrow 32
+    TIMESTAMP_BYTES +                   // start timestamp 8
+    AMOUNT_BYTES +                      // rate per second 8
+    AMOUNT_BYTES +                      // total amount 8
+    AMOUNT_BYTES +                      // withdrawn amount 8
+    TIMESTAMP_BYTES +                   // canceled on 8
+    STREAM_RESERVED_BYTES; // reserved for updates
+
+/// Payment stream escrowed out of the squad vault, accruing to the recipient every second
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct Stream {
+    pub is_initialized: bool,
+    pub squad_address: Pubkey,
+    // the proposal that created the stream
+    pub proposal_address: Pubkey,
+    pub recipient: Pubkey,
+    // the vault (SOL) or vault token account (SPL) the funds came from, and return to
+    pub source: Pubkey,
+    // default pubkey for SOL streams
+    pub mint: Pubkey,
+    // the stream account itself for SOL, the stream token account for SPL
+    pub escrow: Pubkey,
+    pub start_timestamp: UnixTimestamp,
+    pub rate_per_second: u64,
+    pub total_amount: u64,
+    pub withdrawn_amount: u64,
+    // 0 unless the stream was canceled
+    pub canceled_on: UnixTimestamp,
+
+    // reserved for future updates
+    pub reserved: [u64; 8],
+}
+
+impl Sealed for Stream {}
+
+impl IsInitialized for Stream {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Stream {
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_stream(
+        &mut self,
+        squad_address: &Pubkey,
+        proposal_address: &Pubkey,
+        recipient: &Pubkey,
+        source: &Pubkey,
+        mint: &Pubkey,
+        escrow: &Pubkey,
+        start_timestamp: i64,
+        rate_per_second: u64,
+        total_amount: u64,
+    ) {
+        self.is_initialized = true;
+        self.squad_address = *squad_address;
+        self.proposal_address = *proposal_address;
+        self.recipient = *recipient;
+        self.source = *source;
+        self.mint = *mint;
+        self.escrow = *escrow;
+        self.start_timestamp = start_timestamp;
+        self.rate_per_second = rate_per_second;
+        self.total_amount = total_amount;
+        self.withdrawn_amount = 0;
+        self.canceled_on = 0;
+    }
+
+    pub fn is_sol(&self) -> bool {
+        self.mint == Pubkey::default()
+    }
+
+    /// Amount streamed to the recipient so far, withdrawn or not
+    pub fn accrued_amount(&self, now: i64) -> u64 {
+        if now <= self.start_timestamp {
+            return 0;
+        }
+        let accrued = (now - self.start_timestamp) as u128 * self.rate_per_second as u128;
+        accrued.min(self.total_amount as u128) as u64
+    }
+
+    /// Amount the recipient can pull right now
+    pub fn withdrawable_amount(&self, now: i64) -> u64 {
+        self.accrued_amount(now)
+            .saturating_sub(self.withdrawn_amount)
+    }
+
+    /// Stop the stream, returns the unaccrued amount to send back to the source
+    pub fn cancel(&mut self, now: i64) -> u64 {
+        let accrued = self.accrued_amount(now);
+        let unaccrued = self.total_amount - accrued;
+        self.total_amount = accrued;
+        self.canceled_on = now;
+        unaccrued
+    }
+}
+
+impl Pack for Stream {
+    const LEN: usize = STREAM_TOTAL_BYTES;
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, STREAM_TOTAL_BYTES];
+
+        let (
+            is_initialized_dst,
+            squad_address_dst,
+            proposal_address_dst,
+            recipient_dst,
+            source_dst,
+            mint_dst,
+            escrow_dst,
+            start_timestamp_dst,
+            rate_per_second_dst,
+            total_amount_dst,
+            withdrawn_amount_dst,
+            canceled_on_dst,
+            _reserved,
+        ) = mut_array_refs![
+            dst,
+            STREAM_INITIALIZED_BYTES, // is_initialized 1
+            PUBLIC_KEY_BYTES,         // squad address 32
+            PUBLIC_KEY_BYTES,         // proposal address 32
+            PUBLIC_KEY_BYTES,         // recipient 32
+            PUBLIC_KEY_BYTES,         // source 32
+            PUBLIC_KEY_BYTES,         // mint 32
+            PUBLIC_KEY_BYTES,         // escrow 32
+            TIMESTAMP_BYTES,          // start timestamp 8
+            AMOUNT_BYTES,             // rate per second 8
+            AMOUNT_BYTES,             // total amount 8
+            AMOUNT_BYTES,             // withdrawn amount 8
+            TIMESTAMP_BYTES,          // canceled on 8
+            STREAM_RESERVED_BYTES
+        ];
+
+        let Stream {
+            is_initialized,
+            squad_address,
+            proposal_address,
+            recipient,
+            source,
+            mint,
+            escrow,
+            start_timestamp,
+            rate_per_second,
+            total_amount,
+            withdrawn_amount,
+            canceled_on,
+            reserved: _,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        *squad_address_dst = squad_address.to_bytes();
+        *proposal_address_dst = proposal_address.to_bytes();
+        *recipient_dst = recipient.to_bytes();
+        *source_dst = source.to_bytes();
+        *mint_dst = mint.to_bytes();
+        *escrow_dst = escrow.to_bytes();
+        *start_timestamp_dst = start_timestamp.to_le_bytes();
+        *rate_per_second_dst = rate_per_second.to_le_bytes();
+        *total_amount_dst = total_amount.to_le_bytes();
+        *withdrawn_amount_dst = withdrawn_amount.to_le_bytes();
+        *canceled_on_dst = canceled_on.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, STREAM_TOTAL_BYTES];
+        let (
+            is_initialized,
+            squad_address_src,
+            proposal_address_src,
+            recipient_src,
+            source_src,
+            mint_src,
+            escrow_src,
+            start_timestamp_src,
+            rate_per_second_src,
+            total_amount_src,
+            withdrawn_amount_src,
+            canceled_on_src,
+            _reserved,
+        ) = array_refs![
+            src,
+            STREAM_INITIALIZED_BYTES,
+            PUBLIC_KEY_BYTES,
+            PUBLIC_KEY_BYTES,
+            PUBLIC_KEY_BYTES,
+            PUBLIC_KEY_BYTES,
+            PUBLIC_KEY_BYTES,
+            PUBLIC_KEY_BYTES,
+            TIMESTAMP_BYTES,
+            AMOUNT_BYTES,
+            AMOUNT_BYTES,
+            AMOUNT_BYTES,
+            TIMESTAMP_BYTES,
+            STREAM_RESERVED_BYTES
+        ];
+
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(Stream {
+            is_initialized,
+            squad_address: Pubkey::new(squad_address_src),
+            proposal_address: Pubkey::new(proposal_address_src),
+            recipient: Pubkey::new(recipient_src),
+            source: Pubkey::new(source_src),
+            mint: Pubkey::new(mint_src),
+            escrow: Pubkey::new(escrow_src),
+            start_timestamp: i64::from_le_bytes(*start_timestamp_src),
+            rate_per_second: u64::from_le_bytes(*rate_per_second_src),
+            total_amount: u64::from_le_bytes(*total_amount_src),
+            withdrawn_amount: u64::from_le_bytes(*withdrawn_amount_src),
+            canceled_on: i64::from_le_bytes(*canceled_on_src),
+            reserved: [0; 8],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_accrual() {
+        let mut stream = Stream::unpack_unchecked(&[0; STREAM_TOTAL_BYTES]).unwrap();
+        stream.save_stream(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::default(),
+            &Pubkey::new_unique(),
+            1_000,
+            10,
+            5_000,
+        );
+        assert!(stream.is_sol());
+
+        assert_eq!(stream.accrued_amount(900), 0);
+        assert_eq!(stream.accrued_amount(1_100), 1_000);
+        assert_eq!(stream.accrued_amount(2_000), 5_000);
+
+        stream.withdrawn_amount = 1_000;
+        assert_eq!(stream.withdrawable_amount(1_200), 1_000);
+
+        assert_eq!(stream.cancel(1_300), 2_000);
+        assert_eq!(stream.withdrawable_amount(9_000), 2_000);
+    }
+}
@@ -0,0 +1,327 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use crate::UnixTimestamp;
+
+const VESTING_INITIALIZED_BYTES: usize = 1;
+const PUBLIC_KEY_BYTES: usize = 32;
+const TIMESTAMP_BYTES: usize = 8;
+const AMOUNT_BYTES: usize = 8;
+const VESTING_RESERVED_BYTES: usize = 8 * 8;
+
+const VESTING_TOTAL_BYTES: usize = VESTING_INITIALIZED_BYTES + // is_initialized 1
+    PUBLIC_KEY_BYTES +                  // squad address 32
+    PUBLIC_KEY_BYTES +                  // member address 32
+    PUBLIC_KEY_BYTES +                  // proposal address 32
+    TIMESTAMP_BYTES +                   // start timestamp 8
+    TIMESTAMP_BYTES +                   // cliff timestamp 8
+    TIMESTAMP_BYTES +                   // duration 8
+    AMOUNT_BYTES +                      // total amount 8
+    AMOUNT_BYTES +                      // claimed amount 8
+    TIMESTAMP_BYTES +                   // revoked on 8
+    VESTING_RESERVED_BYTES; // reserved for updates
+
+/// Equity grant for a member of a Team squad, minted from the squad mint as it vests
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct Vesting {
+    pub is_initialized: bool,
+    pub squad_address: Pubkey,
+    pub member: Pubkey,
+    // the proposal that created the grant
+    pub proposal_address: Pubkey,
+    pub start_timestamp: UnixTimestamp,
+    // nothing can be claimed before the cliff
+    pub cliff_timestamp: UnixTimestamp,
+    // seconds from start until the grant is fully vested
+    pub duration: i64,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    // 0 unless the unvested part of the grant was revoked
+    pub revoked_on: UnixTimestamp,
+
+    // reserved for future updates
+    pub reserved: [u64; 8],
+}
+
+impl Sealed for Vesting {}
+
+impl IsInitialized for Vesting {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Vesting {
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_grant(
+        &mut self,
+        squad_address: &Pubkey,
+        member: &Pubkey,
+        proposal_address: &Pubkey,
+        start_timestamp: i64,
+        cliff: i64,
+        duration: i64,
+        total_amount: u64,
+    ) -> Result<(), ProgramError> {
+        // a cliff past the end of time would wrap around to the past
+        self.cliff_timestamp = start_timestamp
+            .checked_add(cliff)
+            .ok_or(ProgramError::InvalidArgument)?;
+        self.is_initialized = true;
+        self.squad_address = *squad_address;
+        self.member = *member;
+        self.proposal_address = *proposal_address;
+        self.start_timestamp = start_timestamp;
+        self.duration = duration;
+        self.total_amount = total_amount;
+        self.claimed_amount = 0;
+        self.revoked_on = 0;
+        Ok(())
+    }
+
+    /// Amount of the grant vested at the given time, claimed or not
+    pub fn vested_amount(&self, now: i64) -> u64 {
+        // a revoked grant was cut down to what had vested, all of it stays vested
+        if self.revoked_on != 0 {
+            return self.total_amount;
+        }
+        if now < self.cliff_timestamp || now < self.start_timestamp {
+            return 0;
+        }
+        let elapsed = now - self.start_timestamp;
+        if self.duration <= 0 || elapsed >= self.duration {
+            return self.total_amount;
+        }
+        (self.total_amount as u128 * elapsed as u128 / self.duration as u128) as u64
+    }
+
+    /// Amount that has vested but has not been minted to the member yet
+    pub fn claimable_amount(&self, now: i64) -> u64 {
+        self.vested_amount(now).saturating_sub(self.claimed_amount)
+    }
+
+    /// Cut the grant down to what has vested so far
+    pub fn revoke(&mut self, now: i64) {
+        self.total_amount = self.vested_amount(now);
+        self.revoked_on = now;
+    }
+}
+
+impl Pack for Vesting {
+    const LEN: usize = VESTING_TOTAL_BYTES;
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, VESTING_TOTAL_BYTES];
+
+        let (
+            is_initialized_dst,
+            squad_address_dst,
+            member_dst,
+            proposal_address_dst,
+            start_timestamp_dst,
+            cliff_timestamp_dst,
+            duration_dst,
+            total_amount_dst,
+            claimed_amount_dst,
+            revoked_on_dst,
+            _reserved,
+        ) = mut_array_refs![
+            dst,
+            VESTING_INITIALIZED_BYTES, // is_initialized 1
+            PUBLIC_KEY_BYTES,          // squad address 32
+            PUBLIC_KEY_BYTES,          // member address 32
+            PUBLIC_KEY_BYTES,          // proposal address 32
+            TIMESTAMP_BYTES,           // start timestamp 8
+            TIMESTAMP_BYTES,           // cliff timestamp 8
+            TIMESTAMP_BYTES,           // duration 8
+            AMOUNT_BYTES,              // total amount 8
+            AMOUNT_BYTES,              // claimed amount 8
+            TIMESTAMP_BYTES,           // revoked on 8
+            VESTING_RESERVED_BYTES
+        ];
+
+        let Vesting {
+            is_initialized,
+            squad_address,
+            member,
+            proposal_address,
+            start_timestamp,
+            cliff_timestamp,
+            duration,
+            total_amount,
+            claimed_amount,
+            revoked_on,
+            reserved: _,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        *squad_address_dst = squad_address.to_bytes();
+        *member_dst = member.to_bytes();
+        *proposal_address_dst = proposal_address.to_bytes();
+        *start_timestamp_dst = start_timestamp.to_le_bytes();
+        *cliff_timestamp_dst = cliff_timestamp.to_le_bytes();
+        *duration_dst = duration.to_le_bytes();
+        *total_amount_dst = total_amount.to_le_bytes();
+        *claimed_amount_dst = claimed_amount.to_le_bytes();
+        *revoked_on_dst = revoked_on.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, VESTING_TOTAL_BYTES];
+        let (
+            is_initialized,
+            squad_address_src,
+            member_src,
+            proposal_address_src,
+            start_timestamp_src,
+            cliff_timestamp_src,
+            duration_src,
+            total_amount_src,
+            claimed_amount_src,
+            revoked_on_src,
+            _reserved,
+        ) = array_refs![
+            src,
+            VESTING_INITIALIZED_BYTES,
+            PUBLIC_KEY_BYTES,
+            PUBLIC_KEY_BYTES,
+            PUBLIC_KEY_BYTES,
+            TIMESTAMP_BYTES,
+            TIMESTAMP_BYTES,
+            TIMESTAMP_BYTES,
+            AMOUNT_BYTES,
+            AMOUNT_BYTES,
+            TIMESTAMP_BYTES,
+            VESTING_RESERVED_BYTES
+        ];
+
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(Vesting {
+            is_initialized,
+            squad_address: Pubkey::new(squad_address_src),
+            member: Pubkey::new(member_src),
+            proposal_address: Pubkey::new(proposal_address_src),
+            start_timestamp: i64::from_le_bytes(*start_timestamp_src),
+            cliff_timestamp: i64::from_le_bytes(*cliff_timestamp_src),
+            duration: i64::from_le_bytes(*duration_src),
+            total_amount: u64::from_le_bytes(*total_amount_src),
+            claimed_amount: u64::from_le_bytes(*claimed_amount_src),
+            revoked_on: i64::from_le_bytes(*revoked_on_src),
+            reserved: [0; 8],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vesting_schedule() {
+        let mut grant = Vesting::unpack_unchecked(&[0; VESTING_TOTAL_BYTES]).unwrap();
+        grant
+            .save_grant(
+                &Pubkey::new_unique(),
+                &Pubkey::new_unique(),
+                &Pubkey::new_unique(),
+                1_000,
+                100,
+                1_000,
+                10_000,
+            )
+            .unwrap();
+
+        assert_eq!(grant.vested_amount(999), 0);
+        assert_eq!(grant.vested_amount(1_099), 0);
+        assert_eq!(grant.vested_amount(1_100), 1_000);
+        assert_eq!(grant.vested_amount(1_500), 5_000);
+        assert_eq!(grant.vested_amount(2_000), 10_000);
+        assert_eq!(grant.vested_amount(5_000), 10_000);
+
+        grant.claimed_amount = 1_000;
+        assert_eq!(grant.claimable_amount(1_500), 4_000);
+
+        grant.revoke(1_500);
+        assert_eq!(grant.total_amount, 5_000);
+        assert_eq!(grant.claimable_amount(5_000), 4_000);
+        // the schedule no longer applies, claiming right after the revoke gets the rest
+        assert_eq!(grant.vested_amount(1_600), 5_000);
+        assert_eq!(grant.claimable_amount(1_600), 4_000);
+
+        let mut dst = [0; VESTING_TOTAL_BYTES];
+        Vesting::pack(grant, &mut dst).unwrap();
+        let grant = Vesting::unpack(&dst).unwrap();
+        assert_eq!(grant.revoked_on, 1_500);
+    }
+
+    #[test]
+    fn vesting_claim_after_revoke() {
+        let mut grant = Vesting::unpack_unchecked(&[0; VESTING_TOTAL_BYTES]).unwrap();
+        grant
+            .save_grant(
+                &Pubkey::new_unique(),
+                &Pubkey::new_unique(),
+                &Pubkey::new_unique(),
+                1_000,
+                0,
+                1_000,
+                10_000,
+            )
+            .unwrap();
+
+        // claim what vested, revoke, then claim again the same second and later
+        grant.claimed_amount = grant.claimable_amount(1_200);
+        assert_eq!(grant.claimed_amount, 2_000);
+        grant.revoke(1_400);
+        assert_eq!(grant.total_amount, 4_000);
+        assert_eq!(grant.claimable_amount(1_400), 2_000);
+        assert_eq!(grant.claimable_amount(1_401), 2_000);
+        grant.claimed_amount += grant.claimable_amount(1_401);
+        assert_eq!(grant.claimed_amount, 4_000);
+        assert_eq!(grant.claimable_amount(10_000), 0);
+
+        // a grant revoked before its cliff has nothing to claim
+        let mut grant = Vesting::unpack_unchecked(&[0; VESTING_TOTAL_BYTES]).unwrap();
+        grant
+            .save_grant(
+                &Pubkey::new_unique(),
+                &Pubkey::new_unique(),
+                &Pubkey::new_unique(),
+                1_000,
+                500,
+                1_000,
+                10_000,
+            )
+            .unwrap();
+        grant.revoke(1_200);
+        assert_eq!(grant.total_amount, 0);
+        assert_eq!(grant.claimable_amount(5_000), 0);
+    }
+
+    #[test]
+    fn vesting_cliff_overflow() {
+        let mut grant = Vesting::unpack_unchecked(&[0; VESTING_TOTAL_BYTES]).unwrap();
+        assert_eq!(
+            grant.save_grant(
+                &Pubkey::new_unique(),
+                &Pubkey::new_unique(),
+                &Pubkey::new_unique(),
+                1_000,
+                i64::MAX,
+                i64::MAX,
+                10_000,
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert!(!grant.is_initialized);
+    }
+}
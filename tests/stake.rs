@@ -0,0 +1,372 @@
+#![cfg(feature = "test-bpf")]
+
+use {
+    solana_program::{
+        borsh::try_from_slice_unchecked,
+        instruction::{AccountMeta, Instruction},
+        program_pack::Pack,
+        pubkey::Pubkey,
+        stake::{self, state::StakeState},
+        system_program, sysvar,
+    },
+    solana_program_test::{processor, tokio, BanksClient, ProgramTest},
+    solana_sdk::{
+        account::Account,
+        hash::Hash,
+        native_token::LAMPORTS_PER_SOL,
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    },
+    solana_vote_program::vote_state,
+    squads_program::{
+        get_stake_address, get_vault_address,
+        processor::process,
+        state::{
+            proposal::{Proposal, ProposalType},
+            squad::{AllocationType, Member, Squad, MEMBER_ROLE_ALL},
+        },
+    },
+};
+
+// a multisig squad whose only member can execute its proposals
+fn squad_account(member: &Pubkey, program_id: &Pubkey) -> Account {
+    let mut data = vec![0; Squad::get_packed_len()];
+    let mut squad = Squad::unpack_unchecked(&data).unwrap();
+    squad.is_initialized = true;
+    squad.allocation_type = AllocationType::Multisig as u8;
+    squad.vote_quorum = 1;
+    squad.members.insert(
+        *member,
+        Member {
+            equity_token_account: Pubkey::default(),
+            role: MEMBER_ROLE_ALL,
+            joined_version: 0,
+        },
+    );
+    Squad::pack(squad, &mut data).unwrap();
+    Account {
+        lamports: LAMPORTS_PER_SOL,
+        data,
+        owner: *program_id,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// a proposal of the squad that already reached its threshold
+fn approved_proposal(
+    squad: &Pubkey,
+    proposal_type: ProposalType,
+    source: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+    program_id: &Pubkey,
+) -> Account {
+    let mut data = vec![0; Proposal::get_packed_len()];
+    let mut proposal = Proposal::unpack_unchecked(&data).unwrap();
+    proposal.is_initialized = true;
+    proposal.proposal_type = proposal_type as u8;
+    proposal.squad_address = *squad;
+    proposal.execution_source = *source;
+    proposal.execution_destination = *destination;
+    proposal.execution_amount = amount;
+    proposal.votes_num = 2;
+    proposal.votes = vec![1, 0];
+    proposal.close_timestamp = i64::MAX;
+    proposal.threshold_at_execute = 1;
+    proposal.approve(0).unwrap();
+    Proposal::pack(proposal, &mut data).unwrap();
+    Account {
+        lamports: LAMPORTS_PER_SOL,
+        data,
+        owner: *program_id,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+async fn execute(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    member: &Keypair,
+    recent_blockhash: Hash,
+    accounts: [&Pubkey; 4],
+    program_id: &Pubkey,
+) {
+    let [squad, proposal, source, destination] = accounts;
+    let vault = get_vault_address(squad, 0, program_id);
+    let instruction = Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(member.pubkey(), true),
+            AccountMeta::new(*squad, false),
+            AccountMeta::new(*proposal, false),
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(stake::program::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(sysvar::stake_history::id(), false),
+            AccountMeta::new_readonly(stake::config::id(), false),
+        ],
+        // ExecuteMultisigProposal
+        data: vec![6],
+    };
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    transaction.sign(&[payer, member], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_vault_stake_proposals() {
+    let program_id = Pubkey::new_unique();
+    let member = Keypair::new();
+    let squad = Pubkey::new_unique();
+    let vault = get_vault_address(&squad, 0, &program_id);
+
+    let create_proposal = Pubkey::new_unique();
+    let split_proposal = Pubkey::new_unique();
+    let merge_proposal = Pubkey::new_unique();
+    let withdraw_proposal = Pubkey::new_unique();
+    let stake_account = get_stake_address(&create_proposal, &program_id);
+    let split_account = get_stake_address(&split_proposal, &program_id);
+
+    let mut program_test = ProgramTest::new("squads_program", program_id, processor!(process));
+    program_test.add_account(squad, squad_account(&member.pubkey(), &program_id));
+    program_test.add_account(
+        vault,
+        Account::new(10 * LAMPORTS_PER_SOL, 0, &system_program::id()),
+    );
+    program_test.add_account(
+        create_proposal,
+        approved_proposal(
+            &squad,
+            ProposalType::CreateStake,
+            &vault,
+            &stake_account,
+            5 * LAMPORTS_PER_SOL,
+            &program_id,
+        ),
+    );
+    program_test.add_account(
+        split_proposal,
+        approved_proposal(
+            &squad,
+            ProposalType::SplitStake,
+            &stake_account,
+            &split_account,
+            2 * LAMPORTS_PER_SOL,
+            &program_id,
+        ),
+    );
+    program_test.add_account(
+        merge_proposal,
+        approved_proposal(
+            &squad,
+            ProposalType::MergeStake,
+            &split_account,
+            &stake_account,
+            0,
+            &program_id,
+        ),
+    );
+    program_test.add_account(
+        withdraw_proposal,
+        approved_proposal(
+            &squad,
+            ProposalType::WithdrawStake,
+            &stake_account,
+            &vault,
+            LAMPORTS_PER_SOL,
+            &program_id,
+        ),
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // the vault funds a stake account it is the staker and withdrawer of
+    execute(
+        &mut banks_client,
+        &payer,
+        &member,
+        recent_blockhash,
+        [&squad, &create_proposal, &vault, &stake_account],
+        &program_id,
+    )
+    .await;
+    let stake = banks_client
+        .get_account(stake_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stake.owner, stake::program::id());
+    assert_eq!(stake.lamports, 5 * LAMPORTS_PER_SOL);
+
+    execute(
+        &mut banks_client,
+        &payer,
+        &member,
+        recent_blockhash,
+        [&squad, &split_proposal, &stake_account, &split_account],
+        &program_id,
+    )
+    .await;
+    let split = banks_client
+        .get_account(split_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(split.lamports, 2 * LAMPORTS_PER_SOL);
+
+    execute(
+        &mut banks_client,
+        &payer,
+        &member,
+        recent_blockhash,
+        [&squad, &merge_proposal, &split_account, &stake_account],
+        &program_id,
+    )
+    .await;
+    let stake = banks_client
+        .get_account(stake_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stake.lamports, 5 * LAMPORTS_PER_SOL);
+
+    // undelegated lamports can be withdrawn right away
+    execute(
+        &mut banks_client,
+        &payer,
+        &member,
+        recent_blockhash,
+        [&squad, &withdraw_proposal, &stake_account, &vault],
+        &program_id,
+    )
+    .await;
+    let stake = banks_client
+        .get_account(stake_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stake.lamports, 4 * LAMPORTS_PER_SOL);
+    let vault_account = banks_client.get_account(vault).await.unwrap().unwrap();
+    assert_eq!(vault_account.lamports, 6 * LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn test_vault_stake_delegation() {
+    let program_id = Pubkey::new_unique();
+    let member = Keypair::new();
+    let squad = Pubkey::new_unique();
+    let vault = get_vault_address(&squad, 0, &program_id);
+    let vote = Pubkey::new_unique();
+
+    let create_proposal = Pubkey::new_unique();
+    let delegate_proposal = Pubkey::new_unique();
+    let deactivate_proposal = Pubkey::new_unique();
+    let stake_account = get_stake_address(&create_proposal, &program_id);
+
+    let mut program_test = ProgramTest::new("squads_program", program_id, processor!(process));
+    program_test.add_account(squad, squad_account(&member.pubkey(), &program_id));
+    program_test.add_account(
+        vault,
+        Account::new(10 * LAMPORTS_PER_SOL, 0, &system_program::id()),
+    );
+    program_test.add_account(
+        vote,
+        vote_state::create_account(&vote, &Pubkey::new_unique(), 0, LAMPORTS_PER_SOL).into(),
+    );
+    program_test.add_account(
+        create_proposal,
+        approved_proposal(
+            &squad,
+            ProposalType::CreateStake,
+            &vault,
+            &stake_account,
+            5 * LAMPORTS_PER_SOL,
+            &program_id,
+        ),
+    );
+    program_test.add_account(
+        delegate_proposal,
+        approved_proposal(
+            &squad,
+            ProposalType::DelegateStake,
+            &stake_account,
+            &vote,
+            0,
+            &program_id,
+        ),
+    );
+    program_test.add_account(
+        deactivate_proposal,
+        approved_proposal(
+            &squad,
+            ProposalType::DeactivateStake,
+            &stake_account,
+            &vault,
+            0,
+            &program_id,
+        ),
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    execute(
+        &mut banks_client,
+        &payer,
+        &member,
+        recent_blockhash,
+        [&squad, &create_proposal, &vault, &stake_account],
+        &program_id,
+    )
+    .await;
+
+    // the vault signs as staker to delegate to the vote account
+    execute(
+        &mut banks_client,
+        &payer,
+        &member,
+        recent_blockhash,
+        [&squad, &delegate_proposal, &stake_account, &vote],
+        &program_id,
+    )
+    .await;
+    let stake = banks_client
+        .get_account(stake_account)
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation = try_from_slice_unchecked::<StakeState>(&stake.data)
+        .unwrap()
+        .delegation()
+        .unwrap();
+    assert_eq!(delegation.voter_pubkey, vote);
+    assert_eq!(delegation.deactivation_epoch, u64::MAX);
+
+    // deactivating keeps the delegation and records the epoch it cools down from
+    execute(
+        &mut banks_client,
+        &payer,
+        &member,
+        recent_blockhash,
+        [&squad, &deactivate_proposal, &stake_account, &vault],
+        &program_id,
+    )
+    .await;
+    let stake = banks_client
+        .get_account(stake_account)
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation = try_from_slice_unchecked::<StakeState>(&stake.data)
+        .unwrap()
+        .delegation()
+        .unwrap();
+    assert_eq!(delegation.voter_pubkey, vote);
+    assert_eq!(delegation.deactivation_epoch, delegation.activation_epoch);
+    assert_eq!(stake.lamports, 5 * LAMPORTS_PER_SOL);
+}